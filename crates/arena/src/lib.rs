@@ -49,15 +49,13 @@ impl<T> Arena<T> {
         self.data.shrink_to_fit();
     }
 
-    pub fn iter(
-        &self,
-    ) -> impl Iterator<Item = (Id<T>, &T)> + ExactSizeIterator + DoubleEndedIterator {
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Id<T>, &T)> + DoubleEndedIterator {
         self.data.iter().enumerate().map(|(raw, value)| (Id::from_raw(raw as u32), value))
     }
 
     pub fn iter_mut(
         &mut self,
-    ) -> impl Iterator<Item = (Id<T>, &mut T)> + ExactSizeIterator + DoubleEndedIterator {
+    ) -> impl ExactSizeIterator<Item = (Id<T>, &mut T)> + DoubleEndedIterator {
         self.data.iter_mut().enumerate().map(|(raw, value)| (Id::from_raw(raw as u32), value))
     }
 }
@@ -71,13 +69,13 @@ impl<T> Default for Arena<T> {
 impl<T> Index<Id<T>> for Arena<T> {
     type Output = T;
     fn index(&self, id: Id<T>) -> &T {
-        &self.data[(id.to_raw() as usize)]
+        &self.data[id.to_raw() as usize]
     }
 }
 
 impl<T> IndexMut<Id<T>> for Arena<T> {
     fn index_mut(&mut self, id: Id<T>) -> &mut T {
-        &mut self.data[(id.to_raw() as usize)]
+        &mut self.data[id.to_raw() as usize]
     }
 }
 
@@ -93,6 +93,9 @@ impl Root {
 pub enum Def {
     Function(Function),
     Record(Record),
+    Test(Test),
+    Interface(Interface),
+    Impl(Impl),
 }
 
 impl AstNode for Def {
@@ -100,6 +103,9 @@ impl AstNode for Def {
         match node.kind(tree) {
             NodeKind::Function => Some(Self::Function(Function(node))),
             NodeKind::Record => Some(Self::Record(Record(node))),
+            NodeKind::Test => Some(Self::Test(Test(node))),
+            NodeKind::Interface => Some(Self::Interface(Interface(node))),
+            NodeKind::Impl => Some(Self::Impl(Impl(node))),
             _ => None,
         }
     }
@@ -108,6 +114,9 @@ impl AstNode for Def {
         match self {
             Self::Function(function) => function.syntax(),
             Self::Record(record) => record.syntax(),
+            Self::Test(test) => test.syntax(),
+            Self::Interface(interface) => interface.syntax(),
+            Self::Impl(imp) => imp.syntax(),
         }
     }
 }
@@ -119,10 +128,18 @@ impl Function {
         node(self, tree)
     }
 
+    pub fn ext_kw(self, tree: &SyntaxTree) -> Option<ExtKw> {
+        token(self, tree)
+    }
+
     pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
         token(self, tree)
     }
 
+    pub fn generic_param_list(self, tree: &SyntaxTree) -> Option<GenericParamList> {
+        node(self, tree)
+    }
+
     pub fn param_list(self, tree: &SyntaxTree) -> Option<ParamList> {
         node(self, tree)
     }
@@ -131,6 +148,14 @@ impl Function {
         node(self, tree)
     }
 
+    pub fn requires(self, tree: &SyntaxTree) -> impl Iterator<Item = Requires> + '_ {
+        nodes(self, tree)
+    }
+
+    pub fn ensures(self, tree: &SyntaxTree) -> impl Iterator<Item = Ensures> + '_ {
+        nodes(self, tree)
+    }
+
     pub fn body(self, tree: &SyntaxTree) -> Option<Expr> {
         node(self, tree)
     }
@@ -152,6 +177,86 @@ impl Record {
     }
 }
 
+def_ast_node!(Interface);
+
+impl Interface {
+    pub fn docs(self, tree: &SyntaxTree) -> Option<Docs> {
+        node(self, tree)
+    }
+
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+
+    pub fn functions(self, tree: &SyntaxTree) -> impl Iterator<Item = Function> + '_ {
+        nodes(self, tree)
+    }
+}
+
+def_ast_node!(Impl);
+
+impl Impl {
+    pub fn docs(self, tree: &SyntaxTree) -> Option<Docs> {
+        node(self, tree)
+    }
+
+    pub fn interface(self, tree: &SyntaxTree) -> Option<Path> {
+        node(self, tree)
+    }
+
+    /// The type the interface is implemented for.
+    pub fn ty(self, tree: &SyntaxTree) -> Option<Ty> {
+        node(self, tree)
+    }
+
+    pub fn functions(self, tree: &SyntaxTree) -> impl Iterator<Item = Function> + '_ {
+        nodes(self, tree)
+    }
+}
+
+def_ast_node!(Test);
+
+impl Test {
+    pub fn docs(self, tree: &SyntaxTree) -> Option<Docs> {
+        node(self, tree)
+    }
+
+    pub fn name(self, tree: &SyntaxTree) -> Option<StringLiteral> {
+        match self.name_and_body(tree).0 {
+            Some(Expr::StringLiteral(string_literal)) => Some(string_literal),
+            _ => None,
+        }
+    }
+
+    pub fn body(self, tree: &SyntaxTree) -> Option<Expr> {
+        self.name_and_body(tree).1
+    }
+
+    // the name and the body are both expressions,
+    // so we tell them apart by which side of the arrow they’re on
+    fn name_and_body(self, tree: &SyntaxTree) -> (Option<Expr>, Option<Expr>) {
+        let arrow = self.0.child_tokens(tree).find(|token| token.kind(tree) == TokenKind::Arrow);
+
+        let mut name = None;
+        let mut body = None;
+
+        for expr in nodes::<_, Expr>(self, tree) {
+            let is_before_arrow = match arrow {
+                Some(arrow) => expr.range(tree).end() <= arrow.range(tree).start(),
+                None => name.is_none(),
+            };
+
+            if is_before_arrow {
+                name = Some(expr);
+            } else {
+                body = Some(expr);
+            }
+        }
+
+        (name, body)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Statement {
     LocalDef(LocalDef),
@@ -182,11 +287,113 @@ impl LocalDef {
         token(self, tree)
     }
 
+    /// The pattern being bound, if the local definition isn’t of a plain name.
+    pub fn pattern(self, tree: &SyntaxTree) -> Option<Pattern> {
+        node(self, tree)
+    }
+
+    pub fn ty(self, tree: &SyntaxTree) -> Option<Ty> {
+        node(self, tree)
+    }
+
     pub fn value(self, tree: &SyntaxTree) -> Option<Expr> {
         node(self, tree)
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pattern {
+    Ident(IdentPat),
+    Wildcard(WildcardPat),
+    Record(RecordPat),
+    Tuple(TuplePat),
+}
+
+impl AstNode for Pattern {
+    fn cast(node: SyntaxNode, tree: &SyntaxTree) -> Option<Self> {
+        match node.kind(tree) {
+            NodeKind::IdentPat => Some(Self::Ident(IdentPat(node))),
+            NodeKind::WildcardPat => Some(Self::Wildcard(WildcardPat(node))),
+            NodeKind::RecordPat => Some(Self::Record(RecordPat(node))),
+            NodeKind::TuplePat => Some(Self::Tuple(TuplePat(node))),
+            _ => None,
+        }
+    }
+
+    fn syntax(self) -> SyntaxNode {
+        match self {
+            Self::Ident(ident_pat) => ident_pat.syntax(),
+            Self::Wildcard(wildcard_pat) => wildcard_pat.syntax(),
+            Self::Record(record_pat) => record_pat.syntax(),
+            Self::Tuple(tuple_pat) => tuple_pat.syntax(),
+        }
+    }
+}
+
+def_ast_node!(IdentPat);
+
+impl IdentPat {
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+}
+
+def_ast_node!(WildcardPat);
+
+def_ast_node!(RecordPat);
+
+impl RecordPat {
+    pub fn path(self, tree: &SyntaxTree) -> Option<Path> {
+        node(self, tree)
+    }
+
+    pub fn fields(self, tree: &SyntaxTree) -> impl Iterator<Item = RecordPatField> + '_ {
+        nodes(self, tree)
+    }
+}
+
+def_ast_node!(RecordPatField);
+
+impl RecordPatField {
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+
+    /// Missing in the shorthand `x`, which binds the field to a local of the same name.
+    pub fn pattern(self, tree: &SyntaxTree) -> Option<Pattern> {
+        node(self, tree)
+    }
+}
+
+def_ast_node!(TuplePat);
+
+impl TuplePat {
+    pub fn elements(self, tree: &SyntaxTree) -> impl Iterator<Item = Pattern> + '_ {
+        nodes(self, tree)
+    }
+}
+
+def_ast_node!(GenericParamList);
+
+impl GenericParamList {
+    pub fn params(self, tree: &SyntaxTree) -> impl Iterator<Item = GenericParam> + '_ {
+        nodes(self, tree)
+    }
+}
+
+def_ast_node!(GenericParam);
+
+impl GenericParam {
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+
+    /// The interfaces the type the parameter stands for has to implement.
+    pub fn bounds(self, tree: &SyntaxTree) -> impl Iterator<Item = Path> + '_ {
+        nodes(self, tree)
+    }
+}
+
 def_ast_node!(ParamList);
 
 impl ParamList {
@@ -203,6 +410,27 @@ impl ReturnTy {
     }
 }
 
+def_ast_node!(Requires);
+
+impl Requires {
+    pub fn condition(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+}
+
+def_ast_node!(Ensures);
+
+impl Ensures {
+    /// The name the condition refers to the return value by, if any.
+    pub fn result_name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+
+    pub fn condition(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+}
+
 def_ast_node!(Param);
 
 impl Param {
@@ -213,6 +441,10 @@ impl Param {
     pub fn ty(self, tree: &SyntaxTree) -> Option<Ty> {
         node(self, tree)
     }
+
+    pub fn default_value(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
 }
 
 def_ast_node!(Field);
@@ -233,6 +465,24 @@ impl Ty {
     pub fn path(self, tree: &SyntaxTree) -> Option<Path> {
         node(self, tree)
     }
+
+    /// Present on tuple types like `(s32, string)`, which have no path.
+    pub fn tuple(self, tree: &SyntaxTree) -> Option<TupleTy> {
+        node(self, tree)
+    }
+
+    /// One for each level of optional, as in `s32?`.
+    pub fn questions(self, tree: &SyntaxTree) -> impl Iterator<Item = Question> + '_ {
+        tokens(self, tree)
+    }
+}
+
+def_ast_node!(TupleTy);
+
+impl TupleTy {
+    pub fn elements(self, tree: &SyntaxTree) -> impl Iterator<Item = Ty> + '_ {
+        nodes(self, tree)
+    }
 }
 
 def_ast_node!(Docs);
@@ -262,6 +512,10 @@ impl ExprStatement {
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Expr {
     Binary(BinaryExpr),
+    Paren(ParenExpr),
+    Tuple(TupleExpr),
+    Try(TryExpr),
+    TupleField(TupleFieldExpr),
     Block(Block),
     Call(Call),
     IntLiteral(IntLiteral),
@@ -272,6 +526,10 @@ impl AstNode for Expr {
     fn cast(node: SyntaxNode, tree: &SyntaxTree) -> Option<Self> {
         match node.kind(tree) {
             NodeKind::BinaryExpr => Some(Self::Binary(BinaryExpr(node))),
+            NodeKind::ParenExpr => Some(Self::Paren(ParenExpr(node))),
+            NodeKind::TupleExpr => Some(Self::Tuple(TupleExpr(node))),
+            NodeKind::TryExpr => Some(Self::Try(TryExpr(node))),
+            NodeKind::TupleFieldExpr => Some(Self::TupleField(TupleFieldExpr(node))),
             NodeKind::Block => Some(Self::Block(Block(node))),
             NodeKind::Call => Some(Self::Call(Call(node))),
             NodeKind::IntLiteral => Some(Self::IntLiteral(IntLiteral(node))),
@@ -283,6 +541,10 @@ impl AstNode for Expr {
     fn syntax(self) -> SyntaxNode {
         match self {
             Self::Binary(binary_expr) => binary_expr.syntax(),
+            Self::Paren(paren_expr) => paren_expr.syntax(),
+            Self::Tuple(tuple_expr) => tuple_expr.syntax(),
+            Self::Try(try_expr) => try_expr.syntax(),
+            Self::TupleField(tuple_field_expr) => tuple_field_expr.syntax(),
             Self::Block(block) => block.syntax(),
             Self::Call(call) => call.syntax(),
             Self::IntLiteral(int_literal) => int_literal.syntax(),
@@ -307,6 +569,43 @@ impl BinaryExpr {
     }
 }
 
+def_ast_node!(ParenExpr);
+
+impl ParenExpr {
+    pub fn inner(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+}
+
+def_ast_node!(TupleExpr);
+
+impl TupleExpr {
+    pub fn elements(self, tree: &SyntaxTree) -> impl Iterator<Item = Expr> + '_ {
+        nodes(self, tree)
+    }
+}
+
+def_ast_node!(TryExpr);
+
+impl TryExpr {
+    pub fn inner(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+}
+
+def_ast_node!(TupleFieldExpr);
+
+impl TupleFieldExpr {
+    pub fn base(self, tree: &SyntaxTree) -> Option<Expr> {
+        node(self, tree)
+    }
+
+    /// The position of the field, like the `0` in `pair.0`.
+    pub fn idx(self, tree: &SyntaxTree) -> Option<Int> {
+        token(self, tree)
+    }
+}
+
 def_ast_node!(Block);
 
 impl Block {
@@ -314,6 +613,10 @@ impl Block {
         nodes(self, tree)
     }
 
+    pub fn defs(self, tree: &SyntaxTree) -> impl Iterator<Item = Def> + '_ {
+        nodes(self, tree)
+    }
+
     pub fn tail_expr(self, tree: &SyntaxTree) -> Option<Expr> {
         node(self, tree)
     }
@@ -341,6 +644,10 @@ impl Path {
     pub fn nested_name(self, tree: &SyntaxTree) -> Option<Ident> {
         tokens(self, tree).nth(1)
     }
+
+    pub fn doubly_nested_name(self, tree: &SyntaxTree) -> Option<Ident> {
+        tokens(self, tree).nth(2)
+    }
 }
 
 def_ast_node!(ArgList);
@@ -354,6 +661,10 @@ impl ArgList {
 def_ast_node!(Arg);
 
 impl Arg {
+    pub fn name(self, tree: &SyntaxTree) -> Option<Ident> {
+        token(self, tree)
+    }
+
     pub fn value(self, tree: &SyntaxTree) -> Option<Expr> {
         node(self, tree)
     }
@@ -381,6 +692,12 @@ pub enum BinaryOperator {
     Sub(Hyphen),
     Mul(Asterisk),
     Div(Slash),
+    Eq(DoubleEq),
+    NotEq(BangEq),
+    Lt(Less),
+    LtEq(LessEq),
+    Gt(Greater),
+    GtEq(GreaterEq),
 }
 
 impl AstToken for BinaryOperator {
@@ -390,6 +707,12 @@ impl AstToken for BinaryOperator {
             TokenKind::Hyphen => Some(Self::Sub(Hyphen(token))),
             TokenKind::Asterisk => Some(Self::Mul(Asterisk(token))),
             TokenKind::Slash => Some(Self::Div(Slash(token))),
+            TokenKind::DoubleEq => Some(Self::Eq(DoubleEq(token))),
+            TokenKind::BangEq => Some(Self::NotEq(BangEq(token))),
+            TokenKind::Less => Some(Self::Lt(Less(token))),
+            TokenKind::LessEq => Some(Self::LtEq(LessEq(token))),
+            TokenKind::Greater => Some(Self::Gt(Greater(token))),
+            TokenKind::GreaterEq => Some(Self::GtEq(GreaterEq(token))),
             _ => None,
         }
     }
@@ -400,14 +723,28 @@ impl AstToken for BinaryOperator {
             Self::Sub(hyphen) => hyphen.syntax(),
             Self::Mul(asterisk) => asterisk.syntax(),
             Self::Div(slash) => slash.syntax(),
+            Self::Eq(double_eq) => double_eq.syntax(),
+            Self::NotEq(bang_eq) => bang_eq.syntax(),
+            Self::Lt(less) => less.syntax(),
+            Self::LtEq(less_eq) => less_eq.syntax(),
+            Self::Gt(greater) => greater.syntax(),
+            Self::GtEq(greater_eq) => greater_eq.syntax(),
         }
     }
 }
 
+def_ast_token!(ExtKw);
 def_ast_token!(Plus);
 def_ast_token!(Hyphen);
 def_ast_token!(Asterisk);
 def_ast_token!(Slash);
+def_ast_token!(DoubleEq);
+def_ast_token!(BangEq);
+def_ast_token!(Less);
+def_ast_token!(LessEq);
+def_ast_token!(Greater);
+def_ast_token!(GreaterEq);
+def_ast_token!(Question);
 def_ast_token!(Ident);
 def_ast_token!(Int);
 
@@ -518,6 +855,122 @@ mod tests {
         assert!(matches!(local_def.value(&tree), Some(Expr::IntLiteral(_))));
     }
 
+    #[test]
+    fn get_ty_of_local_def() {
+        let (tree, root) = parse("let count: s32 = 5;");
+        let statement = root.statements(&tree).next().unwrap();
+
+        let local_def = match statement {
+            Statement::LocalDef(local_def) => local_def,
+            _ => unreachable!(),
+        };
+
+        let ty = local_def.ty(&tree).unwrap();
+        assert_eq!(ty.path(&tree).unwrap().top_level_name(&tree).unwrap().text(&tree), "s32");
+        assert!(matches!(local_def.value(&tree), Some(Expr::IntLiteral(_))));
+    }
+
+    #[test]
+    fn get_optional_ty() {
+        let (tree, root) = parse("let count: s32? = 5;");
+        let statement = root.statements(&tree).next().unwrap();
+
+        let local_def = match statement {
+            Statement::LocalDef(local_def) => local_def,
+            _ => unreachable!(),
+        };
+
+        let ty = local_def.ty(&tree).unwrap();
+        assert_eq!(ty.path(&tree).unwrap().top_level_name(&tree).unwrap().text(&tree), "s32");
+        assert_eq!(ty.questions(&tree).count(), 1);
+    }
+
+    #[test]
+    fn get_tuple_ty() {
+        let (tree, root) = parse("let pair: (s32, string?) = p;");
+        let statement = root.statements(&tree).next().unwrap();
+
+        let local_def = match statement {
+            Statement::LocalDef(local_def) => local_def,
+            _ => unreachable!(),
+        };
+
+        let ty = local_def.ty(&tree).unwrap();
+        assert!(ty.path(&tree).is_none());
+
+        let mut elements = ty.tuple(&tree).unwrap().elements(&tree);
+        let s32 = elements.next().unwrap();
+        assert_eq!(s32.path(&tree).unwrap().text(&tree), "s32");
+        let string = elements.next().unwrap();
+        assert_eq!(string.path(&tree).unwrap().text(&tree), "string");
+        assert_eq!(string.questions(&tree).count(), 1);
+        assert!(elements.next().is_none());
+    }
+
+    #[test]
+    fn get_tuple_pattern_of_local_def() {
+        let (tree, root) = parse("let (a, _) = pair;");
+        let statement = root.statements(&tree).next().unwrap();
+
+        let local_def = match statement {
+            Statement::LocalDef(local_def) => local_def,
+            _ => unreachable!(),
+        };
+
+        let tuple_pat = match local_def.pattern(&tree) {
+            Some(Pattern::Tuple(tuple_pat)) => tuple_pat,
+            _ => unreachable!(),
+        };
+
+        let mut elements = tuple_pat.elements(&tree);
+        assert!(matches!(elements.next(), Some(Pattern::Ident(_))));
+        assert!(matches!(elements.next(), Some(Pattern::Wildcard(_))));
+        assert!(elements.next().is_none());
+    }
+
+    #[test]
+    fn get_pattern_of_local_def() {
+        let (tree, root) = parse("let Line { start: Point { x, y: _ }, end } = l;");
+        let statement = root.statements(&tree).next().unwrap();
+
+        let local_def = match statement {
+            Statement::LocalDef(local_def) => local_def,
+            _ => unreachable!(),
+        };
+
+        assert!(local_def.name(&tree).is_none());
+
+        let record_pat = match local_def.pattern(&tree) {
+            Some(Pattern::Record(record_pat)) => record_pat,
+            _ => unreachable!(),
+        };
+        assert_eq!(record_pat.path(&tree).unwrap().text(&tree), "Line");
+
+        let mut fields = record_pat.fields(&tree);
+
+        let start = fields.next().unwrap();
+        assert_eq!(start.name(&tree).unwrap().text(&tree), "start");
+        let point_pat = match start.pattern(&tree) {
+            Some(Pattern::Record(record_pat)) => record_pat,
+            _ => unreachable!(),
+        };
+
+        let mut point_fields = point_pat.fields(&tree);
+        let x = point_fields.next().unwrap();
+        assert_eq!(x.name(&tree).unwrap().text(&tree), "x");
+        assert!(x.pattern(&tree).is_none());
+        let y = point_fields.next().unwrap();
+        assert!(matches!(y.pattern(&tree), Some(Pattern::Wildcard(_))));
+        assert!(point_fields.next().is_none());
+
+        let end = fields.next().unwrap();
+        assert_eq!(end.name(&tree).unwrap().text(&tree), "end");
+        assert!(end.pattern(&tree).is_none());
+        assert!(fields.next().is_none());
+
+        assert!(matches!(local_def.value(&tree), Some(Expr::Call(_))));
+    }
+
     #[test]
     fn get_lhs_and_rhs_of_binary_expr() {
         let (tree, root) = parse("foo * 2");
@@ -544,6 +997,71 @@ mod tests {
         assert!(matches!(binary_expr.operator(&tree), Some(BinaryOperator::Add(_))));
     }
 
+    #[test]
+    fn get_comparison_operator_of_binary_expr() {
+        let (tree, root) = parse("a <= b");
+
+        let binary_expr = match root.tail_expr(&tree) {
+            Some(Expr::Binary(binary_expr)) => binary_expr,
+            _ => unreachable!(),
+        };
+
+        assert!(matches!(binary_expr.operator(&tree), Some(BinaryOperator::LtEq(_))));
+    }
+
+    #[test]
+    fn get_inner_expr_of_paren_expr() {
+        let (tree, root) = parse("(a + b)");
+
+        let paren_expr = match root.tail_expr(&tree) {
+            Some(Expr::Paren(paren_expr)) => paren_expr,
+            _ => unreachable!(),
+        };
+
+        assert!(matches!(paren_expr.inner(&tree), Some(Expr::Binary(_))));
+    }
+
+    #[test]
+    fn get_elements_of_tuple_expr() {
+        let (tree, root) = parse("(1, a, \"b\")");
+
+        let tuple_expr = match root.tail_expr(&tree) {
+            Some(Expr::Tuple(tuple_expr)) => tuple_expr,
+            _ => unreachable!(),
+        };
+
+        let mut elements = tuple_expr.elements(&tree);
+        assert!(matches!(elements.next(), Some(Expr::IntLiteral(_))));
+        assert!(matches!(elements.next(), Some(Expr::Call(_))));
+        assert!(matches!(elements.next(), Some(Expr::StringLiteral(_))));
+        assert!(elements.next().is_none());
+    }
+
+    #[test]
+    fn get_base_and_idx_of_tuple_field_expr() {
+        let (tree, root) = parse("pair.1");
+
+        let tuple_field_expr = match root.tail_expr(&tree) {
+            Some(Expr::TupleField(tuple_field_expr)) => tuple_field_expr,
+            _ => unreachable!(),
+        };
+
+        assert!(matches!(tuple_field_expr.base(&tree), Some(Expr::Call(_))));
+        assert_eq!(tuple_field_expr.idx(&tree).unwrap().text(&tree), "1");
+    }
+
+    #[test]
+    fn get_inner_expr_of_try_expr() {
+        let (tree, root) = parse("a?");
+
+        let try_expr = match root.tail_expr(&tree) {
+            Some(Expr::Try(try_expr)) => try_expr,
+            _ => unreachable!(),
+        };
+
+        assert!(matches!(try_expr.inner(&tree), Some(Expr::Call(_))));
+    }
+
     #[test]
     fn get_name_of_call() {
         let (tree, root) = parse("idx");
@@ -574,6 +1092,22 @@ mod tests {
         assert_eq!(path.nested_name(&tree).unwrap().text(&tree), "bar");
     }
 
+    #[test]
+    fn get_name_of_call_in_module_of_other_package() {
+        let (tree, root) = parse("utils.strings.join");
+
+        let call = match root.tail_expr(&tree) {
+            Some(Expr::Call(call)) => call,
+            _ => unreachable!(),
+        };
+
+        let path = call.path(&tree).unwrap();
+
+        assert_eq!(path.top_level_name(&tree).unwrap().text(&tree), "utils");
+        assert_eq!(path.nested_name(&tree).unwrap().text(&tree), "strings");
+        assert_eq!(path.doubly_nested_name(&tree).unwrap().text(&tree), "join");
+    }
+
     #[test]
     fn get_args_of_call() {
         let (tree, root) = parse("mul 10, 20");
@@ -590,6 +1124,28 @@ mod tests {
         assert!(args.next().is_none());
     }
 
+    #[test]
+    fn get_names_of_args() {
+        let (tree, root) = parse("greet name: \"world\", 3");
+
+        let call = match root.tail_expr(&tree) {
+            Some(Expr::Call(call)) => call,
+            _ => unreachable!(),
+        };
+
+        let mut args = call.arg_list(&tree).unwrap().args(&tree);
+
+        let arg = args.next().unwrap();
+        assert_eq!(arg.name(&tree).unwrap().text(&tree), "name");
+        assert!(matches!(arg.value(&tree), Some(Expr::StringLiteral(_))));
+
+        let arg = args.next().unwrap();
+        assert!(arg.name(&tree).is_none());
+        assert!(matches!(arg.value(&tree), Some(Expr::IntLiteral(_))));
+
+        assert!(args.next().is_none());
+    }
+
     #[test]
     fn get_value_of_int_literal() {
         let (tree, root) = parse("92");
@@ -652,6 +1208,25 @@ mod tests {
         assert!(matches!(block.tail_expr(&tree), Some(Expr::Binary(_))));
     }
 
+    #[test]
+    fn get_block_defs() {
+        let (tree, root) = parse("{ fnc two: s32 -> 2; let a = two; rec R {}; a }");
+
+        let block = match root.tail_expr(&tree) {
+            Some(Expr::Block(block)) => block,
+            _ => unreachable!(),
+        };
+
+        let mut defs = block.defs(&tree);
+
+        assert!(matches!(defs.next(), Some(Def::Function(_))));
+        assert!(matches!(defs.next(), Some(Def::Record(_))));
+        assert!(defs.next().is_none());
+
+        assert_eq!(block.statements(&tree).count(), 1);
+        assert!(matches!(block.tail_expr(&tree), Some(Expr::Call(_))));
+    }
+
     #[test]
     fn get_function_name() {
         let (tree, root) = parse("fnc a -> {};");
@@ -688,6 +1263,25 @@ mod tests {
         assert!(params.next().is_none());
     }
 
+    #[test]
+    fn get_default_value_of_param() {
+        let (tree, root) = parse("fnc greet(times: s32 = 1, name: string) -> {};");
+        let def = root.defs(&tree).next().unwrap();
+
+        let function = match def {
+            Def::Function(function) => function,
+            _ => unreachable!(),
+        };
+
+        let mut params = function.param_list(&tree).unwrap().params(&tree);
+
+        let param = params.next().unwrap();
+        assert!(matches!(param.default_value(&tree), Some(Expr::IntLiteral(_))));
+
+        let param = params.next().unwrap();
+        assert!(param.default_value(&tree).is_none());
+    }
+
     #[test]
     fn get_function_return_ty() {
         let (tree, root) = parse("fnc four: s32 -> 4;");
@@ -722,6 +1316,149 @@ mod tests {
         assert!(block.statements(&tree).next().is_none());
     }
 
+    #[test]
+    fn get_contract_of_function() {
+        let (tree, root) =
+            parse("fnc half(n: s32): s32 requires n > 0 ensures h: h < n ensures 1 > 0 -> n / 2;");
+        let def = root.defs(&tree).next().unwrap();
+
+        let function = match def {
+            Def::Function(function) => function,
+            _ => unreachable!(),
+        };
+
+        let mut requires = function.requires(&tree);
+        let condition = requires.next().unwrap().condition(&tree).unwrap();
+        assert_eq!(condition.text(&tree), "n > 0");
+        assert!(requires.next().is_none());
+
+        let mut ensures = function.ensures(&tree);
+
+        let first = ensures.next().unwrap();
+        assert_eq!(first.result_name(&tree).unwrap().text(&tree), "h");
+        assert_eq!(first.condition(&tree).unwrap().text(&tree), "h < n");
+
+        let second = ensures.next().unwrap();
+        assert!(second.result_name(&tree).is_none());
+        assert_eq!(second.condition(&tree).unwrap().text(&tree), "1 > 0");
+
+        assert!(ensures.next().is_none());
+
+        assert!(matches!(function.body(&tree), Some(Expr::Binary(_))));
+    }
+
+    #[test]
+    fn get_ext_kw_of_extern_function() {
+        let (tree, root) = parse("ext fnc now: s32;");
+        let def = root.defs(&tree).next().unwrap();
+
+        let function = match def {
+            Def::Function(function) => function,
+            _ => unreachable!(),
+        };
+
+        assert!(function.ext_kw(&tree).is_some());
+        assert!(function.body(&tree).is_none());
+    }
+
+    #[test]
+    fn get_generic_params_of_function() {
+        let (tree, root) = parse("fnc show<T: Describe + Compare, U>(value: T) -> {};");
+        let def = root.defs(&tree).next().unwrap();
+
+        let function = match def {
+            Def::Function(function) => function,
+            _ => unreachable!(),
+        };
+
+        let mut params = function.generic_param_list(&tree).unwrap().params(&tree);
+
+        let t = params.next().unwrap();
+        assert_eq!(t.name(&tree).unwrap().text(&tree), "T");
+        let bounds: Vec<_> = t.bounds(&tree).map(|path| path.text(&tree)).collect();
+        assert_eq!(bounds, ["Describe", "Compare"]);
+
+        let u = params.next().unwrap();
+        assert_eq!(u.name(&tree).unwrap().text(&tree), "U");
+        assert!(u.bounds(&tree).next().is_none());
+
+        assert!(params.next().is_none());
+    }
+
+    #[test]
+    fn get_name_and_functions_of_interface() {
+        let (tree, root) = parse("ifc Describe { fnc describe(value: Self): string; };");
+        let def = root.defs(&tree).next().unwrap();
+
+        let interface = match def {
+            Def::Interface(interface) => interface,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(interface.name(&tree).unwrap().text(&tree), "Describe");
+
+        let mut functions = interface.functions(&tree);
+        let function = functions.next().unwrap();
+        assert_eq!(function.name(&tree).unwrap().text(&tree), "describe");
+        assert!(function.body(&tree).is_none());
+        assert!(functions.next().is_none());
+    }
+
+    #[test]
+    fn get_interface_ty_and_functions_of_impl() {
+        let (tree, root) =
+            parse(r#"imp Describe for s32 { fnc describe(value: s32): string -> "n"; };"#);
+        let def = root.defs(&tree).next().unwrap();
+
+        let imp = match def {
+            Def::Impl(imp) => imp,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(imp.interface(&tree).unwrap().text(&tree), "Describe");
+        assert_eq!(imp.ty(&tree).unwrap().text(&tree), "s32");
+
+        let mut functions = imp.functions(&tree);
+        let function = functions.next().unwrap();
+        assert_eq!(function.name(&tree).unwrap().text(&tree), "describe");
+        assert!(function.body(&tree).is_some());
+        assert!(functions.next().is_none());
+    }
+
+    #[test]
+    fn get_name_and_body_of_test() {
+        let (tree, root) = parse(r#"test "works" -> "yes";"#);
+        let def = root.defs(&tree).next().unwrap();
+
+        let test = match def {
+            Def::Test(test) => test,
+            _ => unreachable!(),
+        };
+
+        let name = test.name(&tree).unwrap();
+        assert_eq!(name.syntax().text(&tree), r#""works""#);
+
+        let body = match test.body(&tree) {
+            Some(Expr::StringLiteral(string_literal)) => string_literal,
+            _ => unreachable!(),
+        };
+        assert_eq!(body.syntax().text(&tree), r#""yes""#);
+    }
+
+    #[test]
+    fn get_body_of_test_without_name() {
+        let (tree, root) = parse(r#"test -> "yes";"#);
+        let def = root.defs(&tree).next().unwrap();
+
+        let test = match def {
+            Def::Test(test) => test,
+            _ => unreachable!(),
+        };
+
+        assert!(test.name(&tree).is_none());
+        assert!(test.body(&tree).is_some());
+    }
+
     #[test]
     fn get_function_docs() {
         let (tree, root) = parse(
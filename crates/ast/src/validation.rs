@@ -27,11 +27,26 @@ pub struct ValidationDiagnostic {
     pub range: TextRange,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ValidationDiagnosticKind {
     UnneededParens,
 }
 
+impl ValidationDiagnosticKind {
+    pub fn lint_name(self) -> &'static str {
+        match self {
+            Self::UnneededParens => "unneeded_parens",
+        }
+    }
+
+    pub fn from_lint_name(name: &str) -> Option<Self> {
+        match name {
+            "unneeded_parens" => Some(Self::UnneededParens),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod validation_tests {
     use super::*;
@@ -115,8 +115,8 @@ impl<'a> Bench<'a> {
 
         let (index, _diagnostics) = self.stage("index", || hir::index(root, &tree, &mut interner));
 
-        let (bodies, _diagnostics) =
-            self.stage("lower", || hir::lower(root, &tree, &index, &world_index, &mut interner));
+        let (bodies, _diagnostics) = self
+            .stage("lower", || hir::lower(root, &tree, None, &index, &world_index, &mut interner));
 
         let (inference, _diagnostics) =
             self.stage("infer", || hir_ty::infer_all(&bodies, &index, &world_index));
@@ -136,6 +136,8 @@ impl<'a> Bench<'a> {
                     bodies_map.clone(),
                     tys_map.clone(),
                     &world_index,
+                    &interner,
+                    eval::CompileOptions::default(),
                 )
             });
         }
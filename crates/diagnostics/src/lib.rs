@@ -1,14 +1,22 @@
 use ast::validation::{ValidationDiagnostic, ValidationDiagnosticKind};
-use hir::{IndexingDiagnostic, IndexingDiagnosticKind, LoweringDiagnostic, LoweringDiagnosticKind};
+use hir::{
+    IndexingDiagnostic, IndexingDiagnosticKind, LintLevel, Lints, LoweringDiagnostic,
+    LoweringDiagnosticKind,
+};
 use hir_ty::{TyDiagnostic, TyDiagnosticKind};
-use interner::Interner;
+use interner::{Interner, Key};
 use line_index::{ColNr, LineIndex, LineNr};
 use parser::{ExpectedSyntax, SyntaxError, SyntaxErrorKind};
-use std::convert::TryInto;
 use syntax::TokenKind;
 use text_size::{TextRange, TextSize};
 
-pub struct Diagnostic(Repr);
+mod rules;
+pub use rules::{rule_by_code, Rule, RULES};
+
+pub struct Diagnostic {
+    repr: Repr,
+    severity: Severity,
+}
 
 enum Repr {
     Syntax(SyntaxError),
@@ -18,30 +26,66 @@ enum Repr {
     Ty(TyDiagnostic),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Severity {
     Warning,
     Error,
 }
 
+/// The stage of the compiler a diagnostic was emitted by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    Syntax,
+    Validation,
+    Indexing,
+    Lowering,
+    Ty,
+}
+
+/// An edit which resolves the diagnostic it was suggested for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub message: &'static str,
+    pub range: TextRange,
+    pub replacement: String,
+}
+
 impl Diagnostic {
     pub fn from_syntax(error: SyntaxError) -> Self {
-        Self(Repr::Syntax(error))
+        Self { repr: Repr::Syntax(error), severity: Severity::Error }
     }
 
     pub fn from_validation(diagnostic: ValidationDiagnostic) -> Self {
-        Self(Repr::Validation(diagnostic))
+        Self { repr: Repr::Validation(diagnostic), severity: Severity::Warning }
     }
 
     pub fn from_indexing(diagnostic: IndexingDiagnostic) -> Self {
-        Self(Repr::Indexing(diagnostic))
+        Self { repr: Repr::Indexing(diagnostic), severity: Severity::Error }
     }
 
     pub fn from_lowering(diagnostic: LoweringDiagnostic) -> Self {
-        Self(Repr::Lowering(diagnostic))
+        Self { repr: Repr::Lowering(diagnostic), severity: Severity::Error }
     }
 
     pub fn from_ty(diagnostic: TyDiagnostic) -> Self {
-        Self(Repr::Ty(diagnostic))
+        Self { repr: Repr::Ty(diagnostic), severity: Severity::Error }
+    }
+
+    // lints are currently all validation diagnostics;
+    // returns None if the lint has been allowed
+    pub fn with_lint_levels(mut self, lints: &Lints) -> Option<Self> {
+        let kind = match &self.repr {
+            Repr::Validation(ValidationDiagnostic { kind, .. }) => *kind,
+            _ => return Some(self),
+        };
+
+        self.severity = match lints.level(kind) {
+            LintLevel::Allow => return None,
+            LintLevel::Warn => Severity::Warning,
+            LintLevel::Deny => Severity::Error,
+        };
+
+        Some(self)
     }
 
     pub fn display(&self, input: &str, interner: &Interner, line_index: &LineIndex) -> Vec<String> {
@@ -59,8 +103,9 @@ impl Diagnostic {
         };
 
         let mut lines = vec![format!(
-            "{} at {}:{}: {}",
+            "{}[{}] at {}:{}: {}",
             severity,
+            self.rule().code,
             start_line.0 + 1,
             start_col.0 + 1,
             self.message(interner)
@@ -72,7 +117,7 @@ impl Diagnostic {
     }
 
     pub fn range(&self) -> TextRange {
-        match self.0 {
+        match self.repr {
             Repr::Syntax(SyntaxError { kind: SyntaxErrorKind::Missing { offset }, .. }) => {
                 TextRange::new(offset, offset + TextSize::from(1))
             }
@@ -87,17 +132,93 @@ impl Diagnostic {
     }
 
     pub fn severity(&self) -> Severity {
-        match &self.0 {
-            Repr::Syntax(_) => Severity::Error,
-            Repr::Validation(_) => Severity::Warning,
-            Repr::Indexing(_) => Severity::Error,
-            Repr::Lowering(_) => Severity::Error,
-            Repr::Ty(_) => Severity::Error,
+        self.severity
+    }
+
+    pub fn stage(&self) -> Stage {
+        match self.repr {
+            Repr::Syntax(_) => Stage::Syntax,
+            Repr::Validation(_) => Stage::Validation,
+            Repr::Indexing(_) => Stage::Indexing,
+            Repr::Lowering(_) => Stage::Lowering,
+            Repr::Ty(_) => Stage::Ty,
+        }
+    }
+
+    pub fn rule(&self) -> &'static Rule {
+        match &self.repr {
+            Repr::Syntax(e) => match e.kind {
+                SyntaxErrorKind::Missing { .. } => &rules::MISSING_SYNTAX,
+                SyntaxErrorKind::Unexpected { .. } => &rules::UNEXPECTED_SYNTAX,
+            },
+            Repr::Validation(d) => match d.kind {
+                ValidationDiagnosticKind::UnneededParens => &rules::UNNEEDED_PARENS,
+            },
+            Repr::Indexing(d) => match d.kind {
+                IndexingDiagnosticKind::AlreadyDefined { .. } => &rules::ALREADY_DEFINED,
+                IndexingDiagnosticKind::ConflictingImpl { .. } => &rules::CONFLICTING_IMPL,
+            },
+            Repr::Lowering(d) => match d.kind {
+                LoweringDiagnosticKind::OutOfRangeIntLiteral => &rules::OUT_OF_RANGE_INT_LITERAL,
+                LoweringDiagnosticKind::UndefinedLocal { .. } => &rules::UNDEFINED_LOCAL,
+                LoweringDiagnosticKind::UndefinedModule { .. } => &rules::UNDEFINED_MODULE,
+                LoweringDiagnosticKind::MismatchedArgCount { .. } => &rules::MISMATCHED_ARG_COUNT,
+                LoweringDiagnosticKind::MissingArg { .. } => &rules::MISSING_ARG,
+                LoweringDiagnosticKind::UnknownNamedArg { .. } => &rules::UNKNOWN_NAMED_ARG,
+                LoweringDiagnosticKind::DuplicateArg { .. } => &rules::DUPLICATE_ARG,
+                LoweringDiagnosticKind::DuplicateFieldPattern { .. } => {
+                    &rules::DUPLICATE_FIELD_PATTERN
+                }
+                LoweringDiagnosticKind::PositionalArgAfterNamedArg => {
+                    &rules::POSITIONAL_ARG_AFTER_NAMED_ARG
+                }
+                LoweringDiagnosticKind::CalledLocal { .. } => &rules::CALLED_LOCAL,
+                LoweringDiagnosticKind::CalledRecord { .. } => &rules::CALLED_RECORD,
+                LoweringDiagnosticKind::CalledInterface { .. } => &rules::CALLED_INTERFACE,
+                LoweringDiagnosticKind::UndefinedRecord { .. } => &rules::UNDEFINED_RECORD,
+                LoweringDiagnosticKind::InvalidEscape => &rules::INVALID_ESCAPE,
+            },
+            Repr::Ty(d) => match d.kind {
+                TyDiagnosticKind::Mismatch { .. } => &rules::TY_MISMATCH,
+                TyDiagnosticKind::Undefined { .. } => &rules::UNDEFINED_TY,
+                TyDiagnosticKind::UnknownField { .. } => &rules::UNKNOWN_FIELD,
+                TyDiagnosticKind::TryOnNonOptional { .. } => &rules::TRY_ON_NON_OPTIONAL,
+                TyDiagnosticKind::TryInNonOptionalFunction => &rules::TRY_IN_NON_OPTIONAL_FUNCTION,
+                TyDiagnosticKind::TryInContract => &rules::TRY_IN_CONTRACT,
+                TyDiagnosticKind::MissingField { .. } => &rules::MISSING_FIELD,
+                TyDiagnosticKind::NotAnInterface { .. } => &rules::NOT_AN_INTERFACE,
+                TyDiagnosticKind::InterfaceAsTy { .. } => &rules::INTERFACE_AS_TY,
+                TyDiagnosticKind::UninferredGeneric { .. } => &rules::UNINFERRED_GENERIC,
+                TyDiagnosticKind::UnsatisfiedBound { .. } => &rules::UNSATISFIED_BOUND,
+                TyDiagnosticKind::MissingImplFunction { .. } => &rules::MISSING_IMPL_FUNCTION,
+                TyDiagnosticKind::UnknownImplFunction { .. } => &rules::UNKNOWN_IMPL_FUNCTION,
+                TyDiagnosticKind::MismatchedImplParamCount { .. } => {
+                    &rules::MISMATCHED_IMPL_PARAM_COUNT
+                }
+                TyDiagnosticKind::NotATuple { .. } => &rules::NOT_A_TUPLE,
+                TyDiagnosticKind::TupleFieldOutOfRange { .. } => &rules::TUPLE_FIELD_OUT_OF_RANGE,
+                TyDiagnosticKind::NestedOptional { .. } => &rules::NESTED_OPTIONAL,
+                TyDiagnosticKind::MismatchedTuplePatternLength { .. } => {
+                    &rules::MISMATCHED_TUPLE_PATTERN_LENGTH
+                }
+            },
+        }
+    }
+
+    pub fn fixes(&self) -> Vec<Fix> {
+        match self.repr {
+            Repr::Validation(ValidationDiagnostic {
+                kind: ValidationDiagnosticKind::UnneededParens,
+                range,
+            }) => {
+                vec![Fix { message: "remove the parentheses", range, replacement: String::new() }]
+            }
+            _ => Vec::new(),
         }
     }
 
     pub fn message(&self, interner: &Interner) -> String {
-        match &self.0 {
+        match &self.repr {
             Repr::Syntax(e) => syntax_error_message(e),
             Repr::Validation(d) => validation_diagnostic_message(d),
             Repr::Indexing(d) => indexing_diagnostic_message(d, interner),
@@ -130,7 +251,7 @@ fn input_snippet(
             "{}{}{}",
             PADDING,
             " ".repeat(start_col.0 as usize),
-            POINTER_UP.repeat(range.len().try_into().unwrap())
+            POINTER_UP.repeat(range.len().into())
         ));
 
         return;
@@ -186,7 +307,14 @@ fn validation_diagnostic_message(d: &ValidationDiagnostic) -> String {
 fn indexing_diagnostic_message(d: &IndexingDiagnostic, interner: &Interner) -> String {
     match &d.kind {
         IndexingDiagnosticKind::AlreadyDefined { name } => {
-            format!("name `{}` already defined", interner.lookup(*name))
+            format!("name `{}` already defined", source_name(interner, *name))
+        }
+        IndexingDiagnosticKind::ConflictingImpl { interface, ty } => {
+            format!(
+                "`{}` is already implemented for `{}`",
+                source_name(interner, *interface),
+                ty.display(interner)
+            )
         }
     }
 }
@@ -195,20 +323,57 @@ fn lowering_diagnostic_message(d: &LoweringDiagnostic, interner: &Interner) -> S
     match &d.kind {
         LoweringDiagnosticKind::OutOfRangeIntLiteral => "integer literal out of range".to_string(),
         LoweringDiagnosticKind::UndefinedLocal { name } => {
-            format!("undefined variable `{}`", interner.lookup(*name))
+            format!("undefined variable `{}`", source_name(interner, *name))
         }
         LoweringDiagnosticKind::UndefinedModule { name } => {
-            format!("undefined module `{}`", interner.lookup(*name))
+            format!("undefined module `{}`", source_name(interner, *name))
         }
         LoweringDiagnosticKind::MismatchedArgCount { name, expected, got } => {
-            format!("`{}` expected {} arguments, but got {}", interner.lookup(*name), expected, got)
+            format!(
+                "`{}` expected {} arguments, but got {}",
+                source_name(interner, *name),
+                expected,
+                got
+            )
+        }
+        LoweringDiagnosticKind::MissingArg { function, param } => {
+            format!(
+                "`{}` is missing an argument for `{}`",
+                source_name(interner, *function),
+                source_name(interner, *param)
+            )
+        }
+        LoweringDiagnosticKind::UnknownNamedArg { function, name } => {
+            format!(
+                "`{}` has no parameter named `{}`",
+                source_name(interner, *function),
+                source_name(interner, *name)
+            )
+        }
+        LoweringDiagnosticKind::DuplicateArg { name } => {
+            format!("argument `{}` was passed more than once", source_name(interner, *name))
+        }
+        LoweringDiagnosticKind::DuplicateFieldPattern { name } => {
+            format!("field `{}` is destructured more than once", source_name(interner, *name))
+        }
+        LoweringDiagnosticKind::PositionalArgAfterNamedArg => {
+            "positional arguments must come before named arguments".to_string()
         }
         LoweringDiagnosticKind::CalledLocal { name } => {
             format!(
                 "tried to call `{}`, which is a variable, not a function",
-                interner.lookup(*name)
+                source_name(interner, *name)
             )
         }
+        LoweringDiagnosticKind::CalledRecord { name } => {
+            format!("`{}` is a record, not a function", source_name(interner, *name))
+        }
+        LoweringDiagnosticKind::CalledInterface { name } => {
+            format!("`{}` is an interface, not a function", source_name(interner, *name))
+        }
+        LoweringDiagnosticKind::UndefinedRecord { name } => {
+            format!("undefined record `{}`", source_name(interner, *name))
+        }
         LoweringDiagnosticKind::InvalidEscape => "invalid escape".to_string(),
     }
 }
@@ -223,17 +388,107 @@ fn ty_diagnostic_message(d: &TyDiagnostic, interner: &Interner) -> String {
             )
         }
         TyDiagnosticKind::Undefined { name } => {
-            format!("undefined type `{}`", interner.lookup(*name))
+            format!("undefined type `{}`", source_name(interner, *name))
+        }
+        TyDiagnosticKind::UnknownField { record, field } => {
+            format!(
+                "`{}` has no field named `{}`",
+                source_name(interner, *record),
+                source_name(interner, *field)
+            )
+        }
+        TyDiagnosticKind::TryOnNonOptional { found } => {
+            format!("`?` can only be used on optionals, but found `{}`", found.display(interner))
+        }
+        TyDiagnosticKind::TryInNonOptionalFunction => {
+            "`?` can only be used in functions that return an optional".to_string()
+        }
+        TyDiagnosticKind::TryInContract => "`?` can’t be used in a contract".to_string(),
+        TyDiagnosticKind::MissingField { record, field } => {
+            format!(
+                "pattern is missing field `{}` of `{}`",
+                source_name(interner, *field),
+                source_name(interner, *record)
+            )
+        }
+        TyDiagnosticKind::NotAnInterface { name } => {
+            format!("`{}` is not an interface", source_name(interner, *name))
+        }
+        TyDiagnosticKind::InterfaceAsTy { name } => {
+            format!("interface `{}` cannot be used as a type", source_name(interner, *name))
+        }
+        TyDiagnosticKind::UninferredGeneric { name } => {
+            format!("could not infer generic parameter `{}`", source_name(interner, *name))
+        }
+        TyDiagnosticKind::UnsatisfiedBound { ty, interface } => {
+            format!(
+                "`{}` does not implement `{}`",
+                ty.display(interner),
+                source_name(interner, *interface)
+            )
+        }
+        TyDiagnosticKind::MissingImplFunction { interface, function } => {
+            format!(
+                "implementation is missing function `{}` of `{}`",
+                source_name(interner, *function),
+                source_name(interner, *interface)
+            )
+        }
+        TyDiagnosticKind::UnknownImplFunction { interface, function } => {
+            format!(
+                "`{}` has no function named `{}`",
+                source_name(interner, *interface),
+                source_name(interner, *function)
+            )
+        }
+        TyDiagnosticKind::MismatchedImplParamCount { function, expected, found } => {
+            format!(
+                "`{}` should take {} parameter{} but takes {}",
+                source_name(interner, *function),
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            )
+        }
+        TyDiagnosticKind::NotATuple { found } => {
+            format!("expected a tuple but found `{}`", found.display(interner))
+        }
+        TyDiagnosticKind::TupleFieldOutOfRange { tuple, idx } => {
+            format!("`{}` has no field `{}`", tuple.display(interner), idx)
+        }
+        TyDiagnosticKind::MismatchedTuplePatternLength { expected, found } => {
+            format!(
+                "expected a tuple of {} element{} but the pattern has {}",
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found
+            )
+        }
+        TyDiagnosticKind::NestedOptional { ty } => {
+            format!("`{}` is an optional of an optional, which isn’t allowed", ty.display(interner))
         }
     }
 }
 
+// names of definitions inside blocks and implementations are shown as they were written
+fn source_name(interner: &Interner, key: Key) -> &str {
+    hir::source_name(interner.lookup(key))
+}
+
 fn format_kind(kind: TokenKind) -> &'static str {
     match kind {
         TokenKind::LetKw => "`let`",
         TokenKind::FncKw => "`fnc`",
         TokenKind::RecKw => "`rec`",
+        TokenKind::ExtKw => "`ext`",
+        TokenKind::TestKw => "`test`",
+        TokenKind::IfcKw => "`ifc`",
+        TokenKind::ImpKw => "`imp`",
+        TokenKind::ForKw => "`for`",
+        TokenKind::RequiresKw => "`requires`",
+        TokenKind::EnsuresKw => "`ensures`",
         TokenKind::Ident => "identifier",
+        TokenKind::Underscore => "`_`",
         TokenKind::Int => "integer literal",
         TokenKind::Quote => "`\"`",
         TokenKind::Escape => "escape sequence",
@@ -242,9 +497,16 @@ fn format_kind(kind: TokenKind) -> &'static str {
         TokenKind::Hyphen => "`-`",
         TokenKind::Asterisk => "`*`",
         TokenKind::Slash => "`/`",
+        TokenKind::DoubleEq => "`==`",
+        TokenKind::BangEq => "`!=`",
+        TokenKind::Less => "`<`",
+        TokenKind::LessEq => "`<=`",
+        TokenKind::Greater => "`>`",
+        TokenKind::GreaterEq => "`>=`",
         TokenKind::Eq => "`=`",
         TokenKind::Dot => "`.`",
         TokenKind::Colon => "`:`",
+        TokenKind::Question => "`?`",
         TokenKind::Comma => "`,`",
         TokenKind::Semicolon => "`;`",
         TokenKind::Arrow => "`->`",
@@ -262,6 +524,7 @@ fn format_kind(kind: TokenKind) -> &'static str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ast::AstNode;
     use expect_test::{expect, Expect};
     use parser::{ExpectedSyntax, SyntaxErrorKind};
     use std::ops::Range as StdRange;
@@ -297,6 +560,30 @@ mod tests {
         ));
     }
 
+    fn check_validation_with_lint_level(
+        input: &str,
+        kind: ValidationDiagnosticKind,
+        level: LintLevel,
+        range: StdRange<u32>,
+        formatted: Expect,
+    ) {
+        let mut lints = Lints::default();
+        lints.set_level(kind, level);
+
+        let diagnostic = Diagnostic::from_validation(ValidationDiagnostic {
+            kind,
+            range: TextRange::new(range.start.into(), range.end.into()),
+        })
+        .with_lint_levels(&lints);
+
+        let formatted_diagnostic = match diagnostic {
+            Some(d) => d.display(input, &Interner::default(), &LineIndex::new(input)).join("\n"),
+            None => "<allowed>".to_string(),
+        };
+
+        formatted.assert_eq(&format!("{}\n", formatted_diagnostic));
+    }
+
     fn check_indexing(
         input: &str,
         kind: impl Fn(&mut Interner) -> IndexingDiagnosticKind,
@@ -361,7 +648,7 @@ mod tests {
                 range: TextRange::new(4.into(), 5.into()),
             },
             expect![[r#"
-                error at 1:5: expected identifier but found `*`
+                error[E0002] at 1:5: expected identifier but found `*`
                   let *
                       ^
             "#]],
@@ -375,7 +662,7 @@ mod tests {
             ExpectedSyntax::Named("variable name"),
             SyntaxErrorKind::Missing { offset: 3.into() },
             expect![[r#"
-                error at 1:4: missing variable name
+                error[E0001] at 1:4: missing variable name
                   let = 10;
                      ^
             "#]],
@@ -389,7 +676,7 @@ mod tests {
             ExpectedSyntax::Named("expression"),
             SyntaxErrorKind::Missing { offset: 7.into() },
             expect![[r#"
-                error at 1:8: missing expression
+                error[E0001] at 1:8: missing expression
                   let a =
                          ^
             "#]],
@@ -403,7 +690,7 @@ mod tests {
             ValidationDiagnosticKind::UnneededParens,
             8..10,
             expect![[r#"
-                warning at 1:9: unneeded parentheses
+                warning[E0003] at 1:9: unneeded parentheses
                   fnc five(): s32 -> 5;
                           ^^
             "#]],
@@ -417,7 +704,7 @@ mod tests {
             ValidationDiagnosticKind::UnneededParens,
             8..11,
             expect![[r#"
-                warning at 1:9: unneeded parentheses
+                warning[E0003] at 1:9: unneeded parentheses
                           v
                   fnc main(
                   ) -> {};
@@ -426,6 +713,184 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validation_denied_lint() {
+        check_validation_with_lint_level(
+            "fnc five(): s32 -> 5;",
+            ValidationDiagnosticKind::UnneededParens,
+            LintLevel::Deny,
+            8..10,
+            expect![[r#"
+                error[E0003] at 1:9: unneeded parentheses
+                  fnc five(): s32 -> 5;
+                          ^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn validation_allowed_lint() {
+        check_validation_with_lint_level(
+            "fnc five(): s32 -> 5;",
+            ValidationDiagnosticKind::UnneededParens,
+            LintLevel::Allow,
+            8..10,
+            expect![[r#"
+                <allowed>
+            "#]],
+        );
+    }
+
+    #[test]
+    fn validation_unneeded_parens_fix() {
+        let diagnostic = Diagnostic::from_validation(ValidationDiagnostic {
+            kind: ValidationDiagnosticKind::UnneededParens,
+            range: TextRange::new(8.into(), 10.into()),
+        });
+
+        assert_eq!(diagnostic.stage(), Stage::Validation);
+        assert_eq!(diagnostic.rule().id, "unneeded_parens");
+        assert_eq!(
+            diagnostic.fixes(),
+            [Fix {
+                message: "remove the parentheses",
+                range: TextRange::new(8.into(), 10.into()),
+                replacement: String::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn rule_ids_are_unique() {
+        let mut ids: Vec<_> = RULES.iter().map(|rule| rule.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        assert_eq!(ids.len(), RULES.len());
+    }
+
+    #[test]
+    fn rule_codes_follow_rule_order() {
+        for (idx, rule) in RULES.iter().enumerate() {
+            assert_eq!(rule.code, format!("E{:04}", idx + 1));
+        }
+    }
+
+    #[test]
+    fn lowering_and_ty_kinds_have_distinct_codes() {
+        let mut interner = Interner::default();
+        let name = interner.intern("a");
+        let range = TextRange::default();
+
+        let lowering = [
+            LoweringDiagnosticKind::OutOfRangeIntLiteral,
+            LoweringDiagnosticKind::UndefinedLocal { name },
+            LoweringDiagnosticKind::UndefinedModule { name },
+            LoweringDiagnosticKind::MismatchedArgCount { name, expected: 0, got: 1 },
+            LoweringDiagnosticKind::MissingArg { function: name, param: name },
+            LoweringDiagnosticKind::UnknownNamedArg { function: name, name },
+            LoweringDiagnosticKind::DuplicateArg { name },
+            LoweringDiagnosticKind::DuplicateFieldPattern { name },
+            LoweringDiagnosticKind::PositionalArgAfterNamedArg,
+            LoweringDiagnosticKind::CalledLocal { name },
+            LoweringDiagnosticKind::CalledRecord { name },
+            LoweringDiagnosticKind::CalledInterface { name },
+            LoweringDiagnosticKind::UndefinedRecord { name },
+            LoweringDiagnosticKind::InvalidEscape,
+        ]
+        .map(|kind| Diagnostic::from_lowering(LoweringDiagnostic { kind, range }));
+
+        let ty = hir_ty::ResolvedTy::Unknown;
+        let tys = [
+            TyDiagnosticKind::Mismatch { expected: ty.clone(), found: ty.clone() },
+            TyDiagnosticKind::Undefined { name },
+            TyDiagnosticKind::UnknownField { record: name, field: name },
+            TyDiagnosticKind::MissingField { record: name, field: name },
+            TyDiagnosticKind::TryOnNonOptional { found: ty.clone() },
+            TyDiagnosticKind::TryInNonOptionalFunction,
+            TyDiagnosticKind::TryInContract,
+            TyDiagnosticKind::NotAnInterface { name },
+            TyDiagnosticKind::InterfaceAsTy { name },
+            TyDiagnosticKind::UninferredGeneric { name },
+            TyDiagnosticKind::UnsatisfiedBound { ty: ty.clone(), interface: name },
+            TyDiagnosticKind::MissingImplFunction { interface: name, function: name },
+            TyDiagnosticKind::UnknownImplFunction { interface: name, function: name },
+            TyDiagnosticKind::MismatchedImplParamCount { function: name, expected: 0, found: 1 },
+            TyDiagnosticKind::NotATuple { found: ty.clone() },
+            TyDiagnosticKind::TupleFieldOutOfRange { tuple: ty.clone(), idx: 0 },
+            TyDiagnosticKind::MismatchedTuplePatternLength { expected: 0, found: 1 },
+            TyDiagnosticKind::NestedOptional { ty },
+        ]
+        .map(|kind| Diagnostic::from_ty(TyDiagnostic { kind, range }));
+
+        let mut codes: Vec<_> = lowering.iter().chain(tys.iter()).map(|d| d.rule().code).collect();
+        let len = codes.len();
+        codes.sort_unstable();
+        codes.dedup();
+
+        assert_eq!(codes.len(), len);
+    }
+
+    #[test]
+    fn find_rule_by_code() {
+        assert_eq!(rule_by_code("E0003").map(|rule| rule.id), Some("unneeded_parens"));
+        assert_eq!(rule_by_code("e0003").map(|rule| rule.id), Some("unneeded_parens"));
+        assert_eq!(rule_by_code("E9999"), None);
+    }
+
+    #[test]
+    fn explanation_examples() {
+        for rule in RULES {
+            let examples: Vec<_> = rule
+                .explanation
+                .split("```gb\n")
+                .skip(1)
+                .map(|block| block.split("```").next().unwrap())
+                .collect();
+
+            assert!(examples.len() >= 2, "{} needs an erroneous and a fixed example", rule.code);
+
+            let erroneous = diagnostics_of(examples[0]);
+            assert!(
+                erroneous.iter().any(|d| d.rule() == rule),
+                "the erroneous example of {} does not cause it",
+                rule.code
+            );
+
+            let fixed = diagnostics_of(examples[examples.len() - 1]);
+            let rules: Vec<_> = fixed.iter().map(|d| d.rule().id).collect();
+            assert_eq!(rules, Vec::<&str>::new(), "the fixed example of {} is wrong", rule.code);
+        }
+    }
+
+    fn diagnostics_of(input: &str) -> Vec<Diagnostic> {
+        let tokens = lexer::lex(input);
+        let parse = parser::parse_source_file(&tokens, input);
+        let tree = parse.syntax_tree();
+        let root = ast::Root::cast(tree.root(), tree).unwrap();
+
+        let mut interner = Interner::default();
+        let mut world_index = hir::WorldIndex::default();
+        let (index, indexing_diagnostics) = hir::index(root, tree, &mut interner);
+        world_index.add_module(hir::Name(interner.intern("main")), index.clone());
+        let (bodies, lowering_diagnostics) =
+            hir::lower(root, tree, None, &index, &world_index, &mut interner);
+        let (_, ty_diagnostics) = hir_ty::infer_all(&bodies, &index, &world_index);
+
+        parse
+            .errors()
+            .iter()
+            .copied()
+            .map(Diagnostic::from_syntax)
+            .chain(
+                ast::validation::validate(root, tree).into_iter().map(Diagnostic::from_validation),
+            )
+            .chain(indexing_diagnostics.into_iter().map(Diagnostic::from_indexing))
+            .chain(lowering_diagnostics.into_iter().map(Diagnostic::from_lowering))
+            .chain(ty_diagnostics.into_iter().map(Diagnostic::from_ty))
+            .collect()
+    }
+
     #[test]
     fn indexing_already_defined() {
         check_indexing(
@@ -433,13 +898,30 @@ mod tests {
             |i| IndexingDiagnosticKind::AlreadyDefined { name: i.intern("do_thing") },
             0..19,
             expect![[r#"
-                error at 1:1: name `do_thing` already defined
+                error[E0004] at 1:1: name `do_thing` already defined
                   fnc do_thing -> {};
                   ^^^^^^^^^^^^^^^^^^^
             "#]],
         );
     }
 
+    #[test]
+    fn indexing_conflicting_impl() {
+        check_indexing(
+            "imp Describe for s32 {};",
+            |i| IndexingDiagnosticKind::ConflictingImpl {
+                interface: i.intern("Describe"),
+                ty: hir::Ty::S32,
+            },
+            4..20,
+            expect![[r#"
+                error[E0034] at 1:5: `Describe` is already implemented for `s32`
+                  imp Describe for s32 {};
+                      ^^^^^^^^^^^^^^^^
+            "#]],
+        );
+    }
+
     #[test]
     fn lowering_out_of_range_int_literal() {
         check_lowering(
@@ -447,7 +929,7 @@ mod tests {
             |_| LoweringDiagnosticKind::OutOfRangeIntLiteral,
             0..16,
             expect![[r#"
-                error at 1:1: integer literal out of range
+                error[E0005] at 1:1: integer literal out of range
                   1000000000000000;
                   ^^^^^^^^^^^^^^^^
             "#]],
@@ -461,7 +943,7 @@ mod tests {
             |i| LoweringDiagnosticKind::UndefinedLocal { name: i.intern("foo") },
             0..3,
             expect![[r#"
-                error at 1:1: undefined variable `foo`
+                error[E0006] at 1:1: undefined variable `foo`
                   foo + 1;
                   ^^^
             "#]],
@@ -475,7 +957,7 @@ mod tests {
             |i| LoweringDiagnosticKind::UndefinedModule { name: i.intern("io") },
             0..2,
             expect![[r#"
-                error at 1:1: undefined module `io`
+                error[E0007] at 1:1: undefined module `io`
                   io.print "10";
                   ^^
             "#]],
@@ -493,13 +975,106 @@ mod tests {
             },
             0..3,
             expect![[r#"
-                error at 1:1: `add` expected 2 arguments, but got 3
+                error[E0008] at 1:1: `add` expected 2 arguments, but got 3
                   add 1, 2, 3
                   ^^^
             "#]],
         );
     }
 
+    #[test]
+    fn lowering_missing_arg() {
+        check_lowering(
+            "greet times: 3",
+            |i| LoweringDiagnosticKind::MissingArg {
+                function: i.intern("greet"),
+                param: i.intern("name"),
+            },
+            0..5,
+            expect![[r#"
+                error[E0009] at 1:1: `greet` is missing an argument for `name`
+                  greet times: 3
+                  ^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lowering_unknown_named_arg() {
+        check_lowering(
+            "greet nmae: \"world\"",
+            |i| LoweringDiagnosticKind::UnknownNamedArg {
+                function: i.intern("greet"),
+                name: i.intern("nmae"),
+            },
+            6..10,
+            expect![[r#"
+                error[E0010] at 1:7: `greet` has no parameter named `nmae`
+                  greet nmae: "world"
+                        ^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lowering_unknown_named_arg_of_local_function() {
+        check_lowering(
+            "local y: 1",
+            |i| LoweringDiagnosticKind::UnknownNamedArg {
+                function: i.intern("main::local#2"),
+                name: i.intern("y"),
+            },
+            6..7,
+            expect![[r#"
+                error[E0010] at 1:7: `local` has no parameter named `y`
+                  local y: 1
+                        ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lowering_duplicate_arg() {
+        check_lowering(
+            "add 1, x: 2",
+            |i| LoweringDiagnosticKind::DuplicateArg { name: i.intern("x") },
+            7..8,
+            expect![[r#"
+                error[E0011] at 1:8: argument `x` was passed more than once
+                  add 1, x: 2
+                         ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lowering_duplicate_field_pattern() {
+        check_lowering(
+            "let Point { y: b, y: c } = p;",
+            |i| LoweringDiagnosticKind::DuplicateFieldPattern { name: i.intern("y") },
+            18..19,
+            expect![[r#"
+                error[E0036] at 1:19: field `y` is destructured more than once
+                  let Point { y: b, y: c } = p;
+                                    ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lowering_positional_arg_after_named_arg() {
+        check_lowering(
+            "add x: 1, 2",
+            |_| LoweringDiagnosticKind::PositionalArgAfterNamedArg,
+            10..11,
+            expect![[r#"
+                error[E0012] at 1:11: positional arguments must come before named arguments
+                  add x: 1, 2
+                            ^
+            "#]],
+        );
+    }
+
     #[test]
     fn lowering_called_local() {
         check_lowering(
@@ -507,24 +1082,66 @@ mod tests {
             |i| LoweringDiagnosticKind::CalledLocal { name: i.intern("frobnicate") },
             0..10,
             expect![[r#"
-                error at 1:1: tried to call `frobnicate`, which is a variable, not a function
+                error[E0013] at 1:1: tried to call `frobnicate`, which is a variable, not a function
                   frobnicate a, b
                   ^^^^^^^^^^
             "#]],
         );
     }
 
+    #[test]
+    fn lowering_called_record() {
+        check_lowering(
+            "Point 1",
+            |i| LoweringDiagnosticKind::CalledRecord { name: i.intern("Point") },
+            0..5,
+            expect![[r#"
+                error[E0033] at 1:1: `Point` is a record, not a function
+                  Point 1
+                  ^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lowering_called_interface() {
+        check_lowering(
+            "Describe 1",
+            |i| LoweringDiagnosticKind::CalledInterface { name: i.intern("Describe") },
+            0..8,
+            expect![[r#"
+                error[E0037] at 1:1: `Describe` is an interface, not a function
+                  Describe 1
+                  ^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lowering_undefined_record() {
+        check_lowering(
+            "let Foo { a } = 1;",
+            |i| LoweringDiagnosticKind::UndefinedRecord { name: i.intern("Foo") },
+            4..7,
+            expect![[r#"
+                error[E0014] at 1:5: undefined record `Foo`
+                  let Foo { a } = 1;
+                      ^^^
+            "#]],
+        );
+    }
+
     #[test]
     fn lowering_invalid_escape() {
         check_lowering(
             "\\#",
             |_| LoweringDiagnosticKind::InvalidEscape,
             0..2,
-            expect![[r#"
-                error at 1:1: invalid escape
+            expect![[r##"
+                error[E0015] at 1:1: invalid escape
                   \#
                   ^^
-            "#]],
+            "##]],
         );
     }
 
@@ -538,7 +1155,7 @@ mod tests {
             },
             4..9,
             expect![[r#"
-                error at 1:5: expected `s32` but found `string`
+                error[E0016] at 1:5: expected `s32` but found `string`
                   1 + "foo"
                       ^^^^^
             "#]],
@@ -552,10 +1169,276 @@ mod tests {
             |i| TyDiagnosticKind::Undefined { name: i.intern("int") },
             9..12,
             expect![[r#"
-                error at 1:10: undefined type `int`
+                error[E0017] at 1:10: undefined type `int`
                   fnc a(i: int) -> {};
                            ^^^
             "#]],
         );
     }
+
+    #[test]
+    fn ty_unknown_field() {
+        check_ty(
+            "let Point { z } = p;",
+            |i| TyDiagnosticKind::UnknownField { record: i.intern("Point"), field: i.intern("z") },
+            12..13,
+            expect![[r#"
+                error[E0018] at 1:13: `Point` has no field named `z`
+                  let Point { z } = p;
+                              ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_missing_field() {
+        check_ty(
+            "let Point { x } = p;",
+            |i| TyDiagnosticKind::MissingField { record: i.intern("Point"), field: i.intern("y") },
+            4..15,
+            expect![[r#"
+                error[E0022] at 1:5: pattern is missing field `y` of `Point`
+                  let Point { x } = p;
+                      ^^^^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_try_on_non_optional() {
+        check_ty(
+            "a?",
+            |_| TyDiagnosticKind::TryOnNonOptional { found: hir_ty::ResolvedTy::S32 },
+            0..1,
+            expect![[r#"
+                error[E0019] at 1:1: `?` can only be used on optionals, but found `s32`
+                  a?
+                  ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_try_in_non_optional_function() {
+        check_ty(
+            "a?",
+            |_| TyDiagnosticKind::TryInNonOptionalFunction,
+            0..2,
+            expect![[r#"
+                error[E0020] at 1:1: `?` can only be used in functions that return an optional
+                  a?
+                  ^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_try_in_contract() {
+        check_ty(
+            "a?",
+            |_| TyDiagnosticKind::TryInContract,
+            0..2,
+            expect![[r#"
+                error[E0021] at 1:1: `?` can’t be used in a contract
+                  a?
+                  ^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_not_an_interface() {
+        check_ty(
+            "fnc f<T: Point>(v: T) -> {};",
+            |i| TyDiagnosticKind::NotAnInterface { name: i.intern("Point") },
+            9..14,
+            expect![[r#"
+                error[E0023] at 1:10: `Point` is not an interface
+                  fnc f<T: Point>(v: T) -> {};
+                           ^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_interface_as_ty() {
+        check_ty(
+            "fnc f(v: Describe) -> {};",
+            |i| TyDiagnosticKind::InterfaceAsTy { name: i.intern("Describe") },
+            9..17,
+            expect![[r#"
+                error[E0024] at 1:10: interface `Describe` cannot be used as a type
+                  fnc f(v: Describe) -> {};
+                           ^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_uninferred_generic() {
+        check_ty(
+            "nothing",
+            |i| TyDiagnosticKind::UninferredGeneric { name: i.intern("T") },
+            0..7,
+            expect![[r#"
+                error[E0025] at 1:1: could not infer generic parameter `T`
+                  nothing
+                  ^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_unsatisfied_bound() {
+        check_ty(
+            "describe \"text\"",
+            |i| TyDiagnosticKind::UnsatisfiedBound {
+                ty: hir_ty::ResolvedTy::String,
+                interface: i.intern("Describe"),
+            },
+            0..15,
+            expect![[r#"
+                error[E0026] at 1:1: `string` does not implement `Describe`
+                  describe "text"
+                  ^^^^^^^^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_missing_impl_function() {
+        check_ty(
+            "imp Describe for s32 {};",
+            |i| TyDiagnosticKind::MissingImplFunction {
+                interface: i.intern("Describe"),
+                function: i.intern("describe"),
+            },
+            4..12,
+            expect![[r#"
+                error[E0027] at 1:5: implementation is missing function `describe` of `Describe`
+                  imp Describe for s32 {};
+                      ^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_unknown_impl_function() {
+        check_ty(
+            "imp Describe for s32 { fnc explain(v: s32) -> {}; };",
+            |i| TyDiagnosticKind::UnknownImplFunction {
+                interface: i.intern("Describe"),
+                function: i.intern("explain"),
+            },
+            27..34,
+            expect![[r#"
+                error[E0028] at 1:28: `Describe` has no function named `explain`
+                  imp Describe for s32 { fnc explain(v: s32) -> {}; };
+                                             ^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_mismatch_with_local_record() {
+        check_ty(
+            "let p: Point = 1;",
+            |i| TyDiagnosticKind::Mismatch {
+                expected: hir_ty::ResolvedTy::Named(hir::Path::ThisModule(hir::Name(
+                    i.intern("main::Point"),
+                ))),
+                found: hir_ty::ResolvedTy::S32,
+            },
+            15..16,
+            expect![[r#"
+                error[E0016] at 1:16: expected `Point` but found `s32`
+                  let p: Point = 1;
+                                 ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_mismatched_impl_param_count() {
+        check_ty(
+            "imp Eq for s32 { fnc eq(a: s32) -> {}; };",
+            |i| TyDiagnosticKind::MismatchedImplParamCount {
+                function: i.intern("eq"),
+                expected: 2,
+                found: 1,
+            },
+            21..23,
+            expect![[r#"
+                error[E0029] at 1:22: `eq` should take 2 parameters but takes 1
+                  imp Eq for s32 { fnc eq(a: s32) -> {}; };
+                                       ^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_not_a_tuple() {
+        check_ty(
+            "n.0",
+            |_| TyDiagnosticKind::NotATuple { found: hir_ty::ResolvedTy::S32 },
+            0..1,
+            expect![[r#"
+                error[E0030] at 1:1: expected a tuple but found `s32`
+                  n.0
+                  ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_tuple_field_out_of_range() {
+        check_ty(
+            "pair.2",
+            |_| TyDiagnosticKind::TupleFieldOutOfRange {
+                tuple: hir_ty::ResolvedTy::Tuple(vec![
+                    hir_ty::ResolvedTy::S32,
+                    hir_ty::ResolvedTy::String,
+                ]),
+                idx: 2,
+            },
+            0..6,
+            expect![[r#"
+                error[E0031] at 1:1: `(s32, string)` has no field `2`
+                  pair.2
+                  ^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_mismatched_tuple_pattern_length() {
+        check_ty(
+            "let (a, b, c) = pair;",
+            |_| TyDiagnosticKind::MismatchedTuplePatternLength { expected: 2, found: 3 },
+            4..13,
+            expect![[r#"
+                error[E0032] at 1:5: expected a tuple of 2 elements but the pattern has 3
+                  let (a, b, c) = pair;
+                      ^^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn ty_nested_optional() {
+        check_ty(
+            "let a: s32?? = none;",
+            |_| TyDiagnosticKind::NestedOptional {
+                ty: hir_ty::ResolvedTy::Optional(Box::new(hir_ty::ResolvedTy::Optional(Box::new(
+                    hir_ty::ResolvedTy::S32,
+                )))),
+            },
+            7..12,
+            expect![[r#"
+                error[E0035] at 1:8: `s32??` is an optional of an optional, which isn’t allowed
+                  let a: s32?? = none;
+                         ^^^^^
+            "#]],
+        );
+    }
 }
@@ -0,0 +1,319 @@
+/// A kind of diagnostic, identified by a name which stays the same across releases
+/// so tools can track and suppress it.
+#[derive(Debug, PartialEq)]
+pub struct Rule {
+    pub id: &'static str,
+    /// A short code such as `E0003`, which is shown alongside the diagnostic
+    /// and can be passed to `gb explain`.
+    pub code: &'static str,
+    pub description: &'static str,
+    /// A long-form explanation in Markdown, with an example of code which causes
+    /// the diagnostic followed by one which fixes it.
+    pub explanation: &'static str,
+}
+
+/// Every rule a diagnostic can be reported under, in a stable order.
+pub static RULES: &[Rule] = &[
+    MISSING_SYNTAX,
+    UNEXPECTED_SYNTAX,
+    UNNEEDED_PARENS,
+    ALREADY_DEFINED,
+    OUT_OF_RANGE_INT_LITERAL,
+    UNDEFINED_LOCAL,
+    UNDEFINED_MODULE,
+    MISMATCHED_ARG_COUNT,
+    MISSING_ARG,
+    UNKNOWN_NAMED_ARG,
+    DUPLICATE_ARG,
+    POSITIONAL_ARG_AFTER_NAMED_ARG,
+    CALLED_LOCAL,
+    UNDEFINED_RECORD,
+    INVALID_ESCAPE,
+    TY_MISMATCH,
+    UNDEFINED_TY,
+    UNKNOWN_FIELD,
+    TRY_ON_NON_OPTIONAL,
+    TRY_IN_NON_OPTIONAL_FUNCTION,
+    TRY_IN_CONTRACT,
+    MISSING_FIELD,
+    NOT_AN_INTERFACE,
+    INTERFACE_AS_TY,
+    UNINFERRED_GENERIC,
+    UNSATISFIED_BOUND,
+    MISSING_IMPL_FUNCTION,
+    UNKNOWN_IMPL_FUNCTION,
+    MISMATCHED_IMPL_PARAM_COUNT,
+    NOT_A_TUPLE,
+    TUPLE_FIELD_OUT_OF_RANGE,
+    MISMATCHED_TUPLE_PATTERN_LENGTH,
+    CALLED_RECORD,
+    CONFLICTING_IMPL,
+    NESTED_OPTIONAL,
+    DUPLICATE_FIELD_PATTERN,
+    CALLED_INTERFACE,
+];
+
+/// The rule with the given code, which is matched regardless of case.
+pub fn rule_by_code(code: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.code.eq_ignore_ascii_case(code))
+}
+
+pub(crate) const MISSING_SYNTAX: Rule = Rule {
+    id: "missing_syntax",
+    code: "E0001",
+    description: "A piece of syntax the parser expected is missing.",
+    explanation: include_str!("explanations/E0001.md"),
+};
+
+pub(crate) const UNEXPECTED_SYNTAX: Rule = Rule {
+    id: "unexpected_syntax",
+    code: "E0002",
+    description: "The parser found a token where it expected something else.",
+    explanation: include_str!("explanations/E0002.md"),
+};
+
+pub(crate) const UNNEEDED_PARENS: Rule = Rule {
+    id: "unneeded_parens",
+    code: "E0003",
+    description: "A function without parameters has an empty parameter list.",
+    explanation: include_str!("explanations/E0003.md"),
+};
+
+pub(crate) const ALREADY_DEFINED: Rule = Rule {
+    id: "already_defined",
+    code: "E0004",
+    description: "A module defines the same name more than once.",
+    explanation: include_str!("explanations/E0004.md"),
+};
+
+pub(crate) const OUT_OF_RANGE_INT_LITERAL: Rule = Rule {
+    id: "out_of_range_int_literal",
+    code: "E0005",
+    description: "An integer literal is too large to be represented.",
+    explanation: include_str!("explanations/E0005.md"),
+};
+
+pub(crate) const UNDEFINED_LOCAL: Rule = Rule {
+    id: "undefined_local",
+    code: "E0006",
+    description: "A variable is used without being defined.",
+    explanation: include_str!("explanations/E0006.md"),
+};
+
+pub(crate) const UNDEFINED_MODULE: Rule = Rule {
+    id: "undefined_module",
+    code: "E0007",
+    description: "A path refers to a module which does not exist.",
+    explanation: include_str!("explanations/E0007.md"),
+};
+
+pub(crate) const MISMATCHED_ARG_COUNT: Rule = Rule {
+    id: "mismatched_arg_count",
+    code: "E0008",
+    description: "A function is called with the wrong number of arguments.",
+    explanation: include_str!("explanations/E0008.md"),
+};
+
+pub(crate) const MISSING_ARG: Rule = Rule {
+    id: "missing_arg",
+    code: "E0009",
+    description: "A call does not pass an argument for one of the function’s parameters.",
+    explanation: include_str!("explanations/E0009.md"),
+};
+
+pub(crate) const UNKNOWN_NAMED_ARG: Rule = Rule {
+    id: "unknown_named_arg",
+    code: "E0010",
+    description: "A named argument does not match any of the function’s parameters.",
+    explanation: include_str!("explanations/E0010.md"),
+};
+
+pub(crate) const DUPLICATE_ARG: Rule = Rule {
+    id: "duplicate_arg",
+    code: "E0011",
+    description: "A call passes the same argument more than once.",
+    explanation: include_str!("explanations/E0011.md"),
+};
+
+pub(crate) const POSITIONAL_ARG_AFTER_NAMED_ARG: Rule = Rule {
+    id: "positional_arg_after_named_arg",
+    code: "E0012",
+    description: "A positional argument comes after a named argument.",
+    explanation: include_str!("explanations/E0012.md"),
+};
+
+pub(crate) const CALLED_LOCAL: Rule = Rule {
+    id: "called_local",
+    code: "E0013",
+    description: "A variable is called as if it were a function.",
+    explanation: include_str!("explanations/E0013.md"),
+};
+
+pub(crate) const UNDEFINED_RECORD: Rule = Rule {
+    id: "undefined_record",
+    code: "E0014",
+    description: "A name used as a record does not refer to one.",
+    explanation: include_str!("explanations/E0014.md"),
+};
+
+pub(crate) const INVALID_ESCAPE: Rule = Rule {
+    id: "invalid_escape",
+    code: "E0015",
+    description: "A string literal contains an unknown escape.",
+    explanation: include_str!("explanations/E0015.md"),
+};
+
+pub(crate) const TY_MISMATCH: Rule = Rule {
+    id: "ty_mismatch",
+    code: "E0016",
+    description: "An expression has a different type from the one its context requires.",
+    explanation: include_str!("explanations/E0016.md"),
+};
+
+pub(crate) const UNDEFINED_TY: Rule = Rule {
+    id: "undefined_ty",
+    code: "E0017",
+    description: "A type annotation refers to a type which does not exist.",
+    explanation: include_str!("explanations/E0017.md"),
+};
+
+pub(crate) const UNKNOWN_FIELD: Rule = Rule {
+    id: "unknown_field",
+    code: "E0018",
+    description: "A record pattern names a field the record lacks.",
+    explanation: include_str!("explanations/E0018.md"),
+};
+
+pub(crate) const TRY_ON_NON_OPTIONAL: Rule = Rule {
+    id: "try_on_non_optional",
+    code: "E0019",
+    description: "`?` is applied to a value which is not optional.",
+    explanation: include_str!("explanations/E0019.md"),
+};
+
+pub(crate) const TRY_IN_NON_OPTIONAL_FUNCTION: Rule = Rule {
+    id: "try_in_non_optional_function",
+    code: "E0020",
+    description: "`?` is used in a function which does not return an optional.",
+    explanation: include_str!("explanations/E0020.md"),
+};
+
+pub(crate) const TRY_IN_CONTRACT: Rule = Rule {
+    id: "try_in_contract",
+    code: "E0021",
+    description: "`?` is used in a `requires` or `ensures` clause.",
+    explanation: include_str!("explanations/E0021.md"),
+};
+
+pub(crate) const MISSING_FIELD: Rule = Rule {
+    id: "missing_field",
+    code: "E0022",
+    description: "A record pattern does not bind every field of the record.",
+    explanation: include_str!("explanations/E0022.md"),
+};
+
+pub(crate) const NOT_AN_INTERFACE: Rule = Rule {
+    id: "not_an_interface",
+    code: "E0023",
+    description: "A generic bound or implementation names something which is not an interface.",
+    explanation: include_str!("explanations/E0023.md"),
+};
+
+pub(crate) const INTERFACE_AS_TY: Rule = Rule {
+    id: "interface_as_ty",
+    code: "E0024",
+    description: "An interface is used where a type is expected.",
+    explanation: include_str!("explanations/E0024.md"),
+};
+
+pub(crate) const UNINFERRED_GENERIC: Rule = Rule {
+    id: "uninferred_generic",
+    code: "E0025",
+    description: "The type of a generic parameter could not be inferred from the call.",
+    explanation: include_str!("explanations/E0025.md"),
+};
+
+pub(crate) const UNSATISFIED_BOUND: Rule = Rule {
+    id: "unsatisfied_bound",
+    code: "E0026",
+    description: "A generic argument does not implement an interface its parameter requires.",
+    explanation: include_str!("explanations/E0026.md"),
+};
+
+pub(crate) const MISSING_IMPL_FUNCTION: Rule = Rule {
+    id: "missing_impl_function",
+    code: "E0027",
+    description: "An implementation does not define every function of its interface.",
+    explanation: include_str!("explanations/E0027.md"),
+};
+
+pub(crate) const UNKNOWN_IMPL_FUNCTION: Rule = Rule {
+    id: "unknown_impl_function",
+    code: "E0028",
+    description: "An implementation defines a function its interface does not declare.",
+    explanation: include_str!("explanations/E0028.md"),
+};
+
+pub(crate) const MISMATCHED_IMPL_PARAM_COUNT: Rule = Rule {
+    id: "mismatched_impl_param_count",
+    code: "E0029",
+    description:
+        "An implementation’s function takes a different number of parameters from the interface’s.",
+    explanation: include_str!("explanations/E0029.md"),
+};
+
+pub(crate) const NOT_A_TUPLE: Rule = Rule {
+    id: "not_a_tuple",
+    code: "E0030",
+    description: "A tuple field is accessed on a value which is not a tuple.",
+    explanation: include_str!("explanations/E0030.md"),
+};
+
+pub(crate) const TUPLE_FIELD_OUT_OF_RANGE: Rule = Rule {
+    id: "tuple_field_out_of_range",
+    code: "E0031",
+    description: "A tuple field is accessed past the end of the tuple.",
+    explanation: include_str!("explanations/E0031.md"),
+};
+
+pub(crate) const MISMATCHED_TUPLE_PATTERN_LENGTH: Rule = Rule {
+    id: "mismatched_tuple_pattern_length",
+    code: "E0032",
+    description: "A tuple pattern has a different number of elements from the tuple it matches.",
+    explanation: include_str!("explanations/E0032.md"),
+};
+
+pub(crate) const CALLED_RECORD: Rule = Rule {
+    id: "called_record",
+    code: "E0033",
+    description: "A record is called as if it were a function.",
+    explanation: include_str!("explanations/E0033.md"),
+};
+
+pub(crate) const CONFLICTING_IMPL: Rule = Rule {
+    id: "conflicting_impl",
+    code: "E0034",
+    description: "An interface is implemented for the same type more than once.",
+    explanation: include_str!("explanations/E0034.md"),
+};
+
+pub(crate) const NESTED_OPTIONAL: Rule = Rule {
+    id: "nested_optional",
+    code: "E0035",
+    description: "An optional type is made optional again.",
+    explanation: include_str!("explanations/E0035.md"),
+};
+
+pub(crate) const DUPLICATE_FIELD_PATTERN: Rule = Rule {
+    id: "duplicate_field_pattern",
+    code: "E0036",
+    description: "A record pattern destructures the same field more than once.",
+    explanation: include_str!("explanations/E0036.md"),
+};
+
+pub(crate) const CALLED_INTERFACE: Rule = Rule {
+    id: "called_interface",
+    code: "E0037",
+    description: "An interface is called as if it were a function.",
+    explanation: include_str!("explanations/E0037.md"),
+};
@@ -1,27 +1,72 @@
+use crate::CompileOptions;
 use arena::{ArenaMap, Id};
-use rustc_hash::FxHashMap;
+use interner::Interner;
+use rustc_hash::{FxHashMap, FxHashSet};
+use text_size::TextRange;
 use wasm_encoder::{
-    CodeSection, DataSection, Export, ExportSection, Function, FunctionSection, Instruction,
-    MemorySection, MemoryType, Module, TypeSection, ValType,
+    BlockType, CodeSection, DataSection, EntityType, Export, ExportSection, Function,
+    FunctionSection, ImportSection, Instruction, MemArg, MemorySection, MemoryType, Module,
+    TypeSection, ValType,
 };
 
+pub(crate) const HEAP_PTR_ADDRESS: i32 = 0;
+
+pub(crate) const PANIC_MODULE: &str = "gb";
+pub(crate) const PANIC_NAME: &str = "panic";
+
+#[derive(Clone, Copy)]
+pub(crate) enum EntryPoint {
+    Function(hir::Fqn),
+    Test(hir::Fqn),
+}
+
+pub(crate) struct PanicLocation {
+    pub(crate) function: hir::Fqn,
+    pub(crate) range: TextRange,
+    pub(crate) kind: PanicKind,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum PanicKind {
+    Panic,
+    Assert,
+    Requires,
+    Ensures,
+}
+
+type Instance = (hir::Fqn, Vec<hir_ty::ResolvedTy>);
+
 pub(crate) struct Ctx<'a> {
     type_section: TypeSection,
+    import_section: ImportSection,
     function_section: FunctionSection,
     export_section: ExportSection,
     code_section: CodeSection,
     data_section: DataSection,
     instructions: Vec<Instruction<'static>>,
-    function_idxs: FxHashMap<hir::Fqn, u32>,
+    function_idxs: FxHashMap<Instance, u32>,
     function_idx: u32,
-    functions_to_compile: Vec<hir::Fqn>,
+    panic_function_idx: Option<u32>,
+    alloc_function_idx: Option<u32>,
+    panic_locations: Vec<PanicLocation>,
+    current_function: hir::Fqn,
+    entry_point: EntryPoint,
+    entry_point_idx: u32,
+    functions_to_compile: Vec<Instance>,
+    generic_args: FxHashMap<hir::Name, hir_ty::ResolvedTy>,
     local_idxs: ArenaMap<Id<hir::LocalDef>, u32>,
     local_idx: u32,
     local_tys: Vec<(u32, ValType)>,
+    block_depth: u32,
+    // `?` breaks out of this block so the postconditions still get checked
+    checked_body_depth: Option<u32>,
+    return_value_local: Option<u32>,
     constant_idx: i32,
     bodies_map: FxHashMap<hir::Name, hir::Bodies>,
     tys_map: FxHashMap<hir::Name, hir_ty::InferenceResult>,
     world_index: &'a hir::WorldIndex,
+    interner: &'a Interner,
+    options: CompileOptions,
 }
 
 impl<'a> Ctx<'a> {
@@ -29,10 +74,16 @@ impl<'a> Ctx<'a> {
         bodies_map: FxHashMap<hir::Name, hir::Bodies>,
         tys_map: FxHashMap<hir::Name, hir_ty::InferenceResult>,
         world_index: &'a hir::WorldIndex,
-        entry_point: hir::Fqn,
+        interner: &'a Interner,
+        entry_point: EntryPoint,
+        options: CompileOptions,
     ) -> Self {
+        let (EntryPoint::Function(entry_point_fqn) | EntryPoint::Test(entry_point_fqn)) =
+            entry_point;
+
         let mut ctx = Self {
             type_section: TypeSection::new(),
+            import_section: ImportSection::new(),
             function_section: FunctionSection::new(),
             export_section: ExportSection::new(),
             code_section: CodeSection::new(),
@@ -40,29 +91,72 @@ impl<'a> Ctx<'a> {
             instructions: Vec::new(),
             function_idxs: FxHashMap::default(),
             function_idx: 0,
-            functions_to_compile: vec![entry_point],
+            panic_function_idx: None,
+            alloc_function_idx: None,
+            panic_locations: Vec::new(),
+            current_function: entry_point_fqn,
+            entry_point,
+            entry_point_idx: 0,
+            functions_to_compile: Vec::new(),
+            generic_args: FxHashMap::default(),
             local_idxs: ArenaMap::default(),
             local_idx: 0,
             local_tys: Vec::new(),
-            constant_idx: 0,
+            block_depth: 0,
+            checked_body_depth: None,
+            return_value_local: None,
+            constant_idx: HEAP_PTR_ADDRESS + std::mem::size_of::<i32>() as i32,
             bodies_map,
             tys_map,
             world_index,
+            interner,
+            options,
         };
-        ctx.export_section.export("main", Export::Function(ctx.function_idx));
+
+        // imported functions come before all other functions in the index space,
+        // and calls can refer to functions that haven’t been compiled yet,
+        // so we number every reachable function before compiling anything
+        ctx.number_reachable_functions(entry_point);
+
+        ctx.export_section.export("main", Export::Function(ctx.entry_point_idx));
         ctx.export_section.export("memory", Export::Memory(0));
 
         ctx
     }
 
-    pub(crate) fn finish(mut self) -> Vec<u8> {
-        self.compile_queued_functions();
+    pub(crate) fn finish(mut self) -> (Vec<u8>, Vec<PanicLocation>) {
+        // functions have to be compiled in the order they were numbered in
+        if let EntryPoint::Test(fqn) = self.entry_point {
+            let body = self.bodies_map[&fqn.module].test_body(fqn.name);
+            self.compile_body(fqn, self.entry_point_idx, (Vec::new(), Vec::new()), body, None);
+        }
+
+        for instance in std::mem::take(&mut self.functions_to_compile) {
+            self.compile_function(instance);
+        }
+
+        if let Some(idx) = self.alloc_function_idx {
+            self.compile_alloc_function(idx);
+        }
+
+        // the heap starts right after the constants
+        let heap_start = (self.constant_idx + 3) & !3;
+        self.data_section.active(
+            0,
+            &Instruction::I32Const(HEAP_PTR_ADDRESS),
+            heap_start.to_le_bytes(),
+        );
+
+        // the constants have to fit no matter how few pages were asked for
+        let page_size = 64 * 1024;
+        let minimum = (heap_start as u64).div_ceil(page_size).max(self.options.memory_pages.into());
 
         let mut module = Module::new();
         module.section(&self.type_section);
+        module.section(&self.import_section);
         module.section(&self.function_section);
         module.section(MemorySection::new().memory(MemoryType {
-            minimum: 1,
+            minimum,
             maximum: None,
             memory64: false,
         }));
@@ -70,42 +164,272 @@ impl<'a> Ctx<'a> {
         module.section(&self.code_section);
         module.section(&self.data_section);
 
-        module.finish()
+        (module.finish(), self.panic_locations)
+    }
+
+    fn number_reachable_functions(&mut self, entry_point: EntryPoint) {
+        let mut visited = FxHashSet::default();
+        let mut to_visit = Vec::new();
+        let mut panics = false;
+        let mut allocates = false;
+
+        match entry_point {
+            EntryPoint::Function(fqn) => to_visit.push((fqn, Vec::new())),
+            EntryPoint::Test(fqn) => {
+                let body = self.bodies_map[&fqn.module].test_body(fqn.name);
+                self.collect_callees(fqn.module, body, &mut to_visit, &mut panics, &mut allocates);
+            }
+        }
+
+        while let Some(instance) = to_visit.pop() {
+            if visited.contains(&instance) {
+                continue;
+            }
+            visited.insert(instance.clone());
+
+            let (fqn, _) = instance;
+            let function = self.get_function(fqn);
+            self.generic_args = substitution(function, &instance.1);
+
+            if !function.is_extern {
+                let body = self.bodies_map[&fqn.module].function_body(fqn.name);
+                self.collect_callees(fqn.module, body, &mut to_visit, &mut panics, &mut allocates);
+
+                if let Some(contract) = self.contract(fqn) {
+                    panics = true;
+                    for condition in contract.requires.iter().chain(&contract.ensures) {
+                        self.collect_callees(
+                            fqn.module,
+                            *condition,
+                            &mut to_visit,
+                            &mut panics,
+                            &mut allocates,
+                        );
+                    }
+                }
+
+                self.functions_to_compile.push(instance);
+                continue;
+            }
+
+            let (params, results) = signature(function, &self.generic_args);
+            self.type_section.function(params, results);
+            self.import_section.import(
+                self.interner.lookup(fqn.module.0),
+                Some(self.interner.lookup(fqn.name.0)),
+                EntityType::Function(self.function_idx),
+            );
+            self.function_idxs.insert(instance, self.function_idx);
+            self.function_idx += 1;
+        }
+
+        if panics {
+            // takes the index of the panic location and the message
+            self.type_section.function([ValType::I32, ValType::I32], []);
+            self.import_section.import(
+                PANIC_MODULE,
+                Some(PANIC_NAME),
+                EntityType::Function(self.function_idx),
+            );
+            self.panic_function_idx = Some(self.function_idx);
+            self.function_idx += 1;
+        }
+
+        // a function entry point is the first function we visited,
+        // while a test gets an index of its own
+        self.entry_point_idx = self.function_idx;
+        if let EntryPoint::Test(_) = entry_point {
+            self.function_idx += 1;
+        }
+
+        for instance in &self.functions_to_compile {
+            self.function_idxs.insert(instance.clone(), self.function_idx);
+            self.function_idx += 1;
+        }
+
+        // the allocator comes after everything else since it is compiled last
+        if allocates {
+            self.alloc_function_idx = Some(self.function_idx);
+            self.function_idx += 1;
+        }
     }
 
-    fn compile_function(&mut self, fqn: hir::Fqn) {
-        let definition = self.world_index.get_definition(fqn).unwrap();
+    fn collect_callees(
+        &self,
+        module: hir::Name,
+        expr: Id<hir::Expr>,
+        callees: &mut Vec<Instance>,
+        panics: &mut bool,
+        allocates: &mut bool,
+    ) {
+        let bodies = &self.bodies_map[&module];
+
+        match &bodies[expr] {
+            hir::Expr::Missing
+            | hir::Expr::IntLiteral(_)
+            | hir::Expr::StringLiteral(_)
+            | hir::Expr::Local(_)
+            | hir::Expr::Param { .. }
+            | hir::Expr::None
+            | hir::Expr::ReturnValue => {}
+
+            hir::Expr::Try { inner } => {
+                self.collect_callees(module, *inner, callees, panics, allocates)
+            }
+
+            hir::Expr::Field { base, .. } | hir::Expr::TupleField { base, .. } => {
+                self.collect_callees(module, *base, callees, panics, allocates)
+            }
+
+            hir::Expr::Tuple { elements } => {
+                *allocates = true;
+                for element in elements {
+                    self.collect_callees(module, *element, callees, panics, allocates);
+                }
+            }
+
+            hir::Expr::Binary { lhs, rhs, .. } => {
+                self.collect_callees(module, *lhs, callees, panics, allocates);
+                self.collect_callees(module, *rhs, callees, panics, allocates);
+            }
+
+            hir::Expr::Block { statements, tail_expr } => {
+                for statement in statements {
+                    let expr = match bodies[*statement] {
+                        hir::Statement::Expr(expr) => expr,
+                        hir::Statement::LocalDef(local_def) => bodies[local_def].value,
+                    };
+                    self.collect_callees(module, expr, callees, panics, allocates);
+                }
 
-        let function = match definition {
+                if let Some(tail_expr) = tail_expr {
+                    self.collect_callees(module, *tail_expr, callees, panics, allocates);
+                }
+            }
+
+            hir::Expr::Call { path, args, .. } => {
+                callees.push(self.callee(module, expr, *path));
+
+                for arg in args {
+                    self.collect_callees(module, *arg, callees, panics, allocates);
+                }
+            }
+
+            hir::Expr::Default { function, idx } => {
+                let fqn = default_fqn(module, *function);
+                let default_value = self.bodies_map[&fqn.module].param_default(fqn.name, *idx);
+                self.collect_callees(
+                    fqn.module,
+                    default_value.unwrap(),
+                    callees,
+                    panics,
+                    allocates,
+                );
+            }
+
+            hir::Expr::Panic { message } => {
+                *panics = true;
+                self.collect_callees(module, *message, callees, panics, allocates);
+            }
+
+            hir::Expr::Assert { condition, message } => {
+                *panics = true;
+                self.collect_callees(module, *condition, callees, panics, allocates);
+                self.collect_callees(module, *message, callees, panics, allocates);
+            }
+        }
+    }
+
+    fn contract(&self, fqn: hir::Fqn) -> Option<hir::Contract> {
+        if self.options.strip_contracts {
+            return None;
+        }
+
+        self.bodies_map[&fqn.module].function_contract(fqn.name).cloned()
+    }
+
+    fn get_function(&self, fqn: hir::Fqn) -> &'a hir::Function {
+        match self.world_index.get_definition(fqn).unwrap() {
             hir::Definition::Function(function) => function,
             hir::Definition::Record(_) => panic!("tried to compile record as function"),
+            hir::Definition::Interface(_) => panic!("tried to compile interface as function"),
+        }
+    }
+
+    fn callee(&self, module: hir::Name, call: Id<hir::Expr>, path: hir::PathWithRange) -> Instance {
+        let fqn = match path {
+            hir::PathWithRange::ThisModule { name, .. } => hir::Fqn { module, name },
+            hir::PathWithRange::OtherModule { fqn, .. } => fqn,
         };
 
-        let params: Vec<_> = function
-            .params
+        let generic_args: Vec<_> = self.tys_map[&module]
+            .generic_args(call)
             .iter()
-            .filter_map(|param| match param.ty {
-                hir::Ty::Unknown => unreachable!(),
-                hir::Ty::S32 => Some(ValType::I32),
-                hir::Ty::String => todo!(),
-                hir::Ty::Named(_) => todo!(),
-                hir::Ty::Unit => None,
-            })
+            .map(|ty| self.instantiate(module, ty))
             .collect();
 
-        let results = match function.return_ty {
-            hir::Ty::Unknown => unreachable!(),
-            hir::Ty::S32 => vec![ValType::I32],
-            hir::Ty::String => vec![ValType::I32],
-            hir::Ty::Named(_) => todo!(),
-            hir::Ty::Unit => Vec::new(),
-        };
+        match self.get_function(fqn).interface {
+            // the first generic argument is the type implementing the interface,
+            // and the rest go to the implementation
+            Some(interface) => {
+                let interface = hir::Fqn { module: fqn.module, name: interface };
+                let fqn =
+                    hir_ty::impl_function(interface, &generic_args[0], fqn.name, self.world_index)
+                        .unwrap();
+                (fqn, generic_args[1..].to_vec())
+            }
+            None => (fqn, generic_args),
+        }
+    }
 
-        self.type_section.function(params, results);
+    fn instantiate(&self, module: hir::Name, ty: &hir_ty::ResolvedTy) -> hir_ty::ResolvedTy {
+        ty.clone().qualify(module).substitute(&self.generic_args)
+    }
+
+    fn expr_ty(&self, module: hir::Name, expr: Id<hir::Expr>) -> hir_ty::ResolvedTy {
+        self.instantiate(module, &self.tys_map[&module][expr])
+    }
+
+    fn is_wrapped_in_optional(&self, module: hir::Name, expr: Id<hir::Expr>) -> bool {
+        // a generic value may already be an optional in this instance,
+        // in which case it is passed on as it is
+        self.tys_map[&module].is_wrapped_in_optional(expr)
+            && !matches!(self.expr_ty(module, expr), hir_ty::ResolvedTy::Optional(_))
+    }
 
-        self.function_section.function(self.function_idx);
+    fn compile_function(&mut self, instance: Instance) {
+        let (fqn, generic_args) = &instance;
+        let function = self.get_function(*fqn);
+        self.generic_args = substitution(function, generic_args);
 
-        self.compile_expr(fqn.module, self.bodies_map[&fqn.module].function_body(fqn.name));
+        let body = self.bodies_map[&fqn.module].function_body(fqn.name);
+        let signature = signature(function, &self.generic_args);
+        let contract = self.contract(*fqn);
+        self.compile_body(*fqn, self.function_idxs[&instance], signature, body, contract.as_ref());
+    }
+
+    fn compile_body(
+        &mut self,
+        fqn: hir::Fqn,
+        idx: u32,
+        (params, results): (Vec<ValType>, Vec<ValType>),
+        body: Id<hir::Expr>,
+        contract: Option<&hir::Contract>,
+    ) {
+        // every function gets a type of its own, added in the same order as the functions,
+        // so the type index matches the function index
+        self.function_section.function(idx);
+
+        // locals are numbered after the parameters
+        self.local_idx = params.len() as u32;
+        let result = results.first().copied();
+        self.type_section.function(params, results);
+
+        self.current_function = fqn;
+        match contract {
+            Some(contract) => self.compile_checked_body(fqn.module, body, contract, result),
+            None => self.compile_expr(fqn.module, body),
+        }
         self.push(Instruction::End);
 
         let mut f = Function::new(self.local_tys.drain(..));
@@ -114,41 +438,113 @@ impl<'a> Ctx<'a> {
         }
         self.instructions.clear();
         self.code_section.function(&f);
-
-        self.compile_queued_functions();
     }
 
-    fn compile_queued_functions(&mut self) {
-        while let Some(name) = self.functions_to_compile.pop() {
-            self.compile_function(name);
+    fn compile_checked_body(
+        &mut self,
+        module: hir::Name,
+        body: Id<hir::Expr>,
+        contract: &hir::Contract,
+        result: Option<ValType>,
+    ) {
+        for condition in &contract.requires {
+            self.compile_contract_check(module, *condition, PanicKind::Requires);
+        }
+
+        self.push(Instruction::Block(match result {
+            Some(val_type) => BlockType::Result(val_type),
+            None => BlockType::Empty,
+        }));
+        self.checked_body_depth = Some(self.block_depth);
+        self.block_depth += 1;
+        self.compile_expr(module, body);
+        self.block_depth -= 1;
+        self.checked_body_depth = None;
+        self.push(Instruction::End);
+
+        if let Some(val_type) = result {
+            let idx = self.local_idx;
+            self.local_idx += 1;
+            self.local_tys.push((1, val_type));
+            self.push(Instruction::LocalSet(idx));
+            self.return_value_local = Some(idx);
         }
+
+        for condition in &contract.ensures {
+            self.compile_contract_check(module, *condition, PanicKind::Ensures);
+        }
+
+        if let Some(idx) = self.return_value_local.take() {
+            self.push(Instruction::LocalGet(idx));
+        }
+    }
+
+    fn compile_contract_check(
+        &mut self,
+        module: hir::Name,
+        condition: Id<hir::Expr>,
+        kind: PanicKind,
+    ) {
+        self.compile_expr(module, condition);
+        self.push(Instruction::I32Eqz);
+        self.push(Instruction::If(BlockType::Empty));
+        self.block_depth += 1;
+        self.compile_panic(module, condition, None, kind);
+        self.block_depth -= 1;
+        self.push(Instruction::End);
     }
 
     fn compile_statement(&mut self, module: hir::Name, statement: Id<hir::Statement>) {
         match self.bodies_map[&module][statement] {
-            hir::Statement::Expr(expr) => self.compile_expr(module, expr),
+            hir::Statement::Expr(expr) => {
+                self.compile_expr(module, expr);
+
+                // the values of expression statements are thrown away
+                if resolved_ty_to_val_type(&self.expr_ty(module, expr)).is_some() {
+                    self.push(Instruction::Drop);
+                }
+            }
             hir::Statement::LocalDef(local_def) => {
-                let idx = self.local_idx;
-                self.local_idx += 1;
+                self.compile_local_def(module, local_def);
 
-                self.local_idxs.insert(local_def, idx);
+                if let Some(pattern) = self.bodies_map[&module][local_def].pattern {
+                    let mut bindings = Vec::new();
+                    collect_bindings(&self.bodies_map[&module], pattern, &mut bindings);
 
-                let value = self.bodies_map[&module][local_def].value;
-                self.compile_expr(module, value);
-                self.push(Instruction::LocalSet(idx));
-
-                let ty = match self.tys_map[&module][local_def] {
-                    hir_ty::ResolvedTy::Unknown => unreachable!(),
-                    hir_ty::ResolvedTy::S32 => ValType::I32,
-                    hir_ty::ResolvedTy::String => ValType::I32,
-                    hir_ty::ResolvedTy::Named(_) => todo!(),
-                    hir_ty::ResolvedTy::Unit => return,
-                };
-                self.local_tys.push((1, ty));
+                    for binding in bindings {
+                        self.compile_local_def(module, binding);
+                    }
+                }
             }
         }
     }
 
+    fn compile_local_def(&mut self, module: hir::Name, local_def: Id<hir::LocalDef>) {
+        let value = self.bodies_map[&module][local_def].value;
+
+        let ty = match resolved_ty_to_val_type(
+            &self.instantiate(module, &self.tys_map[&module][local_def]),
+        ) {
+            Some(ty) => ty,
+            // unit values take up no space, so there is nothing to store
+            None => {
+                self.compile_expr(module, value);
+                return;
+            }
+        };
+
+        // the local is declared before compiling its value,
+        // which may declare locals of its own
+        let idx = self.local_idx;
+        self.local_idx += 1;
+        self.local_tys.push((1, ty));
+
+        self.local_idxs.insert(local_def, idx);
+
+        self.compile_expr(module, value);
+        self.push(Instruction::LocalSet(idx));
+    }
+
     fn compile_expr(&mut self, module: hir::Name, expr: Id<hir::Expr>) {
         match self.bodies_map[&module][expr].clone() {
             hir::Expr::Missing => unreachable!(),
@@ -157,18 +553,7 @@ impl<'a> Ctx<'a> {
                 self.push(Instruction::I32Const(n as i32));
             }
 
-            hir::Expr::StringLiteral(s) => {
-                let instruction = Instruction::I32Const(self.constant_idx);
-
-                let len = s.len() as i32;
-                let mut bytes = len.to_le_bytes().to_vec();
-                bytes.append(&mut s.into_bytes());
-
-                self.constant_idx += bytes.len() as i32;
-
-                self.data_section.active(0, &instruction, bytes);
-                self.push(instruction);
-            }
+            hir::Expr::StringLiteral(s) => self.compile_string(s),
 
             hir::Expr::Binary { lhs, rhs, operator } => {
                 self.compile_expr(module, lhs);
@@ -179,6 +564,12 @@ impl<'a> Ctx<'a> {
                     hir::BinaryOperator::Sub => self.push(Instruction::I32Sub),
                     hir::BinaryOperator::Mul => self.push(Instruction::I32Mul),
                     hir::BinaryOperator::Div => self.push(Instruction::I32DivU),
+                    hir::BinaryOperator::Eq => self.push(Instruction::I32Eq),
+                    hir::BinaryOperator::NotEq => self.push(Instruction::I32Ne),
+                    hir::BinaryOperator::Lt => self.push(Instruction::I32LtS),
+                    hir::BinaryOperator::LtEq => self.push(Instruction::I32LeS),
+                    hir::BinaryOperator::Gt => self.push(Instruction::I32GtS),
+                    hir::BinaryOperator::GtEq => self.push(Instruction::I32GeS),
                 };
             }
 
@@ -193,33 +584,431 @@ impl<'a> Ctx<'a> {
             }
 
             hir::Expr::Local(local_def) => {
-                self.push(Instruction::LocalGet(self.local_idxs[local_def]));
+                // locals of unit type aren’t stored anywhere
+                if let Some(idx) = self.local_idxs.get(local_def) {
+                    self.push(Instruction::LocalGet(*idx));
+                }
             }
 
             hir::Expr::Param { idx } => {
-                self.push(Instruction::LocalGet(idx));
+                // parameters of unit type are left out of the signature,
+                // so they don’t count towards the local index either
+                let params = &self.get_function(self.current_function).params;
+                let val_types: Vec<_> = params[..=idx as usize]
+                    .iter()
+                    .map(|param| ty_to_val_type(&param.ty, &self.generic_args))
+                    .collect();
+
+                if val_types[idx as usize].is_some() {
+                    let local = val_types[..idx as usize].iter().flatten().count();
+                    self.push(Instruction::LocalGet(local as u32));
+                }
             }
 
-            hir::Expr::Call { path, args } => {
-                let fqn = match path {
-                    hir::PathWithRange::ThisModule { name, .. } => hir::Fqn { module, name },
-                    hir::PathWithRange::OtherModule { fqn, .. } => fqn,
+            hir::Expr::None => self.push(Instruction::I64Const(0)),
+
+            hir::Expr::Try { inner } => {
+                self.compile_expr(module, inner);
+
+                let idx = self.local_idx;
+                self.local_idx += 1;
+                self.local_tys.push((1, ValType::I64));
+
+                // return early if the value is absent
+                self.push(Instruction::LocalTee(idx));
+                self.push(Instruction::I64Const(32));
+                self.push(Instruction::I64ShrU);
+                self.push(Instruction::I64Eqz);
+                self.push(Instruction::If(BlockType::Empty));
+                self.block_depth += 1;
+                self.push(Instruction::I64Const(0));
+                match self.checked_body_depth {
+                    Some(depth) => self.push(Instruction::Br(self.block_depth - 1 - depth)),
+                    None => self.push(Instruction::Return),
+                }
+                self.block_depth -= 1;
+                self.push(Instruction::End);
+
+                if self.expr_ty(module, expr) != hir_ty::ResolvedTy::Unit {
+                    self.push(Instruction::LocalGet(idx));
+                    self.push(Instruction::I32WrapI64);
+                }
+            }
+
+            hir::Expr::Field { base, field } => {
+                self.compile_expr(module, base);
+
+                let record = match self.expr_ty(module, base) {
+                    hir_ty::ResolvedTy::Named(record) => default_fqn(module, record),
+                    _ => unreachable!(),
+                };
+                let fields = match self.world_index.get_definition(record).unwrap() {
+                    hir::Definition::Record(record) => &record.fields,
+                    hir::Definition::Function(_) | hir::Definition::Interface(_) => unreachable!(),
                 };
 
-                self.functions_to_compile.push(fqn);
-                self.function_idxs.insert(fqn, self.function_idx);
-                self.function_idx += 1;
+                // fields are laid out one after the other in the order they were defined,
+                // leaving out those of unit type
+                let mut offset = 0;
+                for f in fields {
+                    // record fields are never generic
+                    let val_type = ty_to_val_type(&f.ty, &FxHashMap::default());
 
-                for arg in args {
-                    self.compile_expr(module, arg);
+                    if f.name == Some(field) {
+                        self.load(val_type, offset);
+                        break;
+                    }
+
+                    offset += val_type_size(val_type);
+                }
+            }
+
+            hir::Expr::Tuple { elements } => {
+                // tuples are laid out in memory like records,
+                // with the elements in the order they were written
+                let val_types: Vec<_> = elements
+                    .iter()
+                    .map(|element| {
+                        if self.is_wrapped_in_optional(module, *element) {
+                            Some(ValType::I64)
+                        } else {
+                            resolved_ty_to_val_type(&self.expr_ty(module, *element))
+                        }
+                    })
+                    .collect();
+                let size: u64 = val_types.iter().map(|val_type| val_type_size(*val_type)).sum();
+
+                let ptr = self.local_idx;
+                self.local_idx += 1;
+                self.local_tys.push((1, ValType::I32));
+
+                self.push(Instruction::I32Const(size as i32));
+                self.push(Instruction::Call(self.alloc_function_idx.unwrap()));
+                self.push(Instruction::LocalSet(ptr));
+
+                let mut offset = 0;
+                for (element, val_type) in elements.into_iter().zip(val_types) {
+                    self.push(Instruction::LocalGet(ptr));
+                    self.compile_expr(module, element);
+                    self.store(val_type, offset);
+                    offset += val_type_size(val_type);
+                }
+
+                self.push(Instruction::LocalGet(ptr));
+            }
+
+            hir::Expr::TupleField { base, idx } => {
+                self.compile_expr(module, base);
+
+                let tys = match self.expr_ty(module, base) {
+                    hir_ty::ResolvedTy::Tuple(tys) => tys,
+                    _ => unreachable!(),
+                };
+
+                let offset = tys[..idx as usize]
+                    .iter()
+                    .map(|ty| val_type_size(resolved_ty_to_val_type(ty)))
+                    .sum();
+                self.load(resolved_ty_to_val_type(&tys[idx as usize]), offset);
+            }
+
+            hir::Expr::Call { path, args, evaluation_order } => {
+                let instance = self.callee(module, expr, path);
+
+                match evaluation_order {
+                    Some(evaluation_order) => {
+                        self.compile_args_in_order(module, &args, &evaluation_order)
+                    }
+                    None => {
+                        for arg in args {
+                            self.compile_expr(module, arg);
+                        }
+                    }
+                }
+
+                self.push(Instruction::Call(self.function_idxs[&instance]));
+            }
+
+            hir::Expr::Default { function, idx } => {
+                // default values are compiled inline at each call site,
+                // but belong to the function they were defined on
+                let fqn = default_fqn(module, function);
+                let default_value = self.bodies_map[&fqn.module].param_default(fqn.name, idx);
+
+                let caller = std::mem::replace(&mut self.current_function, fqn);
+                self.compile_expr(fqn.module, default_value.unwrap());
+                self.current_function = caller;
+            }
+
+            hir::Expr::Panic { message } => {
+                self.compile_panic(module, expr, Some(message), PanicKind::Panic);
+            }
+
+            hir::Expr::Assert { condition, message } => {
+                self.compile_expr(module, condition);
+                self.push(Instruction::I32Eqz);
+                self.push(Instruction::If(BlockType::Empty));
+                self.block_depth += 1;
+                self.compile_panic(module, expr, Some(message), PanicKind::Assert);
+                self.block_depth -= 1;
+                self.push(Instruction::End);
+            }
+
+            hir::Expr::ReturnValue => {
+                if let Some(idx) = self.return_value_local {
+                    self.push(Instruction::LocalGet(idx));
                 }
+            }
+        }
 
-                self.push(Instruction::Call(self.function_idx));
+        if self.is_wrapped_in_optional(module, expr) {
+            // the value goes in the lower half, with the bit above it marking it as present
+            if self.expr_ty(module, expr) != hir_ty::ResolvedTy::Unit {
+                self.push(Instruction::I64ExtendI32U);
+                self.push(Instruction::I64Const(1 << 32));
+                self.push(Instruction::I64Or);
+            } else {
+                self.push(Instruction::I64Const(1 << 32));
             }
         }
     }
 
+    fn compile_args_in_order(&mut self, module: hir::Name, args: &[Id<hir::Expr>], order: &[u32]) {
+        let mut temporaries = vec![None; args.len()];
+
+        for &idx in order {
+            let arg = args[idx as usize];
+            self.compile_expr(module, arg);
+
+            let val_type = if self.is_wrapped_in_optional(module, arg) {
+                Some(ValType::I64)
+            } else {
+                resolved_ty_to_val_type(&self.expr_ty(module, arg))
+            };
+
+            // unit values leave nothing on the stack
+            if let Some(val_type) = val_type {
+                let local = self.local_idx;
+                self.local_idx += 1;
+                self.local_tys.push((1, val_type));
+                self.push(Instruction::LocalSet(local));
+                temporaries[idx as usize] = Some(local);
+            }
+        }
+
+        for local in temporaries.into_iter().flatten() {
+            self.push(Instruction::LocalGet(local));
+        }
+    }
+
+    fn compile_panic(
+        &mut self,
+        module: hir::Name,
+        expr: Id<hir::Expr>,
+        message: Option<Id<hir::Expr>>,
+        kind: PanicKind,
+    ) {
+        let location_idx = self.panic_locations.len() as i32;
+        self.panic_locations.push(PanicLocation {
+            function: self.current_function,
+            range: self.bodies_map[&module].range_for_expr(expr),
+            kind,
+        });
+
+        self.push(Instruction::I32Const(location_idx));
+        match message {
+            Some(message) => self.compile_expr(module, message),
+            None => self.compile_string(String::new()),
+        }
+        self.push(Instruction::Call(self.panic_function_idx.unwrap()));
+        self.push(Instruction::Unreachable);
+    }
+
+    fn compile_string(&mut self, s: String) {
+        let instruction = Instruction::I32Const(self.constant_idx);
+
+        let len = s.len() as i32;
+        let mut bytes = len.to_le_bytes().to_vec();
+        bytes.append(&mut s.into_bytes());
+
+        self.constant_idx += bytes.len() as i32;
+
+        self.data_section.active(0, &instruction, bytes);
+        self.push(instruction);
+    }
+
+    fn load(&mut self, val_type: Option<ValType>, offset: u64) {
+        match val_type {
+            Some(ValType::I64) => {
+                self.push(Instruction::I64Load(MemArg { offset, align: 3, memory_index: 0 }))
+            }
+            Some(_) => {
+                self.push(Instruction::I32Load(MemArg { offset, align: 2, memory_index: 0 }))
+            }
+            None => self.push(Instruction::Drop),
+        }
+    }
+
+    fn store(&mut self, val_type: Option<ValType>, offset: u64) {
+        match val_type {
+            Some(ValType::I64) => {
+                self.push(Instruction::I64Store(MemArg { offset, align: 3, memory_index: 0 }))
+            }
+            Some(_) => {
+                self.push(Instruction::I32Store(MemArg { offset, align: 2, memory_index: 0 }))
+            }
+            None => self.push(Instruction::Drop),
+        }
+    }
+
+    fn compile_alloc_function(&mut self, idx: u32) {
+        const SIZE: u32 = 0;
+        const PTR: u32 = 1;
+        const END: u32 = 2;
+
+        self.function_section.function(idx);
+        self.type_section.function([ValType::I32], [ValType::I32]);
+
+        let mut f = Function::new([(2, ValType::I32)]);
+        let heap_ptr = MemArg { offset: 0, align: 2, memory_index: 0 };
+
+        f.instruction(&Instruction::I32Const(HEAP_PTR_ADDRESS));
+        f.instruction(&Instruction::I32Load(heap_ptr));
+        f.instruction(&Instruction::LocalTee(PTR));
+        f.instruction(&Instruction::LocalGet(SIZE));
+        f.instruction(&Instruction::I32Add);
+        f.instruction(&Instruction::LocalTee(END));
+
+        // the size of memory in bytes
+        f.instruction(&Instruction::MemorySize(0));
+        f.instruction(&Instruction::I32Const(16));
+        f.instruction(&Instruction::I32Shl);
+
+        f.instruction(&Instruction::I32GtU);
+        f.instruction(&Instruction::If(BlockType::Empty));
+        {
+            // grow by however many pages it takes to fit the end
+            f.instruction(&Instruction::LocalGet(END));
+            f.instruction(&Instruction::MemorySize(0));
+            f.instruction(&Instruction::I32Const(16));
+            f.instruction(&Instruction::I32Shl);
+            f.instruction(&Instruction::I32Sub);
+            f.instruction(&Instruction::I32Const(0xffff));
+            f.instruction(&Instruction::I32Add);
+            f.instruction(&Instruction::I32Const(16));
+            f.instruction(&Instruction::I32ShrU);
+            f.instruction(&Instruction::MemoryGrow(0));
+            f.instruction(&Instruction::Drop);
+        }
+        f.instruction(&Instruction::End);
+
+        f.instruction(&Instruction::I32Const(HEAP_PTR_ADDRESS));
+        f.instruction(&Instruction::LocalGet(END));
+        f.instruction(&Instruction::I32Store(heap_ptr));
+
+        f.instruction(&Instruction::LocalGet(PTR));
+        f.instruction(&Instruction::End);
+
+        self.code_section.function(&f);
+    }
+
     fn push(&mut self, instruction: Instruction<'static>) {
         self.instructions.push(instruction);
     }
 }
+
+fn default_fqn(module: hir::Name, function: hir::Path) -> hir::Fqn {
+    match function {
+        hir::Path::ThisModule(name) => hir::Fqn { module, name },
+        hir::Path::OtherModule(fqn) => fqn,
+    }
+}
+
+fn substitution(
+    function: &hir::Function,
+    generic_args: &[hir_ty::ResolvedTy],
+) -> FxHashMap<hir::Name, hir_ty::ResolvedTy> {
+    function
+        .generic_params
+        .iter()
+        .map(|param| param.name)
+        .zip(generic_args.iter().cloned())
+        .collect()
+}
+
+fn signature(
+    function: &hir::Function,
+    generic_args: &FxHashMap<hir::Name, hir_ty::ResolvedTy>,
+) -> (Vec<ValType>, Vec<ValType>) {
+    let params = function
+        .params
+        .iter()
+        .filter_map(|param| ty_to_val_type(&param.ty, generic_args))
+        .collect();
+    let results = ty_to_val_type(&function.return_ty, generic_args).into_iter().collect();
+
+    (params, results)
+}
+
+fn ty_to_val_type(
+    ty: &hir::Ty,
+    generic_args: &FxHashMap<hir::Name, hir_ty::ResolvedTy>,
+) -> Option<ValType> {
+    match ty {
+        hir::Ty::Unknown => unreachable!(),
+        hir::Ty::S32 => Some(ValType::I32),
+        // strings are passed around as pointers into linear memory
+        hir::Ty::String => Some(ValType::I32),
+        hir::Ty::Bool => Some(ValType::I32),
+        // as are records
+        hir::Ty::Named(_) => Some(ValType::I32),
+        hir::Ty::Unit => None,
+        // optionals keep whether they are present next to the value
+        hir::Ty::Optional(_) => Some(ValType::I64),
+        hir::Ty::Generic(name) => resolved_ty_to_val_type(&generic_args[name]),
+        // tuples are passed around as pointers too
+        hir::Ty::Tuple(_) => Some(ValType::I32),
+    }
+}
+
+fn resolved_ty_to_val_type(ty: &hir_ty::ResolvedTy) -> Option<ValType> {
+    match ty {
+        hir_ty::ResolvedTy::Unknown | hir_ty::ResolvedTy::Generic(_) => unreachable!(),
+        hir_ty::ResolvedTy::S32 => Some(ValType::I32),
+        hir_ty::ResolvedTy::String => Some(ValType::I32),
+        hir_ty::ResolvedTy::Bool => Some(ValType::I32),
+        hir_ty::ResolvedTy::Named(_) => Some(ValType::I32),
+        hir_ty::ResolvedTy::Unit => None,
+        hir_ty::ResolvedTy::Optional(_) => Some(ValType::I64),
+        hir_ty::ResolvedTy::Tuple(_) => Some(ValType::I32),
+    }
+}
+
+fn val_type_size(val_type: Option<ValType>) -> u64 {
+    match val_type {
+        Some(ValType::I64) => 8,
+        Some(_) => 4,
+        None => 0,
+    }
+}
+
+fn collect_bindings(
+    bodies: &hir::Bodies,
+    pattern: Id<hir::Pattern>,
+    bindings: &mut Vec<Id<hir::LocalDef>>,
+) {
+    match &bodies[pattern] {
+        hir::Pattern::Missing | hir::Pattern::Wildcard => {}
+        hir::Pattern::Binding(local_def) => bindings.push(*local_def),
+        hir::Pattern::Record { fields, .. } => {
+            for field in fields {
+                collect_bindings(bodies, field.pattern, bindings);
+            }
+        }
+        hir::Pattern::Tuple { elements } => {
+            for element in elements {
+                collect_bindings(bodies, *element, bindings);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::codegen::{HEAP_PTR_ADDRESS, PANIC_MODULE, PANIC_NAME};
+use std::mem;
+use wasmtime::{Caller, Extern};
+
+/// Rust implementations of the `ext fnc`s a program declares.
+///
+/// Each extern function is imported from the wasm module named after the module
+/// it was declared in, so `ext fnc print(n: s32);` in `io.gb` is registered
+/// under the module `io` and the name `print`.
+/// Strings are passed to and from the host as pointers into the exported `memory`;
+/// see [`read_string`] and [`alloc_string`] for working with them.
+/// Records are passed as pointers to their fields, which are stored one after the other
+/// as four bytes each (eight for optionals), leaving out those of unit type.
+/// Optionals are 64-bit integers holding the value in their lower half
+/// and whether it is present in the bit above it.
+///
+/// The default set of host functions implements the extern functions of `std`
+/// and the host side of the `panic` and `assert` built-ins.
+pub struct HostFunctions {
+    pub(crate) engine: wasmtime::Engine,
+    pub(crate) linker: wasmtime::Linker<HostState>,
+}
+
+/// The data host functions can access through their [`Caller`].
+#[derive(Default)]
+pub struct HostState {
+    pub(crate) panic: Option<(u32, String)>,
+}
+
+impl Default for HostFunctions {
+    fn default() -> Self {
+        let engine = wasmtime::Engine::default();
+        let mut linker = wasmtime::Linker::new(&engine);
+        linker.allow_shadowing(true);
+
+        let mut host_functions = Self { engine, linker };
+        host_functions.register_std();
+
+        host_functions
+    }
+}
+
+impl HostFunctions {
+    pub fn register<Params, Results>(
+        &mut self,
+        module: &str,
+        name: &str,
+        f: impl wasmtime::IntoFunc<HostState, Params, Results>,
+    ) -> &mut Self {
+        // defining a function can only fail on a name collision,
+        // which can’t happen because we allow shadowing
+        self.linker.func_wrap(module, name, f).unwrap();
+        self
+    }
+
+    fn register_std(&mut self) {
+        self.register(
+            PANIC_MODULE,
+            PANIC_NAME,
+            |mut caller: Caller<'_, HostState>, location: i32, message: i32| {
+                let message = read_string(&mut caller, message)?;
+                caller.data_mut().panic = Some((location as u32, message));
+                Ok(())
+            },
+        );
+
+        let std = hir::STD_MODULE_NAME;
+
+        self.register(std, "print", |mut caller: Caller<'_, HostState>, s: i32| {
+            println!("{}", read_string(&mut caller, s)?);
+            Ok(())
+        })
+        .register(std, "len", |mut caller: Caller<'_, HostState>, s: i32| {
+            Ok(read_string(&mut caller, s)?.len() as i32)
+        })
+        .register(std, "format_int", |mut caller: Caller<'_, HostState>, n: i32| {
+            alloc_string(&mut caller, &n.to_string())
+        })
+        .register(std, "parse_int", |mut caller: Caller<'_, HostState>, s: i32, fallback: i32| {
+            Ok(read_string(&mut caller, s)?.parse().unwrap_or(fallback))
+        })
+        .register(std, "min", |a: i32, b: i32| a.min(b))
+        .register(std, "max", |a: i32, b: i32| a.max(b))
+        .register(std, "abs", |n: i32| n.wrapping_abs())
+        .register(std, "assert_eq", |expected: i32, actual: i32| {
+            if expected == actual {
+                Ok(())
+            } else {
+                Err(wasmtime::Error::msg(format!(
+                    "assertion failed: expected {expected}, found {actual}"
+                )))
+            }
+        })
+        .register(std, "assert_ne", |unexpected: i32, actual: i32| {
+            if unexpected != actual {
+                Ok(())
+            } else {
+                Err(wasmtime::Error::msg(format!(
+                    "assertion failed: found {actual}, which was unexpected"
+                )))
+            }
+        });
+    }
+}
+
+/// Reads the string `ptr` points to out of the program’s memory.
+pub fn read_string(caller: &mut Caller<'_, HostState>, ptr: i32) -> wasmtime::Result<String> {
+    let memory = memory(caller)?;
+    let data = memory.data(&caller);
+
+    let start = ptr as usize;
+    let len = data
+        .get(start..start + mem::size_of::<i32>())
+        .ok_or_else(|| wasmtime::Error::msg("string pointer out of bounds"))?;
+    let len = i32::from_le_bytes(len.try_into().unwrap()) as usize;
+
+    let start = start + mem::size_of::<i32>();
+    let bytes = data
+        .get(start..start + len)
+        .ok_or_else(|| wasmtime::Error::msg("string contents out of bounds"))?;
+
+    String::from_utf8(bytes.to_vec()).map_err(|_| wasmtime::Error::msg("string is not valid UTF-8"))
+}
+
+/// Copies `s` onto the program’s heap, returning a pointer to it.
+pub fn alloc_string(caller: &mut Caller<'_, HostState>, s: &str) -> wasmtime::Result<i32> {
+    let mut bytes = (s.len() as i32).to_le_bytes().to_vec();
+    bytes.extend_from_slice(s.as_bytes());
+
+    alloc(caller, &bytes)
+}
+
+/// Copies `bytes` onto the program’s heap, returning a pointer to them.
+pub fn alloc(caller: &mut Caller<'_, HostState>, bytes: &[u8]) -> wasmtime::Result<i32> {
+    let memory = memory(caller)?;
+
+    let mut ptr = [0; mem::size_of::<i32>()];
+    memory.read(&mut *caller, HEAP_PTR_ADDRESS as usize, &mut ptr).unwrap();
+    let ptr = i32::from_le_bytes(ptr);
+
+    // keep the heap pointer aligned
+    let size = (bytes.len() + 3) & !3;
+    let end = ptr as usize + size;
+
+    let page_size = 64 * 1024;
+    let data_size = memory.data_size(&caller);
+    if end > data_size {
+        let pages_needed = (end - data_size).div_ceil(page_size);
+        memory.grow(&mut *caller, pages_needed as u64)?;
+    }
+
+    memory.write(&mut *caller, ptr as usize, bytes)?;
+    memory.write(&mut *caller, HEAP_PTR_ADDRESS as usize, &(end as i32).to_le_bytes()).unwrap();
+
+    Ok(ptr)
+}
+
+fn memory(caller: &mut Caller<'_, HostState>) -> wasmtime::Result<wasmtime::Memory> {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => Err(wasmtime::Error::msg("missing `memory` export")),
+    }
+}
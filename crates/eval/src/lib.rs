@@ -1,75 +1,305 @@
 mod codegen;
-use self::codegen::Ctx;
+mod host;
 
+use self::codegen::{Ctx, EntryPoint, PanicKind};
+pub use self::host::*;
+
+use interner::Interner;
 use rustc_hash::FxHashMap;
+use std::fmt;
+use text_size::TextRange;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EvalError {
+    #[error("no host function was registered for `ext fnc {module}.{name}`")]
+    MissingHostFunction { module: String, name: String },
+
+    #[error(
+        "host function for `ext fnc {module}.{name}` has signature {found}, but it was declared as {expected}"
+    )]
+    MismatchedHostFunction { module: String, name: String, expected: String, found: String },
+
+    #[error("program aborted: {0}")]
+    Trap(String),
+
+    #[error("{module}.{function} panicked at {range:?}: {message}")]
+    Panic { module: String, function: String, range: TextRange, message: String },
+
+    #[error("{module}.{function} broke its contract at {range:?}: {clause} does not hold")]
+    ContractViolation { module: String, function: String, range: TextRange, clause: &'static str },
+
+    #[error("the program was compiled to an invalid module: {0}")]
+    InvalidModule(String),
+}
+
+/// Settings that change how programs are compiled.
+#[derive(Debug, Clone, Copy)]
+pub struct CompileOptions {
+    /// Leaves out the checks of `requires` and `ensures` clauses, as release builds do.
+    pub strip_contracts: bool,
+    /// The number of 64 KiB pages of memory the program starts with.
+    pub memory_pages: u32,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self { strip_contracts: false, memory_pages: 1 }
+    }
+}
+
+struct DisplayFuncTy<'a>(&'a wasmtime::FuncType);
+
+impl fmt::Display for DisplayFuncTy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        for (idx, param) in self.0.params().enumerate() {
+            if idx != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{param}")?;
+        }
+        write!(f, ") -> (")?;
+        for (idx, result) in self.0.results().enumerate() {
+            if idx != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{result}")?;
+        }
+        write!(f, ")")
+    }
+}
 
 pub fn eval(
     fqn: hir::Fqn,
     bodies_map: FxHashMap<hir::Name, hir::Bodies>,
     tys_map: FxHashMap<hir::Name, hir_ty::InferenceResult>,
     world_index: &hir::WorldIndex,
-) -> Val {
+    interner: &Interner,
+    host_functions: &HostFunctions,
+    options: CompileOptions,
+) -> Result<Val, EvalError> {
     let entry_point_return_ty = {
         let definition = world_index.get_definition(fqn).unwrap();
         let function = match definition {
             hir::Definition::Function(f) => f,
-            hir::Definition::Record(_) => panic!("tried to eval record"),
+            hir::Definition::Record(_) | hir::Definition::Interface(_) => {
+                panic!("tried to eval non-function")
+            }
         };
-        function.return_ty
+        function.return_ty.clone()
     };
 
-    let ctx = Ctx::new(bodies_map, tys_map, world_index, fqn);
+    run(
+        EntryPoint::Function(fqn),
+        entry_point_return_ty,
+        bodies_map,
+        tys_map,
+        world_index,
+        interner,
+        host_functions,
+        options,
+    )
+}
+
+/// Runs the test with the given name in isolation,
+/// succeeding if it finishes without panicking.
+pub fn eval_test(
+    test: hir::Fqn,
+    bodies_map: FxHashMap<hir::Name, hir::Bodies>,
+    tys_map: FxHashMap<hir::Name, hir_ty::InferenceResult>,
+    world_index: &hir::WorldIndex,
+    interner: &Interner,
+    host_functions: &HostFunctions,
+    options: CompileOptions,
+) -> Result<(), EvalError> {
+    run(
+        EntryPoint::Test(test),
+        hir::Ty::Unit,
+        bodies_map,
+        tys_map,
+        world_index,
+        interner,
+        host_functions,
+        options,
+    )?;
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run(
+    entry_point: EntryPoint,
+    entry_point_return_ty: hir::Ty,
+    bodies_map: FxHashMap<hir::Name, hir::Bodies>,
+    tys_map: FxHashMap<hir::Name, hir_ty::InferenceResult>,
+    world_index: &hir::WorldIndex,
+    interner: &Interner,
+    host_functions: &HostFunctions,
+    options: CompileOptions,
+) -> Result<Val, EvalError> {
+    let ctx = Ctx::new(bodies_map, tys_map, world_index, interner, entry_point, options);
+
+    let (wasm, panic_locations) = ctx.finish();
+
+    let mut store = wasmtime::Store::new(&host_functions.engine, HostState::default());
+    let module = wasmtime::Module::new(store.engine(), wasm)
+        .map_err(|error| EvalError::InvalidModule(error.to_string()))?;
+
+    for import in module.imports() {
+        let module = import.module().to_string();
+        let name = import.name().to_string();
+
+        let expected = match import.ty() {
+            wasmtime::ExternType::Func(ty) => ty,
+            _ => unreachable!("only functions are imported"),
+        };
+
+        let found = match host_functions.linker.get(&mut store, &module, &name) {
+            Some(wasmtime::Extern::Func(func)) => func.ty(&store),
+            _ => return Err(EvalError::MissingHostFunction { module, name }),
+        };
+
+        if !wasmtime::FuncType::eq(&found, &expected) {
+            return Err(EvalError::MismatchedHostFunction {
+                module,
+                name,
+                expected: DisplayFuncTy(&expected).to_string(),
+                found: DisplayFuncTy(&found).to_string(),
+            });
+        }
+    }
 
-    let mut store = wasmtime::Store::<()>::default();
-    let module = wasmtime::Module::new(store.engine(), ctx.finish()).unwrap();
-    let instance = wasmtime::Instance::new(&mut store, &module, &[]).unwrap();
+    let instance = host_functions.linker.instantiate(&mut store, &module).unwrap();
 
     let main = instance.get_func(&mut store, "main").unwrap();
 
     let num_results = if entry_point_return_ty == hir::Ty::Unit { 0 } else { 1 };
     let mut results = vec![wasmtime::Val::I32(0); num_results];
-    main.call(&mut store, &[], &mut results).unwrap();
-
-    match results.get(0).cloned() {
-        Some(wasmtime::Val::I32(n)) => match entry_point_return_ty {
-            hir::Ty::S32 => Val::S32(n),
-            hir::Ty::String => {
-                let mut len = [0; std::mem::size_of::<i32>()];
-                instance
-                    .get_memory(&mut store, "memory")
-                    .unwrap()
-                    .read(&mut store, n as usize, &mut len)
-                    .unwrap();
-
-                let len = i32::from_le_bytes(len);
-                let len = len.try_into().unwrap();
-                let mut string = vec![0; len];
-                instance
-                    .get_memory(&mut store, "memory")
-                    .unwrap()
-                    .read(&mut store, n as usize + std::mem::size_of::<i32>(), &mut string)
-                    .unwrap();
-
-                Val::String(String::from_utf8(string).unwrap())
-            }
+    if let Err(e) = main.call(&mut store, &[], &mut results) {
+        if let Some((location_idx, message)) = store.data_mut().panic.take() {
+            let location = &panic_locations[location_idx as usize];
+            let module = interner.lookup(location.function.module.0).to_string();
+            let function = interner.lookup(location.function.name.0).to_string();
+            let range = location.range;
+
+            return Err(match location.kind {
+                PanicKind::Panic => EvalError::Panic { module, function, range, message },
+                PanicKind::Assert => EvalError::Panic {
+                    module,
+                    function,
+                    range,
+                    message: format!("assertion failed: {message}"),
+                },
+                PanicKind::Requires => {
+                    EvalError::ContractViolation { module, function, range, clause: "precondition" }
+                }
+                PanicKind::Ensures => EvalError::ContractViolation {
+                    module,
+                    function,
+                    range,
+                    clause: "postcondition",
+                },
+            });
+        }
+
+        // leave out the wasm backtrace wasmtime attaches as context
+        return Err(EvalError::Trap(e.root_cause().to_string()));
+    }
+
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+
+    match results.first().cloned() {
+        Some(wasmtime::Val::I32(n)) => Ok(read_val(&entry_point_return_ty, n, memory, &store)),
+
+        Some(wasmtime::Val::I64(n)) => match entry_point_return_ty {
+            hir::Ty::Optional(ty) => Ok(read_optional(&ty, n, memory, &store)),
             _ => unreachable!(),
         },
 
-        None => {
-            assert_eq!(entry_point_return_ty, hir::Ty::Unit);
-            Val::Nil
+        None => match entry_point_return_ty {
+            hir::Ty::Unit => Ok(Val::Nil),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn read_val(
+    ty: &hir::Ty,
+    n: i32,
+    memory: wasmtime::Memory,
+    store: &wasmtime::Store<HostState>,
+) -> Val {
+    match ty {
+        hir::Ty::S32 => Val::S32(n),
+        hir::Ty::String => {
+            let mut len = [0; std::mem::size_of::<i32>()];
+            memory.read(store, n as usize, &mut len).unwrap();
+
+            let len = i32::from_le_bytes(len);
+            let len = len.try_into().unwrap();
+            let mut string = vec![0; len];
+            memory.read(store, n as usize + std::mem::size_of::<i32>(), &mut string).unwrap();
+
+            Val::String(String::from_utf8(string).unwrap())
+        }
+        hir::Ty::Bool => Val::Bool(n != 0),
+        hir::Ty::Unit => Val::Nil,
+        hir::Ty::Tuple(tys) => {
+            let mut address = n as usize;
+            let mut elements = Vec::new();
+
+            // elements are stored one after the other, leaving out those of unit type
+            for ty in tys {
+                let element = match ty {
+                    hir::Ty::Unit => Val::Nil,
+                    hir::Ty::Optional(ty) => {
+                        let mut n = [0; std::mem::size_of::<i64>()];
+                        memory.read(store, address, &mut n).unwrap();
+                        address += n.len();
+                        read_optional(ty, i64::from_le_bytes(n), memory, store)
+                    }
+                    _ => {
+                        let mut n = [0; std::mem::size_of::<i32>()];
+                        memory.read(store, address, &mut n).unwrap();
+                        address += n.len();
+                        read_val(ty, i32::from_le_bytes(n), memory, store)
+                    }
+                };
+                elements.push(element);
+            }
+
+            Val::Tuple(elements)
         }
         _ => unreachable!(),
     }
 }
 
+fn read_optional(
+    ty: &hir::Ty,
+    n: i64,
+    memory: wasmtime::Memory,
+    store: &wasmtime::Store<HostState>,
+) -> Val {
+    if n >> 32 == 0 {
+        Val::None
+    } else {
+        Val::Some(Box::new(read_val(ty, n as i32, memory, store)))
+    }
+}
+
 pub fn compile(
     fqn: hir::Fqn,
     bodies_map: FxHashMap<hir::Name, hir::Bodies>,
     tys_map: FxHashMap<hir::Name, hir_ty::InferenceResult>,
     world_index: &hir::WorldIndex,
+    interner: &Interner,
+    options: CompileOptions,
 ) -> Vec<u8> {
-    Ctx::new(bodies_map, tys_map, world_index, fqn).finish()
+    Ctx::new(bodies_map, tys_map, world_index, interner, EntryPoint::Function(fqn), options)
+        .finish()
+        .0
 }
 
 #[derive(Debug)]
@@ -77,48 +307,84 @@ pub enum Val {
     Nil,
     S32(i32),
     String(String),
+    Bool(bool),
+    None,
+    Some(Box<Val>),
+    Tuple(Vec<Val>),
+}
+
+impl fmt::Display for Val {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nil => write!(f, "{{}}"),
+            Self::S32(n) => write!(f, "{n}"),
+            Self::String(s) => write!(f, "{s:?}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::None => write!(f, "none"),
+            Self::Some(val) => write!(f, "{val}"),
+            Self::Tuple(elements) => {
+                write!(f, "(")?;
+                for (idx, element) in elements.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                if elements.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ast::AstNode;
     use expect_test::{expect, Expect};
-    use interner::Interner;
 
     fn check<const N: usize>(modules: [(&str, &str); N], expect: Expect) {
-        let mut analysis_results = FxHashMap::default();
-        let mut interner = Interner::default();
-        let mut world_index = hir::WorldIndex::default();
-
-        for (module, text) in &modules {
-            let tokens = lexer::lex(text);
-            let parse = parser::parse_source_file(&tokens, text);
-            assert!(parse.errors().is_empty());
-
-            let tree = parse.into_syntax_tree();
-            let root = ast::Root::cast(tree.root(), &tree).unwrap();
-            assert!(ast::validation::validate(root, &tree).is_empty());
+        check_with_host_functions(modules, &HostFunctions::default(), expect);
+    }
 
-            let (index, d) = hir::index(root, &tree, &mut interner);
-            assert!(d.is_empty());
+    fn analyze<const N: usize>(modules: [(&str, &str); N]) -> ide::Program {
+        let program = ide::analyze_sources(&modules);
 
-            world_index.add_module(hir::Name(interner.intern(module)), index.clone());
-            analysis_results.insert(module, (tree, root, index));
+        for (module, diagnostics) in &program.diagnostics {
+            assert!(
+                diagnostics.is_empty(),
+                "`{}` has diagnostics",
+                program.interner.lookup(module.0)
+            );
         }
 
-        let mut bodies_map = FxHashMap::default();
-        let mut tys_map = FxHashMap::default();
+        program
+    }
 
-        for (module, (tree, root, index)) in analysis_results {
-            let (bodies, _) = hir::lower(root, &tree, &index, &world_index, &mut interner);
+    fn check_with_host_functions<const N: usize>(
+        modules: [(&str, &str); N],
+        host_functions: &HostFunctions,
+        expect: Expect,
+    ) {
+        check_eval(modules, host_functions, CompileOptions::default(), expect);
+    }
 
-            let (inference, d) = hir_ty::infer_all(&bodies, &index, &world_index);
-            assert!(d.is_empty());
+    fn check_with_options<const N: usize>(
+        modules: [(&str, &str); N],
+        options: CompileOptions,
+        expect: Expect,
+    ) {
+        check_eval(modules, &HostFunctions::default(), options, expect);
+    }
 
-            bodies_map.insert(hir::Name(interner.intern(module)), bodies);
-            tys_map.insert(hir::Name(interner.intern(module)), inference);
-        }
+    fn check_eval<const N: usize>(
+        modules: [(&str, &str); N],
+        host_functions: &HostFunctions,
+        options: CompileOptions,
+        expect: Expect,
+    ) {
+        let ide::Program { bodies_map, tys_map, world_index, mut interner, .. } = analyze(modules);
 
         let result = eval(
             hir::Fqn {
@@ -128,9 +394,53 @@ mod tests {
             bodies_map,
             tys_map,
             &world_index,
+            &interner,
+            host_functions,
+            options,
+        );
+
+        let actual = match result {
+            Ok(val) => format!("{:?}", val),
+            Err(e) => e.to_string(),
+        };
+
+        expect.assert_eq(&actual);
+    }
+
+    fn check_test<const N: usize>(modules: [(&str, &str); N], test_name: &str, expect: Expect) {
+        let ide::Program { bodies_map, tys_map, world_index, mut interner, .. } = analyze(modules);
+
+        let result = eval_test(
+            hir::Fqn {
+                module: hir::Name(interner.intern("main")),
+                name: hir::Name(interner.intern(test_name)),
+            },
+            bodies_map,
+            tys_map,
+            &world_index,
+            &interner,
+            &HostFunctions::default(),
+            CompileOptions::default(),
         );
 
-        expect.assert_eq(&format!("{:?}", result));
+        let actual = match result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => e.to_string(),
+        };
+
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn display_vals() {
+        let val = Val::Tuple(vec![
+            Val::S32(-1),
+            Val::String("say \"hi\"".to_string()),
+            Val::Some(Box::new(Val::Tuple(vec![Val::Nil]))),
+            Val::None,
+        ]);
+
+        expect![[r#"(-1, "say \"hi\"", ({},), none)"#]].assert_eq(&val.to_string());
     }
 
     #[test]
@@ -159,6 +469,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn discard_value_of_expr_statement() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc id(n: s32): s32 -> n;
+                    fnc main: s32 -> {
+                        id 1;
+                        "unused";
+                        2
+                    };
+                "#,
+            )],
+            expect![["S32(2)"]],
+        );
+    }
+
+    #[test]
+    fn return_bool() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: (bool, bool) -> (1 < 2, 1 == 2);
+                "#,
+            )],
+            expect![["Tuple([Bool(true), Bool(false)])"]],
+        );
+    }
+
     #[test]
     fn binary_expr() {
         check(
@@ -259,50 +600,1005 @@ mod tests {
     }
 
     #[test]
-    fn create_string() {
+    fn call_into_dependency() {
+        check(
+            [
+                (
+                    "main",
+                    r#"
+                        fnc main: s32 -> utils.math.add 10, 20;
+                    "#,
+                ),
+                (
+                    "utils.math",
+                    r#"
+                        fnc add(x: s32, y: s32): s32 -> ops.plus x, y;
+                    "#,
+                ),
+                (
+                    "utils.ops",
+                    r#"
+                        fnc plus(x: s32, y: s32): s32 -> x + y;
+                    "#,
+                ),
+            ],
+            expect![["S32(30)"]],
+        );
+    }
+
+    #[test]
+    fn call_extern_function() {
+        let mut host_functions = HostFunctions::default();
+        host_functions.register("main", "double", |n: i32| n * 2);
+
+        check_with_host_functions(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> double 21;
+                    ext fnc double(n: s32): s32;
+                "#,
+            )],
+            &host_functions,
+            expect![["S32(42)"]],
+        );
+    }
+
+    #[test]
+    fn call_extern_function_from_other_module() {
+        let printed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut host_functions = HostFunctions::default();
+        host_functions.register("io", "print", {
+            let printed = printed.clone();
+            move |n: i32| printed.lock().unwrap().push(n)
+        });
+
+        check_with_host_functions(
+            [
+                (
+                    "main",
+                    r#"
+                        fnc main: s32 -> {
+                            io.print 92;
+                            0
+                        };
+                    "#,
+                ),
+                (
+                    "io",
+                    r#"
+                        ext fnc print(n: s32);
+                    "#,
+                ),
+            ],
+            &host_functions,
+            expect![["S32(0)"]],
+        );
+
+        assert_eq!(*printed.lock().unwrap(), [92]);
+    }
+
+    #[test]
+    fn unused_extern_function_is_not_imported() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> 5;
+                    ext fnc now: s32;
+                "#,
+            )],
+            expect![["S32(5)"]],
+        );
+    }
+
+    #[test]
+    fn missing_host_function() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> now;
+                    ext fnc now: s32;
+                "#,
+            )],
+            expect![["no host function was registered for `ext fnc main.now`"]],
+        );
+    }
+
+    #[test]
+    fn mismatched_host_function() {
+        let mut host_functions = HostFunctions::default();
+        host_functions.register("main", "add", |x: i32, y: i32| x + y);
+
+        check_with_host_functions(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> add 1;
+                    ext fnc add(x: s32): s32;
+                "#,
+            )],
+            &host_functions,
+            expect![[
+                "host function for `ext fnc main.add` has signature (i32, i32) -> (i32), but it was declared as (i32) -> (i32)"
+            ]],
+        );
+    }
+
+    #[test]
+    fn call_std_functions() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> std.clamp {std.abs 0 - 20}, 5, {std.len "hello, world"};
+                "#,
+            )],
+            expect![["S32(12)"]],
+        );
+    }
+
+    #[test]
+    fn format_and_parse_int() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> std.parse_int {std.format_int 92}, 0;
+                "#,
+            )],
+            expect![["S32(92)"]],
+        );
+    }
+
+    #[test]
+    fn return_string_allocated_by_host() {
         check(
             [(
                 "main",
                 r#"
+                    fnc main: string -> {
+                        let before = "foo";
+                        std.format_int 0 - 1234
+                    };
+                "#,
+            )],
+            expect![[r#"String("-1234")"#]],
+        );
+    }
+
+    #[test]
+    fn destructure_record_from_host() {
+        let mut host_functions = HostFunctions::default();
+        host_functions.register("main", "origin", |mut caller: wasmtime::Caller<'_, HostState>| {
+            let bytes: Vec<u8> = [3, 4].iter().flat_map(|n: &i32| n.to_le_bytes()).collect();
+            alloc(&mut caller, &bytes)
+        });
+
+        check_with_host_functions(
+            [(
+                "main",
+                r#"
+                    rec Point { x: s32, y: s32 };
+                    ext fnc origin: Point;
                     fnc main: s32 -> {
-                        let s = "foo";
-                        0
+                        let Point { x, y: _ } = origin;
+                        let Point { x: _, y } = origin;
+                        x * 10 + y
                     };
                 "#,
             )],
-            expect![["S32(0)"]],
+            &host_functions,
+            expect![["S32(34)"]],
         );
     }
 
     #[test]
-    fn return_string() {
+    fn destructure_nested_record() {
+        let mut host_functions = HostFunctions::default();
+        host_functions.register("main", "line", |mut caller: wasmtime::Caller<'_, HostState>| {
+            let end: Vec<u8> = [5, 6].iter().flat_map(|n: &i32| n.to_le_bytes()).collect();
+            let end = alloc(&mut caller, &end)?;
+            let bytes: Vec<u8> = [7, end].iter().flat_map(|n: &i32| n.to_le_bytes()).collect();
+            alloc(&mut caller, &bytes)
+        });
+
+        check_with_host_functions(
+            [(
+                "main",
+                r#"
+                    rec Point { x: s32, y: s32 };
+                    rec Line { width: s32, end: Point };
+                    ext fnc line: Line;
+                    fnc main: s32 -> {
+                        let Line { width, end: Point { x, y } } = line;
+                        width * 100 + x * 10 + y
+                    };
+                "#,
+            )],
+            &host_functions,
+            expect![["S32(756)"]],
+        );
+    }
+
+    #[test]
+    fn return_present_optional() {
         check(
             [(
                 "main",
                 r#"
-                    fnc main: string -> "hello";
+                    fnc main: s32? -> 92;
                 "#,
             )],
-            expect![[r#"String("hello")"#]],
+            expect![["Some(S32(92))"]],
         );
     }
 
     #[test]
-    fn multiple_strings() {
+    fn return_absent_optional() {
         check(
             [(
                 "main",
                 r#"
-                    fnc main: string -> {
-                        let a = "foo";
-                        let b = "bar";
-                        let c = "baz";
-                        let d = "quux";
-                        c
+                    fnc main: string? -> none;
+                "#,
+            )],
+            expect![["None"]],
+        );
+    }
+
+    #[test]
+    fn propagate_present_optional() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc half(n: s32): s32? -> n / 2;
+                    fnc quarter(n: s32): s32? -> {
+                        let half: s32? = half n;
+                        half? / 2
                     };
+                    fnc main: s32? -> quarter 12;
                 "#,
             )],
-            expect![[r#"String("baz")"#]],
+            expect![["Some(S32(3))"]],
+        );
+    }
+
+    #[test]
+    fn propagate_absent_optional() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc nothing: s32? -> none;
+                    fnc main: string? -> {
+                        let n = nothing?;
+                        "reached"
+                    };
+                "#,
+            )],
+            expect![["None"]],
+        );
+    }
+
+    #[test]
+    fn failed_std_assertion() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main -> std.assert_eq 4, 2 + 1;
+                "#,
+            )],
+            expect![["program aborted: assertion failed: expected 4, found 3"]],
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main -> {
+                        assert 1 < 2, "<";
+                        assert 2 <= 2, "<=";
+                        assert 3 > 2, ">";
+                        assert 3 >= 4 == (1 > 2), ">=";
+                        assert 5 != 6, "!=";
+                        assert (1 == 1) != (1 == 2), "==";
+                    };
+                "#,
+            )],
+            expect![["Nil"]],
+        );
+    }
+
+    #[test]
+    fn panic() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        panic "something went wrong";
+                        0
+                    };
+                "#,
+            )],
+            expect![["main.main panicked at 64..92: something went wrong"]],
+        );
+    }
+
+    #[test]
+    fn failed_assertion_in_other_module() {
+        check(
+            [
+                (
+                    "main",
+                    r#"
+                        fnc main -> math.check_positive 1 - 2;
+                    "#,
+                ),
+                (
+                    "math",
+                    r#"
+                        fnc check_positive(n: s32) -> assert n > 0, "n must be positive";
+                    "#,
+                ),
+            ],
+            expect![[
+                "math.check_positive panicked at 55..89: assertion failed: n must be positive"
+            ]],
+        );
+    }
+
+    #[test]
+    fn contract_that_holds() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> div 7, 2;
+                    fnc div(a: s32, b: s32): s32
+                        requires b != 0
+                        ensures quotient: quotient * b <= a
+                        -> a / b;
+                "#,
+            )],
+            expect![["S32(3)"]],
+        );
+    }
+
+    #[test]
+    fn broken_precondition() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> div 7, 0;
+                    fnc div(a: s32, b: s32): s32 requires b != 0 -> a / b;
+                "#,
+            )],
+            expect![["main.div broke its contract at 106..112: precondition does not hold"]],
+        );
+    }
+
+    #[test]
+    fn broken_postcondition() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> double 3;
+                    fnc double(n: s32): s32 ensures doubled: doubled == n * 2 -> n + 2;
+                "#,
+            )],
+            expect![["main.double broke its contract at 109..125: postcondition does not hold"]],
+        );
+    }
+
+    #[test]
+    fn postconditions_are_checked_after_returning_early() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32? -> {
+                        let n = add 5, 1;
+                        add none, 0
+                    };
+                    fnc add(a: s32?, b: s32): s32? ensures b > 0 -> a? + b;
+                "#,
+            )],
+            expect![["main.add broke its contract at 201..206: postcondition does not hold"]],
+        );
+    }
+
+    #[test]
+    fn stripped_contracts() {
+        check_with_options(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> double 3;
+                    fnc double(n: s32): s32 requires n < 0 ensures d: d == n * 2 -> n + 2;
+                "#,
+            )],
+            CompileOptions { strip_contracts: true, ..CompileOptions::default() },
+            expect![["S32(5)"]],
+        );
+    }
+
+    #[test]
+    fn memory_pages() {
+        let mut host_functions = HostFunctions::default();
+        host_functions.register("main", "pages", |mut caller: wasmtime::Caller<'_, HostState>| {
+            match caller.get_export("memory") {
+                Some(wasmtime::Extern::Memory(memory)) => memory.size(&caller) as i32,
+                _ => 0,
+            }
+        });
+
+        check_eval(
+            [("main", "ext fnc pages: s32; fnc main: s32 -> pages;")],
+            &host_functions,
+            CompileOptions { memory_pages: 3, ..CompileOptions::default() },
+            expect![["S32(3)"]],
+        );
+    }
+
+    #[test]
+    fn passing_test() {
+        check_test(
+            [(
+                "main",
+                r#"
+                    fnc add(x: s32, y: s32): s32 -> x + y;
+                    test "adds numbers" -> assert (add 1, 2) == 3, "wrong sum";
+                "#,
+            )],
+            "adds numbers",
+            expect![["ok"]],
+        );
+    }
+
+    #[test]
+    fn failing_test() {
+        check_test(
+            [(
+                "main",
+                r#"
+                    fnc add(x: s32, y: s32): s32 -> x - y;
+                    test "adds numbers" -> assert (add 1, 2) == 3, "wrong sum";
+                "#,
+            )],
+            "adds numbers",
+            expect![["main.adds numbers panicked at 103..138: assertion failed: wrong sum"]],
+        );
+    }
+
+    #[test]
+    fn test_with_same_name_as_function() {
+        check_test(
+            [(
+                "main",
+                r#"
+                    fnc f: s32 -> 1;
+                    test "f" -> std.assert_eq 1, f;
+                "#,
+            )],
+            "f",
+            expect![["ok"]],
+        );
+    }
+
+    #[test]
+    fn repeated_calls() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        let a = double 1;
+                        let b = double a;
+                        add (double b), (add a, b)
+                    };
+                    fnc double(n: s32): s32 -> add n, n;
+                    fnc add(x: s32, y: s32): s32 -> {
+                        let sum = x + y;
+                        sum
+                    };
+                "#,
+            )],
+            expect![["S32(14)"]],
+        );
+    }
+
+    #[test]
+    fn recursion() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main -> count_down 3;
+                    fnc count_down(n: s32) -> {
+                        assert n > 0, "reached zero";
+                        count_down n - 1
+                    };
+                "#,
+            )],
+            expect![["main.count_down panicked at 119..147: assertion failed: reached zero"]],
+        );
+    }
+
+    #[test]
+    fn mutual_recursion() {
+        check(
+            [
+                ("main", "fnc main -> game.ping 5;"),
+                (
+                    "game",
+                    r#"
+                        fnc ping(n: s32) -> {
+                            assert n > 0, "ping missed";
+                            pong n - 1
+                        };
+                        fnc pong(n: s32) -> {
+                            assert n > 0, "pong missed";
+                            ping n - 1
+                        };
+                    "#,
+                ),
+            ],
+            expect![["game.pong panicked at 244..271: assertion failed: pong missed"]],
+        );
+    }
+
+    #[test]
+    fn local_functions() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        let a = { fnc f: s32 -> 1; f };
+                        let b = { fnc f: s32 -> 2; f };
+                        fnc scale(n: s32, by: s32 = 10): s32 -> n * by;
+                        scale a + b
+                    };
+                "#,
+            )],
+            expect![["S32(30)"]],
+        );
+    }
+
+    #[test]
+    fn local_function_in_test() {
+        check_test(
+            [(
+                "main",
+                r#"
+                    test "local functions" -> {
+                        fnc count_down(n: s32) -> {
+                            assert n > 0, "reached zero";
+                            count_down n - 1
+                        };
+                        count_down 2;
+                    };
+                "#,
+            )],
+            "local functions",
+            expect![["main.local functions::count_down panicked at 129..157: assertion failed: reached zero"]],
+        );
+    }
+
+    #[test]
+    fn local_with_ty() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        let small: bool = 1 < 2;
+                        let n: s32 = 5;
+                        assert small, "1 wasn’t less than 2";
+                        n
+                    };
+                "#,
+            )],
+            expect![["S32(5)"]],
+        );
+    }
+
+    #[test]
+    fn named_args() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> sub y: 3, x: 10;
+                    fnc sub(x: s32, y: s32 = 1): s32 -> x - y;
+                "#,
+            )],
+            expect![["S32(7)"]],
+        );
+    }
+
+    #[test]
+    fn named_args_are_evaluated_as_written() {
+        let printed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut host_functions = HostFunctions::default();
+        host_functions.register(hir::STD_MODULE_NAME, "print", {
+            let printed = printed.clone();
+            move |mut caller: wasmtime::Caller<'_, HostState>, s: i32| {
+                printed.lock().unwrap().push(read_string(&mut caller, s)?);
+                Ok(())
+            }
+        });
+
+        check_with_host_functions(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> f b: { std.print "b"; 1 }, a: { std.print "a"; 2 };
+                    fnc f(a: s32, b: s32, c: s32 = { std.print "c"; 3 }): s32 -> a * 100 + b * 10 + c;
+                "#,
+            )],
+            &host_functions,
+            expect![["S32(213)"]],
+        );
+
+        assert_eq!(*printed.lock().unwrap(), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn param_after_unit_param() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc f<T>(a: T, b: s32): s32 -> b;
+                    fnc main: s32 -> f {}, 3;
+                "#,
+            )],
+            expect![["S32(3)"]],
+        );
+    }
+
+    #[test]
+    fn return_unit_param() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc f<T>(x: T): T -> x;
+                    fnc main -> f {};
+                "#,
+            )],
+            expect![["Nil"]],
+        );
+    }
+
+    #[test]
+    fn default_arg_from_other_module() {
+        check(
+            [
+                ("main", "fnc main: s32 -> math.offset 1;"),
+                (
+                    "math",
+                    r#"
+                        fnc offset(n: s32, by: s32 = 100): s32 -> n + by;
+                    "#,
+                ),
+            ],
+            expect![["S32(101)"]],
+        );
+    }
+
+    #[test]
+    fn default_arg_referring_to_its_own_module() {
+        check(
+            [
+                ("main", "fnc main: s32 -> math.offset 1;"),
+                (
+                    "math",
+                    r#"
+                        fnc offset(n: s32, by: s32 = base): s32 -> n + by;
+                        fnc base: s32 -> 100;
+                    "#,
+                ),
+            ],
+            expect![["S32(101)"]],
+        );
+    }
+
+    #[test]
+    fn create_string() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        let s = "foo";
+                        0
+                    };
+                "#,
+            )],
+            expect![["S32(0)"]],
+        );
+    }
+
+    #[test]
+    fn return_string() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: string -> "hello";
+                "#,
+            )],
+            expect![[r#"String("hello")"#]],
+        );
+    }
+
+    #[test]
+    fn multiple_strings() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: string -> {
+                        let a = "foo";
+                        let b = "bar";
+                        let c = "baz";
+                        let d = "quux";
+                        c
+                    };
+                "#,
+            )],
+            expect![[r#"String("baz")"#]],
+        );
+    }
+
+    #[test]
+    fn generic_function_with_different_tys() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: string -> {
+                        let n = first 1, 2;
+                        first "a", "b"
+                    };
+                    fnc first<T>(a: T, b: T): T -> a;
+                "#,
+            )],
+            expect![[r#"String("a")"#]],
+        );
+    }
+
+    #[test]
+    fn generic_function_instantiated_with_optional() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc id<T>(v: T): T -> v;
+                    fnc nothing: s32? -> none;
+                    fnc main: (s32?, s32?) -> {
+                        let present: s32? = 5;
+                        let present = id present;
+                        let absent = id nothing;
+                        (present, absent)
+                    };
+                "#,
+            )],
+            expect![[r#"Tuple([Some(S32(5)), None])"#]],
+        );
+    }
+
+    #[test]
+    fn generic_optional_instantiated_with_value() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc wrap<T>(v: T): T? -> v;
+                    fnc first<T>(v: T?): T? -> {
+                        let inner = v?;
+                        inner
+                    };
+                    fnc nothing: string? -> none;
+                    fnc main: (s32?, string?, string?) -> {
+                        let wrapped = wrap 5;
+                        let present = first "a";
+                        let absent = first nothing;
+                        (wrapped, present, absent)
+                    };
+                "#,
+            )],
+            expect![[r#"Tuple([Some(S32(5)), Some(String("a")), None])"#]],
+        );
+    }
+
+    #[test]
+    fn interface_function() {
+        check(
+            [(
+                "main",
+                r#"
+                    ifc Describe {
+                        fnc describe(value: Self): string;
+                    };
+                    imp Describe for s32 {
+                        fnc describe(value: s32): string -> "a number";
+                    };
+                    imp Describe for string {
+                        fnc describe(value: string): string -> value;
+                    };
+                    fnc main: string -> {
+                        let number = describe 5;
+                        describe "text"
+                    };
+                "#,
+            )],
+            expect![[r#"String("text")"#]],
+        );
+    }
+
+    #[test]
+    fn interface_function_through_generic_param() {
+        check(
+            [(
+                "main",
+                r#"
+                    ifc Double {
+                        fnc double(value: Self): Self;
+                    };
+                    imp Double for s32 {
+                        fnc double(value: s32): s32 -> value * 2;
+                    };
+                    fnc quadruple<T: Double>(value: T): T -> double double value;
+                    fnc main: s32 -> quadruple 5;
+                "#,
+            )],
+            expect![["S32(20)"]],
+        );
+    }
+
+    #[test]
+    fn interface_from_other_module() {
+        check(
+            [
+                ("main", "fnc main: s32 -> shapes.sides 4;"),
+                (
+                    "shapes",
+                    r#"
+                        ifc Sides {
+                            fnc sides(value: Self): s32;
+                        };
+                        imp Sides for s32 {
+                            fnc sides(value: s32): s32 -> value;
+                        };
+                    "#,
+                ),
+            ],
+            expect![["S32(4)"]],
+        );
+    }
+
+    #[test]
+    fn return_tuple() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: (s32, string?, s32?) -> (1, "one", none);
+                "#,
+            )],
+            expect![[r#"Tuple([S32(1), Some(String("one")), None])"#]],
+        );
+    }
+
+    #[test]
+    fn tuple_field() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        let pair = (10, (3, "three"));
+                        pair.0 - pair.1.0
+                    };
+                "#,
+            )],
+            expect![["S32(7)"]],
+        );
+    }
+
+    #[test]
+    fn multiple_return_values() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        let (quotient, remainder) = div_rem 17, 5;
+                        quotient * 10 + remainder
+                    };
+                    fnc div_rem(a: s32, b: s32): (s32, s32) -> (a / b, a - a / b * b);
+                "#,
+            )],
+            expect![["S32(32)"]],
+        );
+    }
+
+    #[test]
+    fn destructure_nested_tuple_with_unit_and_optional() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32? -> {
+                        let two = maybe 2;
+                        let (_, (n, _), m) = (nothing, (two, nothing), 3);
+                        n? + m
+                    };
+                    fnc nothing -> {};
+                    fnc maybe(n: s32): s32? -> n;
+                "#,
+            )],
+            expect![["Some(S32(5))"]],
+        );
+    }
+
+    #[test]
+    fn generic_function_on_tuples() {
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: (string, s32) -> swap (1, "one");
+                    fnc swap<A, B>(pair: (A, B)): (B, A) -> (pair.1, pair.0);
+                "#,
+            )],
+            expect![[r#"Tuple([String("one"), S32(1)])"#]],
+        );
+    }
+
+    #[test]
+    fn many_tuples_grow_memory() {
+        // allocates 8⁵ tuples of 16 bytes each, which is well over a page
+        check(
+            [(
+                "main",
+                r#"
+                    fnc main: s32 -> {
+                        e;
+                        quad.3
+                    };
+                    fnc quad: (s32, s32, s32, s32) -> (1, 2, 3, 4);
+                    fnc a -> {
+                        let _ = quad;
+                        let _ = quad;
+                        let _ = quad;
+                        let _ = quad;
+                        let _ = quad;
+                        let _ = quad;
+                        let _ = quad;
+                        let _ = quad;
+                    };
+                    fnc b -> { a; a; a; a; a; a; a; a; };
+                    fnc c -> { b; b; b; b; b; b; b; b; };
+                    fnc d -> { c; c; c; c; c; c; c; c; };
+                    fnc e -> { d; d; d; d; d; d; d; d; };
+                "#,
+            )],
+            expect![["S32(4)"]],
         );
     }
 }
@@ -0,0 +1,709 @@
+use syntax::{NodeKind, SyntaxElement, SyntaxNode, SyntaxToken, SyntaxTree, TokenKind};
+
+const INDENT: &str = "    ";
+
+/// Formats a source file, returning `None` if it contains syntax errors,
+/// since we can’t tell how code the parser didn’t understand is meant to be laid out.
+pub fn format(input: &str) -> Option<String> {
+    let tokens = lexer::lex(input);
+    let parse = parser::parse_source_file(&tokens, input);
+
+    if !parse.errors().is_empty() {
+        return None;
+    }
+
+    let tree = parse.syntax_tree();
+    let mut formatter = Formatter {
+        tree,
+        out: String::new(),
+        indent: 0,
+        pending: Gap::None,
+        newlines: 0,
+        last: Last::Nothing,
+        lists: Vec::new(),
+    };
+    formatter.items(tree.root(), true);
+
+    let mut out = formatter.out;
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    Some(out)
+}
+
+// ordered so that requesting more whitespace wins over requesting less
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Gap {
+    None,
+    Space,
+    Newline,
+    BlankLine,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Last {
+    Nothing,
+    Token(TokenKind, NodeKind),
+    Comment,
+}
+
+struct Formatter<'a> {
+    tree: &'a SyntaxTree,
+    out: String,
+    indent: usize,
+    pending: Gap,
+    // how many line breaks the input had since the last thing we wrote,
+    // which lets us keep comments where they were and the blank lines the author left
+    newlines: usize,
+    last: Last,
+    // the indentation each list we're inside of started at,
+    // and whether a comment has moved the rest of it onto indented lines
+    lists: Vec<(usize, bool)>,
+}
+
+impl Formatter<'_> {
+    fn node(&mut self, node: SyntaxNode) {
+        match node.kind(self.tree) {
+            NodeKind::Block => self.block(node),
+            NodeKind::Record | NodeKind::RecordPat => self.braced_list(node),
+            NodeKind::Interface | NodeKind::Impl => self.function_block(node),
+            NodeKind::Docs => self.docs(node),
+            NodeKind::Comment => self.comment(node, false),
+            NodeKind::StringLiteral => {
+                self.request(Gap::Space);
+                self.write(node.text(self.tree));
+                self.last = Last::Token(TokenKind::Quote, NodeKind::StringLiteral);
+            }
+            _ => self.children(node),
+        }
+    }
+
+    fn children(&mut self, node: SyntaxNode) {
+        let kind = node.kind(self.tree);
+        let children: Vec<_> = node.children(self.tree).collect();
+
+        let mut in_list = matches!(
+            kind,
+            NodeKind::ParamList
+                | NodeKind::ArgList
+                | NodeKind::GenericParamList
+                | NodeKind::TupleExpr
+                | NodeKind::TuplePat
+                | NodeKind::TupleTy
+        );
+        if in_list {
+            self.lists.push((self.indent, false));
+        }
+
+        for (idx, child) in children.iter().enumerate() {
+            match *child {
+                SyntaxElement::Node(node) => self.node(node),
+                SyntaxElement::Token(token) => {
+                    let token_kind = token.kind(self.tree);
+                    if token_kind == TokenKind::Comma
+                        && self.is_trailing_comma(node, &children[idx + 1..])
+                    {
+                        continue;
+                    }
+
+                    // the closing delimiter lines up with the start of the list
+                    if in_list && matches!(token_kind, TokenKind::RParen | TokenKind::Greater) {
+                        self.end_list();
+                        in_list = false;
+                    }
+
+                    self.token(token, kind);
+                }
+            }
+        }
+
+        if in_list {
+            self.end_list();
+        }
+    }
+
+    fn end_list(&mut self) {
+        if let Some((_, true)) = self.lists.pop() {
+            self.indent -= 1;
+        }
+    }
+
+    fn items(&mut self, node: SyntaxNode, is_root: bool) {
+        let kind = node.kind(self.tree);
+        let mut last_was_def = false;
+
+        // interfaces and implementations have a header before their braces
+        let mut in_items = is_root;
+
+        for child in node.children(self.tree) {
+            match child {
+                SyntaxElement::Token(token) => match token.kind(self.tree) {
+                    TokenKind::LBrace => {
+                        self.token(token, kind);
+                        self.indent += 1;
+                        in_items = true;
+                    }
+                    TokenKind::RBrace => {
+                        self.indent -= 1;
+                        self.request(Gap::Newline);
+                        self.token(token, kind);
+                        in_items = false;
+                    }
+                    _ => self.token(token, kind),
+                },
+                SyntaxElement::Node(node) if !in_items => self.node(node),
+                SyntaxElement::Node(node) if node.kind(self.tree) == NodeKind::Comment => {
+                    // top-level definitions are always separated by a blank line
+                    let is_trailing = self.newlines == 0;
+                    self.comment(node, last_was_def && is_root);
+                    if !is_trailing {
+                        last_was_def = false;
+                    }
+                }
+                SyntaxElement::Node(node) => {
+                    let is_def = matches!(
+                        node.kind(self.tree),
+                        NodeKind::Function
+                            | NodeKind::Record
+                            | NodeKind::Test
+                            | NodeKind::Interface
+                            | NodeKind::Impl
+                    );
+
+                    self.request(self.line_break(last_was_def && is_root));
+                    self.node(node);
+                    last_was_def = is_def;
+                }
+            }
+        }
+    }
+
+    fn block(&mut self, node: SyntaxNode) {
+        let has_statements = node.child_nodes(self.tree).any(|node| {
+            matches!(
+                node.kind(self.tree),
+                NodeKind::LocalDef
+                    | NodeKind::ExprStatement
+                    | NodeKind::Function
+                    | NodeKind::Record
+                    | NodeKind::Comment
+            )
+        });
+
+        // blocks with nothing but an expression are often used for grouping,
+        // as in `{a + b} * c`, so we keep them on one line if they already are
+        if has_statements || node.text(self.tree).contains('\n') {
+            self.items(node, false);
+        } else {
+            self.children(node);
+        }
+    }
+
+    fn braced_list(&mut self, node: SyntaxNode) {
+        let kind = node.kind(self.tree);
+        let children: Vec<_> = node.children(self.tree).collect();
+
+        let l_brace = children.iter().position(|child| self.is_token(*child, TokenKind::LBrace));
+        let is_multiline = match l_brace {
+            Some(l_brace) => children[l_brace..].iter().any(|child| match child {
+                SyntaxElement::Token(token) => token.text(self.tree).contains('\n'),
+                SyntaxElement::Node(node) => node.text(self.tree).contains('\n'),
+            }),
+            None => false,
+        };
+
+        if !is_multiline {
+            return self.children(node);
+        }
+
+        for (idx, child) in children.iter().enumerate() {
+            match *child {
+                SyntaxElement::Token(token) => match token.kind(self.tree) {
+                    TokenKind::LBrace => {
+                        self.token(token, kind);
+                        self.indent += 1;
+                    }
+                    TokenKind::RBrace => {
+                        self.indent -= 1;
+                        self.request(Gap::Newline);
+                        self.token(token, kind);
+                    }
+                    _ => self.token(token, kind),
+                },
+                SyntaxElement::Node(node) => match node.kind(self.tree) {
+                    NodeKind::Field | NodeKind::RecordPatField => {
+                        self.request(self.line_break(false));
+                        self.node(node);
+
+                        // every field of a multiline list ends in a comma
+                        let next = self.next_significant(&children[idx + 1..]);
+                        if next.is_none_or(|next| !self.is_token(next, TokenKind::Comma)) {
+                            self.write(",");
+                            self.last = Last::Token(TokenKind::Comma, kind);
+                        }
+                    }
+                    _ => self.node(node),
+                },
+            }
+        }
+    }
+
+    fn function_block(&mut self, node: SyntaxNode) {
+        let has_functions =
+            node.child_nodes(self.tree).any(|node| node.kind(self.tree) != NodeKind::Docs);
+
+        if has_functions {
+            self.items(node, false);
+        } else {
+            self.children(node);
+        }
+    }
+
+    fn docs(&mut self, node: SyntaxNode) {
+        for doc_comment in node.child_nodes(self.tree) {
+            self.request(Gap::Newline);
+            self.write(doc_comment.text(self.tree).trim_end());
+            self.last = Last::Comment;
+        }
+
+        self.request(Gap::Newline);
+    }
+
+    fn comment(&mut self, node: SyntaxNode, force_blank_line: bool) {
+        // comments at the end of a line stay there
+        if self.newlines == 0 && self.last != Last::Nothing {
+            self.pending = Gap::Space;
+        } else {
+            self.request(self.line_break(force_blank_line));
+        }
+
+        self.write(node.text(self.tree).trim_end());
+        self.last = Last::Comment;
+        self.request(Gap::Newline);
+
+        // the rest of a list continues on the lines after the comment
+        if let Some((indent, indented)) = self.lists.last_mut() {
+            if *indent == self.indent && !*indented {
+                *indented = true;
+                self.indent += 1;
+            }
+        }
+    }
+
+    fn token(&mut self, token: SyntaxToken, parent: NodeKind) {
+        let kind = token.kind(self.tree);
+        let text = token.text(self.tree);
+
+        if kind == TokenKind::Whitespace {
+            self.newlines += text.matches('\n').count();
+            return;
+        }
+
+        if !self.hugs_last(kind, parent) {
+            self.request(Gap::Space);
+        }
+
+        self.write(text);
+        self.last = Last::Token(kind, parent);
+    }
+
+    fn hugs_last(&self, kind: TokenKind, parent: NodeKind) -> bool {
+        let (last, last_parent) = match self.last {
+            Last::Token(last, last_parent) => (last, last_parent),
+            Last::Comment | Last::Nothing => return false,
+        };
+
+        let hugs_left = match kind {
+            TokenKind::Comma
+            | TokenKind::Semicolon
+            | TokenKind::Colon
+            | TokenKind::Question
+            | TokenKind::Dot
+            | TokenKind::RParen => true,
+            TokenKind::Less | TokenKind::Greater => parent == NodeKind::GenericParamList,
+            TokenKind::LParen => parent == NodeKind::ParamList,
+            TokenKind::RBrace => parent == NodeKind::Block || last == TokenKind::LBrace,
+            _ => false,
+        };
+
+        let hugs_right = match last {
+            TokenKind::LParen | TokenKind::Dot => true,
+            TokenKind::Less => last_parent == NodeKind::GenericParamList,
+            TokenKind::LBrace => last_parent == NodeKind::Block,
+            _ => false,
+        };
+
+        hugs_left || hugs_right
+    }
+
+    fn line_break(&self, force_blank_line: bool) -> Gap {
+        let just_opened = matches!(self.last, Last::Token(TokenKind::LBrace, _));
+
+        if force_blank_line || (self.newlines >= 2 && !just_opened) {
+            Gap::BlankLine
+        } else {
+            Gap::Newline
+        }
+    }
+
+    fn request(&mut self, gap: Gap) {
+        self.pending = self.pending.max(gap);
+    }
+
+    fn write(&mut self, text: &str) {
+        if !self.out.is_empty() {
+            match self.pending {
+                Gap::None => {}
+                Gap::Space => self.out.push(' '),
+                Gap::Newline | Gap::BlankLine => {
+                    if self.pending == Gap::BlankLine {
+                        self.out.push('\n');
+                    }
+                    self.out.push('\n');
+                    for _ in 0..self.indent {
+                        self.out.push_str(INDENT);
+                    }
+                }
+            }
+        }
+
+        self.out.push_str(text);
+        self.pending = Gap::None;
+        self.newlines = 0;
+    }
+
+    // trailing commas are kept in tuples of one element to tell them apart from parentheses
+    fn is_trailing_comma(&self, list: SyntaxNode, rest: &[SyntaxElement]) -> bool {
+        let is_trailing = match self.next_significant(rest) {
+            Some(next) => {
+                self.is_token(next, TokenKind::RParen)
+                    || self.is_token(next, TokenKind::RBrace)
+                    || self.is_token(next, TokenKind::Greater)
+            }
+            None => true,
+        };
+
+        if !is_trailing {
+            return false;
+        }
+
+        match list.kind(self.tree) {
+            NodeKind::TupleExpr | NodeKind::TuplePat | NodeKind::TupleTy => {
+                let elements = list
+                    .child_nodes(self.tree)
+                    .filter(|node| node.kind(self.tree) != NodeKind::Comment)
+                    .count();
+                elements > 1
+            }
+            _ => true,
+        }
+    }
+
+    fn next_significant(&self, elements: &[SyntaxElement]) -> Option<SyntaxElement> {
+        elements.iter().copied().find(|element| match element {
+            SyntaxElement::Token(token) => token.kind(self.tree) != TokenKind::Whitespace,
+            SyntaxElement::Node(node) => node.kind(self.tree) != NodeKind::Comment,
+        })
+    }
+
+    fn is_token(&self, element: SyntaxElement, kind: TokenKind) -> bool {
+        matches!(element, SyntaxElement::Token(token) if token.kind(self.tree) == kind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    fn check(input: &str, expect: Expect) {
+        let formatted = format(input).expect("input should parse without errors");
+        expect.assert_eq(&formatted);
+
+        assert_eq!(format(&formatted).as_deref(), Some(formatted.as_str()), "not idempotent");
+    }
+
+    #[test]
+    fn empty() {
+        check("", expect![[""]]);
+        check("  \n\n", expect![[""]]);
+    }
+
+    #[test]
+    fn function_spacing() {
+        check(
+            "fnc   add ( x :s32,y:s32 ) :s32->x+y ;",
+            expect![[r#"
+                fnc add(x: s32, y: s32): s32 -> x + y;
+            "#]],
+        );
+    }
+
+    #[test]
+    fn extern_function_and_generics() {
+        check(
+            "ext fnc print( s : string );\nfnc show < T : Describe+Eq , U > ( v : T , ) -> print describe v;",
+            expect![[r#"
+                ext fnc print(s: string);
+
+                fnc show<T: Describe + Eq, U>(v: T) -> print describe v;
+            "#]],
+        );
+    }
+
+    #[test]
+    fn call_arguments() {
+        check(
+            "fnc main -> greet name:\"world\" ,times :3,{ std.abs 0-20 } ;",
+            expect![[r#"
+                fnc main -> greet name: "world", times: 3, {std.abs 0 - 20};
+            "#]],
+        );
+    }
+
+    #[test]
+    fn block_indentation() {
+        check(
+            r#"
+fnc main: s32 -> {
+let a = 1;
+      let b: s32 = a*2;
+
+
+  a+b
+};"#,
+            expect![[r#"
+                fnc main: s32 -> {
+                    let a = 1;
+                    let b: s32 = a * 2;
+
+                    a + b
+                };
+            "#]],
+        );
+    }
+
+    #[test]
+    fn nested_blocks() {
+        check(
+            "fnc main -> { let x = { let y = 1; y }; { x } };",
+            expect![[r#"
+                fnc main -> {
+                    let x = {
+                        let y = 1;
+                        y
+                    };
+                    {x}
+                };
+            "#]],
+        );
+    }
+
+    #[test]
+    fn empty_block() {
+        check(
+            "fnc main -> {   };",
+            expect![[r#"
+                fnc main -> {};
+            "#]],
+        );
+    }
+
+    #[test]
+    fn records() {
+        check(
+            "rec point{x:s32,y:s32,};\nrec unit { };\nrec big {\na: s32, b: string\n};",
+            expect![[r#"
+                rec point { x: s32, y: s32 };
+
+                rec unit {};
+
+                rec big {
+                    a: s32,
+                    b: string,
+                };
+            "#]],
+        );
+    }
+
+    #[test]
+    fn patterns() {
+        check(
+            "fnc main -> { let Point{x,y:_}=p; let ( a ,b, ) = pair; let (c,) = one; };",
+            expect![[r#"
+                fnc main -> {
+                    let Point { x, y: _ } = p;
+                    let (a, b) = pair;
+                    let (c,) = one;
+                };
+            "#]],
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        check(
+            "fnc pair: ( s32,string ) -> ( 1 , \"a\", );\nfnc one: (s32,) -> (1,);",
+            expect![[r#"
+                fnc pair: (s32, string) -> (1, "a");
+
+                fnc one: (s32,) -> (1,);
+            "#]],
+        );
+    }
+
+    #[test]
+    fn optionals_and_tuple_fields() {
+        check(
+            "fnc f(p: (s32?, s32)): s32? -> { let v = p.0 ?; p . 1 };",
+            expect![[r#"
+                fnc f(p: (s32?, s32)): s32? -> {
+                    let v = p.0?;
+                    p.1
+                };
+            "#]],
+        );
+    }
+
+    #[test]
+    fn contracts() {
+        check(
+            "fnc div(a: s32, b: s32): s32 requires b!=0 ensures r:r<=a -> a/b;",
+            expect![[r#"
+                fnc div(a: s32, b: s32): s32 requires b != 0 ensures r: r <= a -> a / b;
+            "#]],
+        );
+    }
+
+    #[test]
+    fn interface_and_impl() {
+        check(
+            "ifc Describe { ## Describes the value.\nfnc describe(value: Self): string; };\nimp Describe for s32 {fnc describe(value: s32): string -> std.format_int value;};\nifc Empty{};",
+            expect![[r#"
+                ifc Describe {
+                    ## Describes the value.
+                    fnc describe(value: Self): string;
+                };
+
+                imp Describe for s32 {
+                    fnc describe(value: s32): string -> std.format_int value;
+                };
+
+                ifc Empty {};
+            "#]],
+        );
+    }
+
+    #[test]
+    fn tests() {
+        check(
+            "test   \"adds  numbers\"->std.assert_eq 2, 1+1;",
+            expect![[r#"
+                test "adds  numbers" -> std.assert_eq 2, 1 + 1;
+            "#]],
+        );
+    }
+
+    #[test]
+    fn blank_lines_between_definitions() {
+        check(
+            "fnc a -> {};\nfnc b -> {};\n\n\n\nfnc c -> {};",
+            expect![[r#"
+                fnc a -> {};
+
+                fnc b -> {};
+
+                fnc c -> {};
+            "#]],
+        );
+    }
+
+    #[test]
+    fn comments() {
+        check(
+            r#"
+# The header.
+#
+# More details.
+
+## Adds one.
+fnc inc(n: s32): s32 -> n + 1;   # trailing
+# about two
+fnc two: s32 -> {
+    # leading
+    let one = 1; # trailing in block
+
+
+    # after a blank line
+    inc one
+};"#,
+            expect![[r#"
+                # The header.
+                #
+                # More details.
+
+                ## Adds one.
+                fnc inc(n: s32): s32 -> n + 1; # trailing
+
+                # about two
+                fnc two: s32 -> {
+                    # leading
+                    let one = 1; # trailing in block
+
+                    # after a blank line
+                    inc one
+                };
+            "#]],
+        );
+    }
+
+    #[test]
+    fn comments_in_records() {
+        check(
+            "rec point {\n    x: s32, # across\n    # down\n    y: s32\n};",
+            expect![[r#"
+                rec point {
+                    x: s32, # across
+                    # down
+                    y: s32,
+                };
+            "#]],
+        );
+    }
+
+    #[test]
+    fn comments_in_params_and_args() {
+        check(
+            "fnc f(a: s32, # first\nb: s32): s32 -> add a, # left\nb;\nfnc g(# none\n) -> {\nlet t = (1, # one\n2);\nf 1, 2\n};",
+            expect![[r##"
+                fnc f(a: s32, # first
+                    b: s32): s32 -> add a, # left
+                    b;
+
+                fnc g( # none
+                ) -> {
+                    let t = (1, # one
+                        2);
+                    f 1, 2
+                };
+            "##]],
+        );
+    }
+
+    #[test]
+    fn escapes_in_strings() {
+        check(
+            r#"fnc main -> std.print "a  \"b\"  c";"#,
+            expect![[r#"
+                fnc main -> std.print "a  \"b\"  c";
+            "#]],
+        );
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert_eq!(format("fnc main -> ;"), None);
+        assert_eq!(format("let a = 1;"), None);
+    }
+}
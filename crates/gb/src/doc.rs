@@ -0,0 +1,484 @@
+use hir::{Definition, Index, Name, Ty};
+use interner::Interner;
+use serde_json::{json, Value};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const STYLE: &str = r#"body {
+    display: flex;
+    margin: 0;
+    font-family: system-ui, sans-serif;
+    line-height: 1.5;
+}
+
+nav {
+    width: 16rem;
+    padding: 1rem;
+    border-right: 1px solid #ddd;
+}
+
+nav input {
+    box-sizing: border-box;
+    width: 100%;
+    margin: 1rem 0;
+}
+
+nav ul {
+    padding: 0;
+    list-style: none;
+}
+
+nav p {
+    margin: 0 0 0.5rem;
+    color: #666;
+    font-size: 0.875rem;
+}
+
+main {
+    max-width: 48rem;
+    padding: 1rem 2rem;
+}
+
+pre {
+    padding: 0.75rem;
+    background: #f5f5f5;
+    overflow-x: auto;
+}
+
+section {
+    margin-bottom: 2rem;
+}
+"#;
+
+const SEARCH_SCRIPT: &str = r#"// Shows the definitions whose path contains what has been typed into the search box.
+(function () {
+    const input = document.getElementById("search");
+    const results = document.getElementById("search-results");
+
+    input.addEventListener("input", () => {
+        const query = input.value.trim().toLowerCase();
+        results.replaceChildren();
+
+        if (query === "") {
+            return;
+        }
+
+        const matches = SEARCH_INDEX.filter(item =>
+            (item.module + "." + item.name).toLowerCase().includes(query));
+
+        for (const item of matches.slice(0, 50)) {
+            const li = document.createElement("li");
+            const a = document.createElement("a");
+            a.href = item.href;
+            a.textContent = item.kind + " " + item.module + "." + item.name;
+            li.append(a);
+
+            if (item.summary !== "") {
+                const p = document.createElement("p");
+                p.textContent = item.summary;
+                li.append(p);
+            }
+
+            results.append(li);
+        }
+    });
+})();
+"#;
+
+pub(crate) fn generate(
+    project: &hir::Project,
+    world_index: &hir::WorldIndex,
+    interner: &Interner,
+    out_dir: &Path,
+) -> io::Result<()> {
+    // dependencies and `std` are documented by their own authors
+    let mut modules: Vec<_> = project
+        .modules()
+        .filter(|module| module.package.is_none())
+        .map(|module| (interner.lookup(module.name.0), world_index.module_index(module.name)))
+        .filter(|(name, _)| *name != hir::STD_MODULE_NAME)
+        .collect();
+    modules.sort_unstable_by_key(|(name, _)| *name);
+
+    fs::create_dir_all(out_dir)?;
+
+    let mut search_index = Vec::new();
+    let mut module_list = String::from("<h1>Modules</h1>\n<ul>\n");
+
+    for (module, index) in &modules {
+        let items = items(index, interner);
+
+        for item in &items {
+            search_index.push(json!({
+                "name": item.text,
+                "module": module,
+                "kind": item.kind(),
+                "href": format!("{module}.html#{}", item.anchor()),
+                "summary": item.docs.and_then(|docs| docs.paras.first()).map_or("", |p| p),
+            }));
+        }
+
+        module_list.push_str(&format!("<li><a href=\"{module}.html\">{module}</a></li>\n"));
+
+        let content = module_page(module, index, &items, interner);
+        fs::write(out_dir.join(format!("{module}.html")), page(module, project, &content))?;
+    }
+
+    module_list.push_str("</ul>\n");
+    fs::write(out_dir.join("index.html"), page(project.name(), project, &module_list))?;
+
+    let search_index = Value::Array(search_index);
+    fs::write(out_dir.join("search-index.js"), format!("const SEARCH_INDEX = {search_index};\n"))?;
+    fs::write(out_dir.join("search.js"), SEARCH_SCRIPT)?;
+    fs::write(out_dir.join("style.css"), STYLE)?;
+
+    Ok(())
+}
+
+struct Item<'a> {
+    name: Name,
+    text: &'a str,
+    definition: &'a Definition,
+    docs: Option<&'a hir::Docs>,
+}
+
+impl Item<'_> {
+    fn kind(&self) -> &'static str {
+        match self.definition {
+            Definition::Function(_) => "fnc",
+            Definition::Record(_) => "rec",
+            Definition::Interface(_) => "ifc",
+        }
+    }
+
+    fn anchor(&self) -> String {
+        format!("{}.{}", self.kind(), self.text)
+    }
+}
+
+fn items<'a>(index: &'a Index, interner: &'a Interner) -> Vec<Item<'a>> {
+    let mut names: Vec<_> = index
+        .definition_names()
+        .filter(|name| !index.is_local_definition(*name))
+        .filter(|name| match index.get_definition(*name) {
+            // functions declared by interfaces are shown with their interface
+            Some(Definition::Function(function)) => function.interface.is_none(),
+            _ => true,
+        })
+        .collect();
+    names.sort_unstable_by_key(|name| index.range_info(*name).whole.start());
+
+    names
+        .into_iter()
+        .map(|name| Item {
+            name,
+            text: interner.lookup(name.0),
+            definition: index.get_definition(name).unwrap(),
+            docs: index.docs(name),
+        })
+        .collect()
+}
+
+fn module_page(module: &str, index: &Index, items: &[Item<'_>], interner: &Interner) -> String {
+    let mut s = format!("<h1>Module <code>{}</code></h1>\n", escape(module));
+
+    for (kind, heading) in [("rec", "Records"), ("ifc", "Interfaces"), ("fnc", "Functions")] {
+        let items: Vec<_> = items.iter().filter(|item| item.kind() == kind).collect();
+        if items.is_empty() {
+            continue;
+        }
+
+        s.push_str(&format!("<h2>{heading}</h2>\n"));
+
+        for item in items {
+            s.push_str(&format!("<section id=\"{}\">\n<pre><code>", escape(&item.anchor())));
+
+            match item.definition {
+                Definition::Function(function) => {
+                    s.push_str(&function_signature(item.name, function, index, interner));
+                }
+                Definition::Record(record) => {
+                    s.push_str(&format!("rec {} {{\n", escape(item.text)));
+                    for field in &record.fields {
+                        s.push_str(&format!(
+                            "    {}: {},\n",
+                            field.name.map_or("?", |name| interner.lookup(name.0)),
+                            ty(&field.ty, index, interner)
+                        ));
+                    }
+                    s.push('}');
+                }
+                Definition::Interface(interface) => {
+                    s.push_str(&format!("ifc {} {{\n", escape(item.text)));
+                    for function_name in &interface.functions {
+                        if let Some(Definition::Function(function)) =
+                            index.get_definition(*function_name)
+                        {
+                            let paras = index.docs(*function_name).map_or(&[][..], |d| &d.paras);
+                            for (idx, para) in paras.iter().enumerate() {
+                                if idx != 0 {
+                                    s.push_str("    ##\n");
+                                }
+                                s.push_str(&format!("    ## {}\n", escape(para)));
+                            }
+
+                            let signature =
+                                function_signature(*function_name, function, index, interner);
+                            s.push_str(&format!("    {signature};\n"));
+                        }
+                    }
+                    s.push('}');
+                }
+            }
+
+            s.push_str("</code></pre>\n");
+
+            if let Some(docs) = item.docs {
+                for para in &docs.paras {
+                    s.push_str(&format!("<p>{}</p>\n", inline_code(&escape(para))));
+                }
+            }
+
+            s.push_str("</section>\n");
+        }
+    }
+
+    s
+}
+
+fn function_signature(
+    name: Name,
+    function: &hir::Function,
+    index: &Index,
+    interner: &Interner,
+) -> String {
+    let mut s = String::new();
+
+    if function.is_extern {
+        s.push_str("ext ");
+    }
+
+    s.push_str(&format!("fnc {}", escape(interner.lookup(name.0))));
+
+    // the type an interface is implemented for is an implicit first parameter
+    let generic_params = match function.interface {
+        Some(_) => &function.generic_params[1..],
+        None => &function.generic_params[..],
+    };
+
+    if !generic_params.is_empty() {
+        s.push_str("&lt;");
+
+        for (idx, generic_param) in generic_params.iter().enumerate() {
+            if idx != 0 {
+                s.push_str(", ");
+            }
+
+            s.push_str(&escape(interner.lookup(generic_param.name.0)));
+
+            for (idx, bound) in generic_param.bounds.iter().enumerate() {
+                s.push_str(if idx == 0 { ": " } else { " + " });
+                s.push_str(&named_ty(*bound, index, interner));
+            }
+        }
+
+        s.push_str("&gt;");
+    }
+
+    if !function.params.is_empty() {
+        s.push('(');
+
+        for (idx, param) in function.params.iter().enumerate() {
+            if idx != 0 {
+                s.push_str(", ");
+            }
+
+            s.push_str(&format!(
+                "{}: {}",
+                escape(param.name.map_or("?", |name| interner.lookup(name.0))),
+                ty(&param.ty, index, interner)
+            ));
+
+            if param.has_default {
+                s.push_str(" = …");
+            }
+        }
+
+        s.push(')');
+    }
+
+    if function.return_ty != Ty::Unit {
+        s.push_str(&format!(": {}", ty(&function.return_ty, index, interner)));
+    }
+
+    s
+}
+
+fn ty(ty: &Ty, index: &Index, interner: &Interner) -> String {
+    match ty {
+        Ty::Named(name) => named_ty(*name, index, interner),
+        Ty::Optional(ty) => format!("{}?", self::ty(ty, index, interner)),
+        Ty::Tuple(tys) if tys.len() == 1 => format!("({},)", self::ty(&tys[0], index, interner)),
+        Ty::Tuple(tys) => {
+            let tys: Vec<_> = tys.iter().map(|ty| self::ty(ty, index, interner)).collect();
+            format!("({})", tys.join(", "))
+        }
+        _ => escape(&ty.display(interner)),
+    }
+}
+
+fn named_ty(name: Name, index: &Index, interner: &Interner) -> String {
+    let text = escape(interner.lookup(name.0));
+
+    let kind = match index.get_definition(name) {
+        Some(Definition::Record(_)) => "rec",
+        Some(Definition::Interface(_)) => "ifc",
+        Some(Definition::Function(_)) | None => return text,
+    };
+
+    if index.is_local_definition(name) {
+        return text;
+    }
+
+    format!("<a href=\"#{kind}.{text}\">{text}</a>")
+}
+
+fn page(title: &str, project: &hir::Project, content: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<link rel="stylesheet" href="style.css">
+</head>
+<body>
+<nav>
+<a href="index.html">{project}</a>
+<input id="search" type="search" placeholder="Search" autocomplete="off">
+<ul id="search-results"></ul>
+</nav>
+<main>
+{content}</main>
+<script src="search-index.js"></script>
+<script src="search.js"></script>
+</body>
+</html>
+"#,
+        title = escape(title),
+        project = escape(project.name()),
+    )
+}
+
+fn inline_code(text: &str) -> String {
+    let mut s = String::new();
+
+    for (idx, part) in text.split('`').enumerate() {
+        if idx % 2 == 1 {
+            s.push_str(&format!("<code>{part}</code>"));
+        } else {
+            s.push_str(part);
+        }
+    }
+
+    s
+}
+
+fn escape(text: &str) -> String {
+    let mut s = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => s.push_str("&amp;"),
+            '<' => s.push_str("&lt;"),
+            '>' => s.push_str("&gt;"),
+            '"' => s.push_str("&quot;"),
+            _ => s.push(c),
+        }
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    #[test]
+    fn module_page_and_search_index() {
+        let (root, global_state) = crate::open_fixture(
+            "doc",
+            &[
+                ("gb.toml", "[package]\nname = \"a<b>\"\n"),
+                (
+                    "shapes.gb",
+                    r#"
+## A point on a grid.
+rec Point { x: s32, y: s32 };
+
+## Things which can be shown.
+ifc Show {
+    ## Shows `value` as <text> & more.
+    fnc show(value: Self): string;
+};
+
+## Moves `p` by `dx` if `dx > 0`.
+##
+## Leaves `p` alone otherwise.
+fnc shift<T: Show>(p: Point, dx: s32 = 1, label: T): Point? -> none;
+"#,
+                ),
+            ],
+        );
+
+        let out_dir = root.join("doc");
+        generate(
+            global_state.project().unwrap(),
+            global_state.world_index(),
+            global_state.interner(),
+            &out_dir,
+        )
+        .unwrap();
+
+        let page = fs::read_to_string(out_dir.join("shapes.html")).unwrap();
+        let main = &page[page.find("<main>").unwrap()..page.find("</main>").unwrap()];
+        expect![[r##"
+            <main>
+            <h1>Module <code>shapes</code></h1>
+            <h2>Records</h2>
+            <section id="rec.Point">
+            <pre><code>rec Point {
+                x: s32,
+                y: s32,
+            }</code></pre>
+            <p>A point on a grid.</p>
+            </section>
+            <h2>Interfaces</h2>
+            <section id="ifc.Show">
+            <pre><code>ifc Show {
+                ## Shows `value` as &lt;text&gt; &amp; more.
+                fnc show(value: Self): string;
+            }</code></pre>
+            <p>Things which can be shown.</p>
+            </section>
+            <h2>Functions</h2>
+            <section id="fnc.shift">
+            <pre><code>fnc shift&lt;T: <a href="#ifc.Show">Show</a>&gt;(p: <a href="#rec.Point">Point</a>, dx: s32 = …, label: T): <a href="#rec.Point">Point</a>?</code></pre>
+            <p>Moves <code>p</code> by <code>dx</code> if <code>dx &gt; 0</code>.</p>
+            <p>Leaves <code>p</code> alone otherwise.</p>
+            </section>
+        "##]]
+        .assert_eq(main);
+
+        let search_index = fs::read_to_string(out_dir.join("search-index.js")).unwrap();
+        expect![[r##"
+            const SEARCH_INDEX = [{"href":"shapes.html#rec.Point","kind":"rec","module":"shapes","name":"Point","summary":"A point on a grid."},{"href":"shapes.html#ifc.Show","kind":"ifc","module":"shapes","name":"Show","summary":"Things which can be shown."},{"href":"shapes.html#fnc.shift","kind":"fnc","module":"shapes","name":"shift","summary":"Moves `p` by `dx` if `dx > 0`."}];
+        "##]]
+        .assert_eq(&search_index);
+
+        let index = fs::read_to_string(out_dir.join("index.html")).unwrap();
+        assert!(index.contains("<title>a&lt;b&gt;</title>"));
+    }
+}
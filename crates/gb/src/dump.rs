@@ -0,0 +1,146 @@
+use anyhow::bail;
+use ast::{AstNode, AstToken};
+use diagnostics::Severity;
+use line_index::LineIndex;
+use syntax::{SyntaxElement, SyntaxNode, SyntaxTree};
+
+#[derive(Clone, Copy)]
+pub(crate) enum Stage {
+    Tokens,
+    Cst,
+    Hir,
+    Types,
+    Wasm,
+}
+
+impl Stage {
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tokens" => Some(Self::Tokens),
+            "cst" => Some(Self::Cst),
+            "hir" => Some(Self::Hir),
+            "types" => Some(Self::Types),
+            "wasm" => Some(Self::Wasm),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn dump(stage: Stage, input: &str, function: Option<&str>) -> anyhow::Result<String> {
+    let tokens = lexer::lex(input);
+    let parse = parser::parse_source_file(&tokens, input);
+    let tree = parse.syntax_tree();
+    let root = ast::Root::cast(tree.root(), tree).unwrap();
+
+    let function_node = match function {
+        Some(name) => {
+            let node = root.defs(tree).find_map(|def| match def {
+                ast::Def::Function(function)
+                    if function.name(tree).map(|n| n.text(tree)) == Some(name) =>
+                {
+                    Some(function.syntax())
+                }
+                _ => None,
+            });
+
+            match node {
+                Some(node) => Some(node),
+                None => bail!("there is no function named `{name}`"),
+            }
+        }
+        None => None,
+    };
+
+    match (stage, function_node) {
+        (Stage::Tokens, None) => return Ok(format!("{tokens:?}")),
+        (Stage::Tokens, Some(node)) => {
+            let range = node.range(tree);
+            let tokens: Vec<_> = tokens
+                .iter()
+                .filter(|(_, token_range)| range.contains_range(*token_range))
+                .map(|(kind, range)| format!("{kind:?}@{range:?}"))
+                .collect();
+            return Ok(tokens.join("\n"));
+        }
+        (Stage::Cst, None) => return Ok(format!("{parse:?}")),
+        (Stage::Cst, Some(node)) => {
+            let mut s = String::new();
+            write_node(node, tree, 0, &mut s);
+            return Ok(s.trim_end().to_string());
+        }
+        (Stage::Hir | Stage::Types | Stage::Wasm, _) => {}
+    }
+
+    let ide::Program { mut interner, world_index, bodies_map, tys_map, mut diagnostics } =
+        ide::analyze_sources(&[("main", input)]);
+    let main_name = hir::Name(interner.intern("main"));
+
+    let function = function.map(|name| hir::Name(interner.intern(name)));
+
+    let bodies = &bodies_map[&main_name];
+    let tys = &tys_map[&main_name];
+    match (stage, function) {
+        (Stage::Hir, None) => return Ok(bodies.debug(&interner)),
+        (Stage::Hir, Some(name)) => return Ok(bodies.debug_function(name, &interner)),
+        (Stage::Types, None) => return Ok(tys.debug(&interner)),
+        (Stage::Types, Some(name)) => return Ok(tys.debug_function(name, bodies, &interner)),
+        _ => {}
+    }
+
+    // code generation relies on the program being free of errors
+    let errors: Vec<_> = diagnostics
+        .remove(&main_name)
+        .unwrap()
+        .into_iter()
+        .filter(|d| d.severity() == Severity::Error)
+        .collect();
+
+    if !errors.is_empty() {
+        let line_index = LineIndex::new(input);
+        let lines: Vec<_> =
+            errors.iter().flat_map(|d| d.display(input, &interner, &line_index)).collect();
+        bail!("cannot compile a file with errors:\n{}", lines.join("\n"));
+    }
+
+    let entry_point = hir::Fqn { module: main_name, name: function.unwrap_or(main_name) };
+    match world_index.get_definition(entry_point) {
+        Ok(hir::Definition::Function(f)) if f.has_body() && f.generic_params.is_empty() => {}
+        _ => bail!(
+            "`{}` is not a function that can be compiled on its own",
+            interner.lookup(entry_point.name.0)
+        ),
+    }
+
+    let wasm = eval::compile(
+        entry_point,
+        bodies_map,
+        tys_map,
+        &world_index,
+        &interner,
+        eval::CompileOptions::default(),
+    );
+
+    wasmprinter::print_bytes(&wasm)
+}
+
+fn write_node(node: SyntaxNode, tree: &SyntaxTree, indentation: usize, s: &mut String) {
+    s.push_str(&format!(
+        "{}{:?}@{:?}\n",
+        "  ".repeat(indentation),
+        node.kind(tree),
+        node.range(tree)
+    ));
+
+    for child in node.children(tree) {
+        match child {
+            SyntaxElement::Node(node) => write_node(node, tree, indentation + 1, s),
+            SyntaxElement::Token(token) => s.push_str(&format!(
+                "{}{:?}@{:?} {:?}\n",
+                "  ".repeat(indentation + 1),
+                token.kind(tree),
+                token.range(tree),
+                token.text(tree)
+            )),
+        }
+    }
+}
@@ -9,10 +9,10 @@ use lsp_types::notification::{PublishDiagnostics, ShowMessage};
 use lsp_types::request::SemanticTokensRefesh as SemanticTokensRefresh;
 use lsp_types::{
     Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
-    GotoDefinitionParams, GotoDefinitionResponse, Location, LocationLink, MessageType, Position,
-    PublishDiagnosticsParams, Range, SelectionRange, SelectionRangeParams, SemanticToken,
-    SemanticTokens, SemanticTokensParams, SemanticTokensResult, ShowMessageParams,
-    SymbolInformation, SymbolKind, WorkspaceSymbolParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Location, LocationLink, MessageType,
+    NumberOrString, Position, PublishDiagnosticsParams, Range, SelectionRange,
+    SelectionRangeParams, SemanticToken, SemanticTokens, SemanticTokensParams,
+    SemanticTokensResult, ShowMessageParams, SymbolInformation, SymbolKind, WorkspaceSymbolParams,
 };
 use text_size::{TextRange, TextSize};
 
@@ -20,6 +20,10 @@ pub fn selection_range(
     params: SelectionRangeParams,
     global_state: &mut GlobalState,
 ) -> Vec<SelectionRange> {
+    if !global_state.is_analyzed(&params.text_document.uri) {
+        return Vec::new();
+    }
+
     let line_index = global_state.line_index(&params.text_document.uri);
 
     params
@@ -50,6 +54,10 @@ pub fn goto_definition(
     global_state: &mut GlobalState,
 ) -> Option<GotoDefinitionResponse> {
     let uri = &params.text_document_position_params.text_document.uri;
+    if !global_state.is_analyzed(uri) {
+        return None;
+    }
+
     let offset = {
         let line_index = global_state.line_index(uri);
         convert_lsp_position(params.text_document_position_params.position, line_index)
@@ -112,6 +120,11 @@ pub fn semantic_tokens(
 ) -> SemanticTokensResult {
     let mut tokens = Vec::new();
     let mut prev_token_position = None;
+
+    if !global_state.is_analyzed(&params.text_document.uri) {
+        return SemanticTokensResult::Tokens(SemanticTokens { result_id: None, data: tokens });
+    }
+
     let line_index = global_state.line_index(&params.text_document.uri);
 
     for highlight in global_state.highlight(&params.text_document.uri) {
@@ -164,7 +177,7 @@ pub fn change_text_document(
     global_state: &mut GlobalState,
     connection: &mut Connection<'_>,
 ) -> Result<(), WriteMsgError> {
-    global_state.update_contents(&params.text_document.uri, |content, line_index| {
+    let updated = global_state.update_contents(&params.text_document.uri, |content, line_index| {
         let mut line_index = line_index.clone();
 
         for change in params.content_changes {
@@ -186,6 +199,11 @@ pub fn change_text_document(
         }
     });
 
+    // the file was never analyzed, which opening it already told the user about
+    if !updated {
+        return Ok(());
+    }
+
     publish_all_diagnostics(global_state, connection)?;
     connection.make_request::<SemanticTokensRefresh>(())?;
 
@@ -210,7 +228,7 @@ fn publish_all_diagnostics(
                 Diagnostic {
                     range: convert_text_range(diagnostic.range(), line_index),
                     severity: Some(severity),
-                    code: None,
+                    code: Some(NumberOrString::String(diagnostic.rule().code.to_string())),
                     code_description: None,
                     source: Some("gb".to_string()),
                     message: diagnostic.message(global_state.interner()),
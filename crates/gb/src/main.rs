@@ -1,3 +1,4 @@
+use anyhow::{bail, Context};
 use interner::Interner;
 use lsp_types::notification::{DidChangeTextDocument, DidOpenTextDocument};
 use lsp_types::request::{
@@ -5,16 +6,23 @@ use lsp_types::request::{
 };
 use lsp_types::InitializeResult;
 use owo_colors::Style;
-use std::env;
 use std::io::Read;
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::{env, fs, process};
+use text_size::TextRange;
+
+mod doc;
+mod dump;
+mod sarif;
 
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 fn main() -> anyhow::Result<()> {
-    let mut args = env::args();
-    args.next();
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    let message_format = take_message_format(&mut args)?;
+    let mut args = args.into_iter();
 
     match args.next().as_deref() {
         Some("highlight") => {
@@ -22,6 +30,36 @@ fn main() -> anyhow::Result<()> {
             highlight(is_html)?;
         }
         Some("server") => server()?,
+        Some("check") => {
+            if !check(args, message_format)? {
+                process::exit(1);
+            }
+        }
+        Some("doc") => doc(args)?,
+        Some("dump") => dump(args)?,
+        Some("explain") => explain(args.next().as_deref())?,
+        Some("fmt") => {
+            if !fmt(args)? {
+                process::exit(1);
+            }
+        }
+        Some("build") => {
+            if !build(args, message_format)? {
+                process::exit(1);
+            }
+        }
+        Some("run") => {
+            let dir = args.next();
+            if !run(dir.as_deref(), message_format)? {
+                process::exit(1);
+            }
+        }
+        Some("repl") => repl()?,
+        Some("test") => {
+            if !test(args, message_format)? {
+                process::exit(1);
+            }
+        }
         Some(subcommand) => eprintln!("`{subcommand}` is not a valid subcommand"),
         None => eprintln!("please provide a subcommand"),
     }
@@ -40,6 +78,7 @@ fn highlight(is_html: bool) -> anyhow::Result<()> {
             "file:///tmp.gb".parse().unwrap(),
             content.clone(),
             hir::Name(interner.intern("main")),
+            None,
             &mut interner,
             &mut world_index,
         )
@@ -97,13 +136,693 @@ fn highlight(is_html: bool) -> anyhow::Result<()> {
 
         last_range = range;
     }
-    bytes.extend_from_slice(content[last_range.end..].as_bytes());
+    bytes.extend_from_slice(&content.as_bytes()[last_range.end..]);
 
     io::stdout().write_all(&bytes)?;
 
     Ok(())
 }
 
+fn check(
+    mut args: impl Iterator<Item = String>,
+    mut message_format: MessageFormat,
+) -> anyhow::Result<bool> {
+    let mut dir = None;
+    let mut deny_warnings = false;
+    let mut is_sarif = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--deny-warnings" => deny_warnings = true,
+            "--format" => match args.next().as_deref() {
+                Some("human") => message_format = MessageFormat::Human,
+                Some("json") => message_format = MessageFormat::Json,
+                Some("sarif") => is_sarif = true,
+                Some(format) => {
+                    bail!("unknown format `{format}`; expected `human`, `json` or `sarif`")
+                }
+                None => bail!("`--format` needs a format"),
+            },
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument `{arg}`"),
+        }
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => env::current_dir()?,
+    };
+
+    let mut global_state = ide::GlobalState::default();
+    global_state.open_project(&dir)?;
+
+    let DiagnosticCounts { errors, warnings } = if is_sarif {
+        let diagnostics = sorted_diagnostics(&global_state);
+        println!("{:#}", sarif::log(&diagnostics, &global_state));
+
+        let mut counts = DiagnosticCounts::default();
+        for diagnostic in diagnostics.iter().flat_map(|(_, diagnostics)| diagnostics) {
+            counts.add(diagnostic);
+        }
+        counts
+    } else {
+        print_diagnostics(&global_state, message_format)
+    };
+
+    let plural = |count: usize, noun: &str| {
+        if count == 1 {
+            format!("1 {noun}")
+        } else {
+            format!("{count} {noun}s")
+        }
+    };
+    eprintln!("{}, {}", plural(errors, "error"), plural(warnings, "warning"));
+
+    Ok(errors == 0 && (!deny_warnings || warnings == 0))
+}
+
+fn fmt(args: impl Iterator<Item = String>) -> anyhow::Result<bool> {
+    let mut dir = None;
+    let mut is_check = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--check" => is_check = true,
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument `{arg}`"),
+        }
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => env::current_dir()?,
+    };
+
+    let project = hir::Project::discover(&dir, &mut Interner::default())?;
+
+    // dependencies are formatted by their own authors
+    let mut paths: Vec<_> = project
+        .modules()
+        .filter(|module| module.package.is_none())
+        .map(|module| module.path.as_path())
+        .collect();
+    paths.sort_unstable();
+
+    let mut is_ok = true;
+
+    for path in paths {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("could not read {}", path.display()))?;
+
+        let formatted = match formatter::format(&content) {
+            Some(formatted) => formatted,
+            None => {
+                eprintln!("error: could not format {} due to syntax errors", path.display());
+                is_ok = false;
+                continue;
+            }
+        };
+
+        if formatted == content {
+            continue;
+        }
+
+        if is_check {
+            eprintln!("{} is not formatted", path.display());
+            is_ok = false;
+        } else {
+            fs::write(path, formatted)
+                .with_context(|| format!("could not write {}", path.display()))?;
+        }
+    }
+
+    Ok(is_ok)
+}
+
+fn run(dir: Option<&str>, message_format: MessageFormat) -> anyhow::Result<bool> {
+    let dir = match dir {
+        Some(dir) => PathBuf::from(dir),
+        None => env::current_dir()?,
+    };
+
+    let mut global_state = ide::GlobalState::default();
+    global_state.open_project(&dir)?;
+
+    if !report_diagnostics(&global_state, message_format) {
+        eprintln!("error: could not run the project due to previous errors");
+        return Ok(false);
+    }
+
+    let entry_point = match checked_entry_point(&global_state) {
+        Some(entry_point) => entry_point,
+        None => return Ok(false),
+    };
+
+    let return_ty = match global_state.world_index().get_definition(entry_point) {
+        Ok(hir::Definition::Function(function)) => &function.return_ty,
+        _ => unreachable!(),
+    };
+    if !is_returnable(return_ty) {
+        eprintln!(
+            "error: cannot run a program returning `{}`; \
+             only integers, strings, booleans, optionals and tuples of them can be shown",
+            return_ty.display(global_state.interner())
+        );
+        return Ok(false);
+    }
+
+    let result = eval::eval(
+        entry_point,
+        global_state.bodies_map(),
+        global_state.tys_map(),
+        global_state.world_index(),
+        global_state.interner(),
+        &eval::HostFunctions::default(),
+        compile_options(&global_state),
+    );
+
+    match result {
+        Ok(eval::Val::Nil) => Ok(true),
+        Ok(val) => {
+            println!("{val}");
+            Ok(true)
+        }
+        Err(error) => {
+            eprintln!("error: {}", describe_eval_error(&global_state, &error));
+            Ok(false)
+        }
+    }
+}
+
+fn doc(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let mut dir = None;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" => output = Some(args.next().context("`-o` needs a directory to write to")?),
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument `{arg}`"),
+        }
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => env::current_dir()?,
+    };
+
+    let mut global_state = ide::GlobalState::default();
+    global_state.open_project(&dir)?;
+    let project = global_state.project().unwrap();
+
+    let output = match output {
+        Some(output) => PathBuf::from(output),
+        None => project.root().join("doc"),
+    };
+
+    doc::generate(project, global_state.world_index(), global_state.interner(), &output)
+        .with_context(|| format!("could not write documentation to {}", output.display()))?;
+    eprintln!("wrote {}", output.join("index.html").display());
+
+    Ok(())
+}
+
+fn explain(code: Option<&str>) -> anyhow::Result<()> {
+    let code = code.context("please provide a diagnostic code, such as `E0003`")?;
+
+    match diagnostics::rule_by_code(code) {
+        Some(rule) => print!("{}", rule.explanation),
+        None => bail!("`{code}` is not a diagnostic code"),
+    }
+
+    Ok(())
+}
+
+fn dump(mut args: impl Iterator<Item = String>) -> anyhow::Result<()> {
+    let stage = match args.next() {
+        Some(stage) => match dump::Stage::from_name(&stage) {
+            Some(stage) => stage,
+            None => {
+                bail!("cannot dump `{stage}`; expected `tokens`, `cst`, `hir`, `types` or `wasm`")
+            }
+        },
+        None => bail!("please provide a stage to dump"),
+    };
+
+    let mut path = None;
+    let mut function = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--function" => {
+                function = Some(args.next().context("`--function` needs a function name")?);
+            }
+            _ if path.is_none() && !arg.starts_with('-') => path = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument `{arg}`"),
+        }
+    }
+
+    let path = path.context("please provide a file to dump")?;
+    let input =
+        fs::read_to_string(&path).with_context(|| format!("could not read {}", path.display()))?;
+
+    let output = dump::dump(stage, &input, function.as_deref())?;
+    println!("{}", output.trim_end());
+
+    Ok(())
+}
+
+fn repl() -> anyhow::Result<()> {
+    let mut session = repl::Session::default();
+    let mut line = String::new();
+
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+
+        line.clear();
+        if io::stdin().read_line(&mut line)? == 0 {
+            println!();
+            return Ok(());
+        }
+
+        if line.trim() == ":quit" {
+            return Ok(());
+        }
+
+        let output = session.eval_line(&line);
+        if !output.is_empty() {
+            println!("{output}");
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Emit {
+    Wasm,
+    Wat,
+}
+
+fn build(
+    mut args: impl Iterator<Item = String>,
+    message_format: MessageFormat,
+) -> anyhow::Result<bool> {
+    let mut dir = None;
+    let mut output = None;
+    let mut emit = Emit::Wasm;
+    let mut strip_contracts = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" => output = Some(args.next().context("`-o` needs a path to write to")?),
+            "--emit" => {
+                emit = match args.next().as_deref() {
+                    Some("wasm") => Emit::Wasm,
+                    Some("wat") => Emit::Wat,
+                    Some(format) => bail!("cannot emit `{format}`; expected `wasm` or `wat`"),
+                    None => bail!("`--emit` needs a format"),
+                }
+            }
+            "--release" => strip_contracts = true,
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument `{arg}`"),
+        }
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => env::current_dir()?,
+    };
+
+    let mut global_state = ide::GlobalState::default();
+    global_state.open_project(&dir)?;
+
+    if !report_diagnostics(&global_state, message_format) {
+        eprintln!("error: could not build the project due to previous errors");
+        return Ok(false);
+    }
+
+    let entry_point = match checked_entry_point(&global_state) {
+        Some(entry_point) => entry_point,
+        None => return Ok(false),
+    };
+
+    let wasm = eval::compile(
+        entry_point,
+        global_state.bodies_map(),
+        global_state.tys_map(),
+        global_state.world_index(),
+        global_state.interner(),
+        eval::CompileOptions { strip_contracts, ..compile_options(&global_state) },
+    );
+
+    let (bytes, extension) = match emit {
+        Emit::Wasm => (wasm, "wasm"),
+        Emit::Wat => (wasmprinter::print_bytes(&wasm)?.into_bytes(), "wat"),
+    };
+
+    let path = match output {
+        Some(output) if output == "-" => {
+            io::stdout().write_all(&bytes)?;
+            return Ok(true);
+        }
+        Some(output) => PathBuf::from(output),
+        None => {
+            let project = global_state.project().unwrap();
+            project.root().join(format!("{}.{extension}", project.name()))
+        }
+    };
+
+    fs::write(&path, bytes)?;
+    eprintln!("wrote {}", path.display());
+
+    Ok(true)
+}
+
+fn compile_options(global_state: &ide::GlobalState) -> eval::CompileOptions {
+    let options = global_state.project().unwrap().options();
+    eval::CompileOptions { memory_pages: options.memory_pages, ..eval::CompileOptions::default() }
+}
+
+fn checked_entry_point(global_state: &ide::GlobalState) -> Option<hir::Fqn> {
+    let interner = global_state.interner();
+    let entry_point = global_state.project().unwrap().entry_point();
+    let name = format!(
+        "{}.{}",
+        interner.lookup(entry_point.module.0),
+        interner.lookup(entry_point.name.0)
+    );
+
+    match global_state.world_index().get_definition(entry_point) {
+        Ok(hir::Definition::Function(function))
+            if function.params.is_empty()
+                && function.generic_params.is_empty()
+                && function.has_body() =>
+        {
+            Some(entry_point)
+        }
+        Ok(hir::Definition::Function(_)) => {
+            eprintln!("error: entry point `{name}` has to be a function without parameters");
+            None
+        }
+        Ok(_) => {
+            eprintln!("error: entry point `{name}` is not a function");
+            None
+        }
+        Err(_) => {
+            eprintln!("error: entry point `{name}` is not defined");
+            None
+        }
+    }
+}
+
+fn is_returnable(ty: &hir::Ty) -> bool {
+    match ty {
+        hir::Ty::S32 | hir::Ty::String | hir::Ty::Bool | hir::Ty::Unit => true,
+        hir::Ty::Optional(ty) => is_returnable(ty),
+        hir::Ty::Tuple(tys) => tys.iter().all(is_returnable),
+        hir::Ty::Unknown | hir::Ty::Named(_) | hir::Ty::Generic(_) => false,
+    }
+}
+
+fn test(
+    mut args: impl Iterator<Item = String>,
+    message_format: MessageFormat,
+) -> anyhow::Result<bool> {
+    let mut dir = None;
+    let mut filter = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--filter" => filter = Some(args.next().context("`--filter` needs a test name")?),
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument `{arg}`"),
+        }
+    }
+
+    let dir = match dir {
+        Some(dir) => dir,
+        None => env::current_dir()?,
+    };
+
+    let mut global_state = ide::GlobalState::default();
+    global_state.open_project(&dir)?;
+
+    if !report_diagnostics(&global_state, message_format) {
+        return Ok(false);
+    }
+
+    let interner = global_state.interner();
+    let host_functions = eval::HostFunctions::default();
+
+    let tests = global_state.tests();
+    let (tests, filtered): (Vec<_>, Vec<_>) = tests.into_iter().partition(|test| {
+        filter.as_deref().is_none_or(|filter| interner.lookup(test.name.0).contains(filter))
+    });
+
+    println!("running {} test{}", tests.len(), if tests.len() == 1 { "" } else { "s" });
+
+    let mut failures = Vec::new();
+
+    for test in &tests {
+        let name =
+            format!("{}: \"{}\"", interner.lookup(test.module.0), interner.lookup(test.name.0));
+
+        let result = eval::eval_test(
+            *test,
+            global_state.bodies_map(),
+            global_state.tys_map(),
+            global_state.world_index(),
+            interner,
+            &host_functions,
+            compile_options(&global_state),
+        );
+
+        match result {
+            Ok(()) => println!("test {name} ... {}", Style::new().green().style("ok")),
+            Err(e) => {
+                println!("test {name} ... {}", Style::new().red().style("FAILED"));
+                failures.push((name, e));
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        println!("\nfailures:");
+
+        for (name, error) in &failures {
+            println!("\ntest {name} {}", describe_eval_error(&global_state, error));
+        }
+    }
+
+    let passed = tests.len() - failures.len();
+    let result = if failures.is_empty() {
+        Style::new().green().style("ok")
+    } else {
+        Style::new().red().style("FAILED")
+    };
+
+    println!(
+        "\ntest result: {result}. {passed} passed; {} failed; {} filtered out",
+        failures.len(),
+        filtered.len()
+    );
+
+    Ok(failures.is_empty())
+}
+
+fn describe_eval_error(global_state: &ide::GlobalState, error: &eval::EvalError) -> String {
+    let location = |module: &String, range: TextRange| {
+        let interner = global_state.interner();
+        global_state
+            .project()
+            .unwrap()
+            .modules()
+            .find(|m| interner.lookup(m.name.0) == module)
+            .and_then(|m| global_state.module_uri(m.name))
+            .map(|uri| {
+                let line_index = global_state.line_index(&uri);
+                let (line, col) = line_index.line_col(range.start());
+                format!("{}:{}:{}", uri.path(), line.0 + 1, col.0 + 1)
+            })
+            .unwrap_or_else(|| module.clone())
+    };
+
+    match error {
+        eval::EvalError::Panic { module, range, message, .. } => {
+            format!("panicked at {}: {message}", location(module, *range))
+        }
+        eval::EvalError::ContractViolation { module, function, range, clause } => {
+            format!(
+                "broke the contract of `{function}` at {}: {clause} does not hold",
+                location(module, *range)
+            )
+        }
+        _ => error.to_string(),
+    }
+}
+
+#[derive(Default)]
+struct DiagnosticCounts {
+    errors: usize,
+    warnings: usize,
+}
+
+impl DiagnosticCounts {
+    fn add(&mut self, diagnostic: &diagnostics::Diagnostic) {
+        match diagnostic.severity() {
+            diagnostics::Severity::Error => self.errors += 1,
+            diagnostics::Severity::Warning => self.warnings += 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum MessageFormat {
+    Human,
+    Json,
+}
+
+fn take_message_format(args: &mut Vec<String>) -> anyhow::Result<MessageFormat> {
+    let idx = match args.iter().position(|arg| arg == "--message-format") {
+        Some(idx) => idx,
+        None => return Ok(MessageFormat::Human),
+    };
+    args.remove(idx);
+
+    if idx == args.len() {
+        bail!("`--message-format` needs a format");
+    }
+
+    match args.remove(idx).as_str() {
+        "human" => Ok(MessageFormat::Human),
+        "json" => Ok(MessageFormat::Json),
+        format => bail!("unknown message format `{format}`; expected `human` or `json`"),
+    }
+}
+
+fn report_diagnostics(global_state: &ide::GlobalState, message_format: MessageFormat) -> bool {
+    print_diagnostics(global_state, message_format).errors == 0
+}
+
+fn print_diagnostics(
+    global_state: &ide::GlobalState,
+    message_format: MessageFormat,
+) -> DiagnosticCounts {
+    let mut counts = DiagnosticCounts::default();
+
+    for (uri, diagnostics) in sorted_diagnostics(global_state) {
+        let line_index = global_state.line_index(uri);
+
+        for diagnostic in diagnostics {
+            counts.add(&diagnostic);
+
+            match message_format {
+                MessageFormat::Human => {
+                    let (line, col) = line_index.line_col(diagnostic.range().start());
+                    let severity = match diagnostic.severity() {
+                        diagnostics::Severity::Warning => "warning",
+                        diagnostics::Severity::Error => "error",
+                    };
+                    eprintln!(
+                        "{}:{}:{}: {severity}[{}]: {}",
+                        uri.path(),
+                        line.0 + 1,
+                        col.0 + 1,
+                        diagnostic.rule().code,
+                        diagnostic.message(global_state.interner())
+                    );
+
+                    // the first line repeats the location, so only the snippet is left
+                    let lines = diagnostic.display(
+                        global_state.content(uri),
+                        global_state.interner(),
+                        line_index,
+                    );
+                    for line in &lines[1..] {
+                        eprintln!("{line}");
+                    }
+                }
+                MessageFormat::Json => {
+                    println!("{}", diagnostic_json(uri, &diagnostic, global_state));
+                }
+            }
+        }
+    }
+
+    counts
+}
+
+fn sorted_diagnostics(
+    global_state: &ide::GlobalState,
+) -> Vec<(&lsp_types::Url, Vec<diagnostics::Diagnostic>)> {
+    let mut diagnostics: Vec<_> = global_state.diagnostics().collect();
+    diagnostics.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+    diagnostics
+}
+
+fn diagnostic_json(
+    uri: &lsp_types::Url,
+    diagnostic: &diagnostics::Diagnostic,
+    global_state: &ide::GlobalState,
+) -> serde_json::Value {
+    let content = global_state.content(uri);
+    let line_index = global_state.line_index(uri);
+
+    // lines and columns are one-based like in human-readable output,
+    // columns count code points like in SARIF output,
+    // and the end of every range is exclusive
+    let range_json = |range: TextRange| {
+        let (start_line, start_col) = sarif::line_col(content, line_index, range.start());
+        let (end_line, end_col) = sarif::line_col(content, line_index, range.end());
+
+        serde_json::json!({
+            "byte_start": u32::from(range.start()),
+            "byte_end": u32::from(range.end()),
+            "line_start": start_line,
+            "column_start": start_col,
+            "line_end": end_line,
+            "column_end": end_col,
+        })
+    };
+
+    let severity = match diagnostic.severity() {
+        diagnostics::Severity::Warning => "warning",
+        diagnostics::Severity::Error => "error",
+    };
+
+    let stage = match diagnostic.stage() {
+        diagnostics::Stage::Syntax => "syntax",
+        diagnostics::Stage::Validation => "validation",
+        diagnostics::Stage::Indexing => "indexing",
+        diagnostics::Stage::Lowering => "lowering",
+        diagnostics::Stage::Ty => "type",
+    };
+
+    let fixes: Vec<_> = diagnostic
+        .fixes()
+        .into_iter()
+        .map(|fix| {
+            serde_json::json!({
+                "message": fix.message,
+                "range": range_json(fix.range),
+                "replacement": fix.replacement,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "file": uri.path(),
+        "range": range_json(diagnostic.range()),
+        "severity": severity,
+        "code": diagnostic.rule().code,
+        "stage": stage,
+        "message": diagnostic.message(global_state.interner()),
+        "fixes": fixes,
+        "column_kind": "unicode_code_points",
+    })
+}
+
 fn server() -> anyhow::Result<()> {
     let stdio_connection_storage = lsp::connection::ConnectionStorage::new();
 
@@ -173,3 +892,110 @@ fn server() -> anyhow::Result<()> {
         }
     }
 }
+
+#[cfg(test)]
+fn open_fixture(name: &str, files: &[(&str, &str)]) -> (PathBuf, ide::GlobalState) {
+    let root = utils::create_fixture(name, files);
+
+    let mut global_state = ide::GlobalState::default();
+    global_state.open_project(&root).unwrap();
+
+    (root, global_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    #[test]
+    fn message_format() {
+        let mut args =
+            vec!["--message-format".to_string(), "json".to_string(), "check".to_string()];
+        assert!(matches!(take_message_format(&mut args), Ok(MessageFormat::Json)));
+        assert_eq!(args, ["check"]);
+
+        let mut args = vec!["check".to_string()];
+        assert!(matches!(take_message_format(&mut args), Ok(MessageFormat::Human)));
+
+        let mut args = vec!["--message-format".to_string()];
+        assert!(take_message_format(&mut args).is_err());
+
+        let mut args = vec!["--message-format".to_string(), "xml".to_string()];
+        assert!(take_message_format(&mut args).is_err());
+    }
+
+    #[test]
+    fn json_diagnostics() {
+        let (root, global_state) = open_fixture(
+            "json",
+            &[
+                ("gb.toml", "[package]\nname = \"app\"\n"),
+                ("main.gb", "fnc main -> {\n    let s = \"ü\"; foo\n};\nfnc f(): s32 -> 1;\n"),
+            ],
+        );
+
+        let root = root.canonicalize().unwrap();
+        let lines: Vec<_> = sorted_diagnostics(&global_state)
+            .into_iter()
+            .flat_map(|(uri, diagnostics)| {
+                diagnostics
+                    .into_iter()
+                    .map(|diagnostic| {
+                        format!("{:#}", diagnostic_json(uri, &diagnostic, &global_state))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        expect![[r#"
+            {
+              "code": "E0003",
+              "column_kind": "unicode_code_points",
+              "file": "/project/main.gb",
+              "fixes": [
+                {
+                  "message": "remove the parentheses",
+                  "range": {
+                    "byte_end": 46,
+                    "byte_start": 44,
+                    "column_end": 8,
+                    "column_start": 6,
+                    "line_end": 4,
+                    "line_start": 4
+                  },
+                  "replacement": ""
+                }
+              ],
+              "message": "unneeded parentheses",
+              "range": {
+                "byte_end": 46,
+                "byte_start": 44,
+                "column_end": 8,
+                "column_start": 6,
+                "line_end": 4,
+                "line_start": 4
+              },
+              "severity": "warning",
+              "stage": "validation"
+            }
+            {
+              "code": "E0006",
+              "column_kind": "unicode_code_points",
+              "file": "/project/main.gb",
+              "fixes": [],
+              "message": "undefined variable `foo`",
+              "range": {
+                "byte_end": 35,
+                "byte_start": 32,
+                "column_end": 21,
+                "column_start": 18,
+                "line_end": 2,
+                "line_start": 2
+              },
+              "severity": "error",
+              "stage": "lowering"
+            }"#]]
+        .assert_eq(&lines.join("\n").replace(root.to_str().unwrap(), "/project"));
+    }
+}
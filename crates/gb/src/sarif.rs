@@ -0,0 +1,172 @@
+use diagnostics::{Diagnostic, Severity, RULES};
+use line_index::LineIndex;
+use lsp_types::Url;
+use serde_json::{json, Value};
+use text_size::{TextRange, TextSize};
+
+pub(crate) fn log(
+    diagnostics: &[(&Url, Vec<Diagnostic>)],
+    global_state: &ide::GlobalState,
+) -> Value {
+    let rules: Vec<_> = RULES
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": { "text": rule.description },
+            })
+        })
+        .collect();
+
+    let results: Vec<_> = diagnostics
+        .iter()
+        .flat_map(|(uri, diagnostics)| {
+            diagnostics.iter().map(move |diagnostic| result(uri, diagnostic, global_state))
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "gb",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+fn result(uri: &Url, diagnostic: &Diagnostic, global_state: &ide::GlobalState) -> Value {
+    let rule = diagnostic.rule();
+    let rule_index = RULES.iter().position(|r| r.id == rule.id).unwrap();
+
+    let level = match diagnostic.severity() {
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    };
+
+    let content = global_state.content(uri);
+    let line_index = global_state.line_index(uri);
+    let range = diagnostic.range();
+    let (start_line, start_column) = line_col(content, line_index, range.start());
+    let (end_line, end_column) = line_col(content, line_index, range.end());
+
+    json!({
+        "ruleId": rule.id,
+        "ruleIndex": rule_index,
+        "level": level,
+        "message": { "text": diagnostic.message(global_state.interner()) },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri.as_str() },
+                "region": {
+                    "startLine": start_line,
+                    "startColumn": start_column,
+                    "endLine": end_line,
+                    "endColumn": end_column,
+                    "byteOffset": u32::from(range.start()),
+                    "byteLength": u32::from(range.len()),
+                },
+            },
+        }],
+    })
+}
+
+// SARIF and JSON output count lines and columns from one,
+// and columns in characters rather than the bytes LineIndex uses
+pub(crate) fn line_col(content: &str, line_index: &LineIndex, offset: TextSize) -> (u32, u32) {
+    // missing syntax at the very end of a file points one past it
+    let offset = offset.min(TextSize::of(content));
+    let (line, _) = line_index.line_col(offset);
+    let line_start = line_index[line];
+    let column = content[TextRange::new(line_start, offset)].chars().count() as u32;
+
+    (line.0 + 1, column + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::expect;
+
+    #[test]
+    fn results() {
+        let (root, global_state) = crate::open_fixture(
+            "sarif",
+            &[
+                ("gb.toml", "[package]\nname = \"app\"\n"),
+                ("main.gb", "fnc main -> {\n    let s = \"ü\"; foo\n};\nfnc f(): s32 -> 1;\n"),
+            ],
+        );
+
+        let diagnostics = crate::sorted_diagnostics(&global_state);
+        let log = log(&diagnostics, &global_state);
+        assert_eq!(
+            log["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            RULES.len()
+        );
+
+        let results = format!("{:#}", log["runs"][0]["results"]);
+        let root = Url::from_directory_path(root.canonicalize().unwrap()).unwrap();
+        expect![[r#"
+            [
+              {
+                "level": "warning",
+                "locations": [
+                  {
+                    "physicalLocation": {
+                      "artifactLocation": {
+                        "uri": "file:///project/main.gb"
+                      },
+                      "region": {
+                        "byteLength": 2,
+                        "byteOffset": 44,
+                        "endColumn": 8,
+                        "endLine": 4,
+                        "startColumn": 6,
+                        "startLine": 4
+                      }
+                    }
+                  }
+                ],
+                "message": {
+                  "text": "unneeded parentheses"
+                },
+                "ruleId": "unneeded_parens",
+                "ruleIndex": 2
+              },
+              {
+                "level": "error",
+                "locations": [
+                  {
+                    "physicalLocation": {
+                      "artifactLocation": {
+                        "uri": "file:///project/main.gb"
+                      },
+                      "region": {
+                        "byteLength": 3,
+                        "byteOffset": 32,
+                        "endColumn": 21,
+                        "endLine": 2,
+                        "startColumn": 18,
+                        "startLine": 2
+                      }
+                    }
+                  }
+                ],
+                "message": {
+                  "text": "undefined variable `foo`"
+                },
+                "ruleId": "undefined_local",
+                "ruleIndex": 5
+              }
+            ]"#]]
+        .assert_eq(&results.replace(root.as_str(), "file:///project/"));
+    }
+}
@@ -1,5 +1,7 @@
+use crate::index::{lower_ty, test_name};
 use crate::{
-    Definition, Fqn, Function, GetDefinitionError, Index, Name, Path, PathWithRange, WorldIndex,
+    Definition, Fqn, Function, GetDefinitionError, Index, Name, Path, PathWithRange, Ty,
+    WorldIndex, STD_MODULE_NAME,
 };
 use arena::{Arena, ArenaMap, Id};
 use ast::{AstNode, AstToken};
@@ -11,12 +13,18 @@ use text_size::TextRange;
 #[derive(Clone)]
 pub struct Bodies {
     local_defs: Arena<LocalDef>,
+    patterns: Arena<Pattern>,
+    pattern_ranges: ArenaMap<Id<Pattern>, TextRange>,
     statements: Arena<Statement>,
     exprs: Arena<Expr>,
     expr_ranges: ArenaMap<Id<Expr>, TextRange>,
     function_bodies: FxHashMap<Name, Id<Expr>>,
+    function_contracts: FxHashMap<Name, Contract>,
+    test_bodies: FxHashMap<Name, Id<Expr>>,
+    param_defaults: FxHashMap<(Name, u32), Id<Expr>>,
     other_module_references: FxHashSet<Fqn>,
     symbol_map: FxHashMap<ast::Ident, Symbol>,
+    tys: FxHashMap<ast::Ident, Path>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,11 +32,60 @@ pub enum Expr {
     Missing,
     IntLiteral(u32),
     StringLiteral(String),
-    Binary { lhs: Id<Expr>, rhs: Id<Expr>, operator: BinaryOperator },
-    Block { statements: Vec<Id<Statement>>, tail_expr: Option<Id<Expr>> },
+    Binary {
+        lhs: Id<Expr>,
+        rhs: Id<Expr>,
+        operator: BinaryOperator,
+    },
+    Block {
+        statements: Vec<Id<Statement>>,
+        tail_expr: Option<Id<Expr>>,
+    },
     Local(Id<LocalDef>),
-    Param { idx: u32 },
-    Call { path: PathWithRange, args: Vec<Id<Expr>> },
+    Param {
+        idx: u32,
+    },
+    None,
+    Try {
+        inner: Id<Expr>,
+    },
+    Field {
+        base: Id<Expr>,
+        field: Name,
+    },
+    Tuple {
+        elements: Vec<Id<Expr>>,
+    },
+    TupleField {
+        base: Id<Expr>,
+        idx: u32,
+    },
+    Call {
+        path: PathWithRange,
+        args: Vec<Id<Expr>>,
+        // named arguments are evaluated as written, followed by any defaults;
+        // `None` when that is the order of the parameters
+        evaluation_order: Option<Vec<u32>>,
+    },
+    Default {
+        function: Path,
+        idx: u32,
+    },
+    Panic {
+        message: Id<Expr>,
+    },
+    Assert {
+        condition: Id<Expr>,
+        message: Id<Expr>,
+    },
+    ReturnValue,
+}
+
+/// The conditions a function checks on entry and on exit.
+#[derive(Debug, Clone)]
+pub struct Contract {
+    pub requires: Vec<Id<Expr>>,
+    pub ensures: Vec<Id<Expr>>,
 }
 
 #[derive(Debug, Clone)]
@@ -40,21 +97,56 @@ pub enum Statement {
 #[derive(Clone)]
 pub struct LocalDef {
     pub value: Id<Expr>,
+    /// The type the local was annotated with, if any.
+    pub ty: Option<Ty>,
+    pub ty_range: Option<TextRange>,
+    /// The pattern the value is destructured with, if any.
+    /// Each name it binds is a local of its own.
+    pub pattern: Option<Id<Pattern>>,
+    pub name: Option<ast::Ident>,
     pub ast: ast::LocalDef,
 }
 
 impl std::fmt::Debug for LocalDef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("LocalDef").field("value", &self.value).finish()
+        f.debug_struct("LocalDef").field("value", &self.value).field("ty", &self.ty).finish()
     }
 }
 
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Missing,
+    Wildcard,
+    Binding(Id<LocalDef>),
+    Record { record: Option<PathWithRange>, fields: Vec<FieldPattern> },
+    Tuple { elements: Vec<Id<Pattern>> },
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldPattern {
+    pub name: Name,
+    pub range: TextRange,
+    pub pattern: Id<Pattern>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum BinaryOperator {
     Add,
     Sub,
     Mul,
     Div,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl BinaryOperator {
+    pub fn is_comparison(self) -> bool {
+        !matches!(self, Self::Add | Self::Sub | Self::Mul | Self::Div)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -69,7 +161,15 @@ pub enum LoweringDiagnosticKind {
     UndefinedLocal { name: Key },
     UndefinedModule { name: Key },
     MismatchedArgCount { name: Key, expected: u32, got: u32 },
+    MissingArg { function: Key, param: Key },
+    UnknownNamedArg { function: Key, name: Key },
+    DuplicateArg { name: Key },
+    DuplicateFieldPattern { name: Key },
+    PositionalArgAfterNamedArg,
     CalledLocal { name: Key },
+    CalledRecord { name: Key },
+    CalledInterface { name: Key },
+    UndefinedRecord { name: Key },
     InvalidEscape,
 }
 
@@ -79,22 +179,44 @@ pub enum Symbol {
     Param(ast::Param),
     Function(Path),
     Module(Name),
+    ReturnValue(ast::Ensures),
     Unknown,
 }
 
 pub fn lower(
     root: ast::Root,
     tree: &SyntaxTree,
+    package: Option<Name>,
     index: &Index,
     world_index: &WorldIndex,
     interner: &mut Interner,
 ) -> (Bodies, Vec<LoweringDiagnostic>) {
-    let mut ctx = Ctx::new(index, world_index, interner, tree);
+    let mut ctx = Ctx::new(package, index, world_index, interner, tree);
 
     for def in root.defs(tree) {
         match def {
-            ast::Def::Function(function) => ctx.lower_function(function),
+            ast::Def::Function(function) => ctx.lower_function(function, None),
             ast::Def::Record(_) => {}
+            ast::Def::Test(test) => ctx.lower_test(test),
+            ast::Def::Interface(interface) => {
+                for function in interface.functions(tree) {
+                    ctx.lower_function(function, None);
+                }
+            }
+            ast::Def::Impl(imp) => {
+                ctx.self_ty = Some(lower_ty(imp.ty(tree), tree, ctx.interner).0);
+
+                // indexing has already given every function a name of its own
+                for function in imp.functions(tree) {
+                    let local_name =
+                        function.name(tree).and_then(|ident| index.local_definition_name(ident));
+                    if let Some(local_name) = local_name {
+                        ctx.lower_function(function, Some(local_name));
+                    }
+                }
+
+                ctx.self_ty = None;
+            }
         }
     }
 
@@ -105,17 +227,23 @@ pub fn lower(
 
 struct Ctx<'a> {
     bodies: Bodies,
+    package: Option<Name>,
     index: &'a Index,
     world_index: &'a WorldIndex,
     interner: &'a mut Interner,
     tree: &'a SyntaxTree,
     diagnostics: Vec<LoweringDiagnostic>,
     scopes: Vec<FxHashMap<Key, Id<LocalDef>>>,
+    item_scopes: Vec<FxHashMap<Key, Name>>,
     params: FxHashMap<Key, (u32, ast::Param)>,
+    generic_params: Vec<Name>,
+    self_ty: Option<Ty>,
+    return_value: Option<(Key, ast::Ensures)>,
 }
 
 impl<'a> Ctx<'a> {
     fn new(
+        package: Option<Name>,
         index: &'a Index,
         world_index: &'a WorldIndex,
         interner: &'a mut Interner,
@@ -124,27 +252,39 @@ impl<'a> Ctx<'a> {
         Self {
             bodies: Bodies {
                 local_defs: Arena::new(),
+                patterns: Arena::new(),
+                pattern_ranges: ArenaMap::default(),
                 statements: Arena::new(),
                 exprs: Arena::new(),
                 expr_ranges: ArenaMap::default(),
                 function_bodies: FxHashMap::default(),
+                function_contracts: FxHashMap::default(),
+                test_bodies: FxHashMap::default(),
+                param_defaults: FxHashMap::default(),
                 other_module_references: FxHashSet::default(),
                 symbol_map: FxHashMap::default(),
+                tys: FxHashMap::default(),
             },
+            package,
             index,
             world_index,
             interner,
             tree,
             diagnostics: Vec::new(),
             scopes: vec![FxHashMap::default()],
+            item_scopes: Vec::new(),
             params: FxHashMap::default(),
+            generic_params: Vec::new(),
+            self_ty: None,
+            return_value: None,
         }
     }
 
-    fn lower_function(&mut self, function: ast::Function) {
-        let name = match function.name(self.tree) {
-            Some(ident) => Name(self.interner.intern(ident.text(self.tree))),
-            None => return,
+    fn lower_function(&mut self, function: ast::Function, local_name: Option<Name>) {
+        let name = match (local_name, function.name(self.tree)) {
+            (Some(name), _) => name,
+            (None, Some(ident)) => Name(self.interner.intern(ident.text(self.tree))),
+            (None, None) => return,
         };
 
         // if we’ve already seen a function with this name,
@@ -156,6 +296,35 @@ impl<'a> Ctx<'a> {
             return;
         }
 
+        // default values are lowered before the parameters are in scope,
+        // since they are evaluated by the caller
+        if let Some(param_list) = function.param_list(self.tree) {
+            for (idx, param) in param_list.params(self.tree).enumerate() {
+                if let Some(default_value) = param.default_value(self.tree) {
+                    let default_value = self.lower_expr(Some(default_value));
+                    self.bodies.param_defaults.insert((name, idx as u32), default_value);
+                }
+            }
+        }
+
+        let definition = match self.index.get_definition(name) {
+            Some(Definition::Function(function)) => Some(function),
+            _ => None,
+        };
+
+        // extern functions are implemented by the host,
+        // and those declared by an interface by each of its implementations
+        if function.ext_kw(self.tree).is_some()
+            || definition.is_some_and(|function| function.interface.is_some())
+        {
+            return;
+        }
+
+        let generic_params = definition.map_or_else(Vec::new, |function| {
+            function.generic_params.iter().map(|generic_param| generic_param.name).collect()
+        });
+        let outer_generic_params = std::mem::replace(&mut self.generic_params, generic_params);
+
         if let Some(param_list) = function.param_list(self.tree) {
             for (idx, param) in param_list.params(self.tree).enumerate() {
                 if let Some(ident) = param.name(self.tree) {
@@ -166,10 +335,47 @@ impl<'a> Ctx<'a> {
         }
 
         let body = self.lower_expr(function.body(self.tree));
+
+        let mut contract = Contract { requires: Vec::new(), ensures: Vec::new() };
+
+        for requires in function.requires(self.tree) {
+            contract.requires.push(self.lower_expr(requires.condition(self.tree)));
+        }
+
+        for ensures in function.ensures(self.tree) {
+            self.return_value = ensures
+                .result_name(self.tree)
+                .map(|ident| (self.interner.intern(ident.text(self.tree)), ensures));
+            contract.ensures.push(self.lower_expr(ensures.condition(self.tree)));
+            self.return_value = None;
+        }
+
+        if !contract.requires.is_empty() || !contract.ensures.is_empty() {
+            self.bodies.function_contracts.insert(name, contract);
+        }
+
         self.params.clear();
+        self.generic_params = outer_generic_params;
         self.bodies.function_bodies.insert(name, body);
     }
 
+    fn lower_test(&mut self, test: ast::Test) {
+        let name = match test.name(self.tree) {
+            Some(string_literal) => {
+                Name(self.interner.intern(&test_name(string_literal, self.tree)))
+            }
+            None => return,
+        };
+
+        // as with functions, indexing reports tests with duplicate names
+        if self.bodies.test_bodies.contains_key(&name) {
+            return;
+        }
+
+        let body = self.lower_expr(test.body(self.tree));
+        self.bodies.test_bodies.insert(name, body);
+    }
+
     fn lower_statement(&mut self, statement: ast::Statement) -> Statement {
         match statement {
             ast::Statement::LocalDef(local_def) => self.lower_local_def(local_def),
@@ -181,10 +387,40 @@ impl<'a> Ctx<'a> {
     }
 
     fn lower_local_def(&mut self, local_def: ast::LocalDef) -> Statement {
+        let (ty, ty_range) = match local_def.ty(self.tree) {
+            Some(ty_ast) => {
+                let (ty, idents) = lower_ty(Some(ty_ast), self.tree, self.interner);
+                for ident in idents {
+                    let key = self.interner.intern(ident.text(self.tree));
+                    let name = self.look_up_item(key).unwrap_or(Name(key));
+                    self.bodies.tys.insert(ident, Path::ThisModule(name));
+                }
+                let ty = ty.map_named(&mut |name| self.lower_named_ty(name));
+                (Some(ty), Some(ty_ast.range(self.tree)))
+            }
+            None => (None, None),
+        };
+
         let value = self.lower_expr(local_def.value(self.tree));
-        let id = self.bodies.local_defs.alloc(LocalDef { value, ast: local_def });
+        let name = local_def.name(self.tree);
+        let id = self.bodies.local_defs.alloc(LocalDef {
+            value,
+            ty,
+            ty_range,
+            pattern: None,
+            name,
+            ast: local_def,
+        });
+
+        if let Some(pattern) = local_def.pattern(self.tree) {
+            // the value is stored in a hidden local,
+            // which the pattern’s bindings then read their fields out of
+            let base = self.bodies.exprs.alloc(Expr::Local(id));
+            self.bodies.expr_ranges.insert(base, pattern.range(self.tree));
 
-        if let Some(ident) = local_def.name(self.tree) {
+            let pattern = self.lower_pattern(pattern, base, local_def);
+            self.bodies.local_defs[id].pattern = Some(pattern);
+        } else if let Some(ident) = name {
             let name = self.interner.intern(ident.text(self.tree));
             self.insert_into_current_scope(name, id);
         }
@@ -192,6 +428,192 @@ impl<'a> Ctx<'a> {
         Statement::LocalDef(id)
     }
 
+    fn lower_pattern(
+        &mut self,
+        pattern: ast::Pattern,
+        value: Id<Expr>,
+        local_def: ast::LocalDef,
+    ) -> Id<Pattern> {
+        let range = pattern.range(self.tree);
+
+        let pattern = match pattern {
+            ast::Pattern::Wildcard(_) => Pattern::Wildcard,
+
+            ast::Pattern::Ident(ident_pat) => match ident_pat.name(self.tree) {
+                Some(ident) => Pattern::Binding(self.lower_binding(ident, value, local_def)),
+                None => Pattern::Missing,
+            },
+
+            ast::Pattern::Record(record_pat) => {
+                let record = self.lower_record_path(record_pat.path(self.tree));
+                let mut fields = Vec::new();
+
+                for field in record_pat.fields(self.tree) {
+                    let ident = match field.name(self.tree) {
+                        Some(ident) => ident,
+                        None => continue,
+                    };
+                    let name = Name(self.interner.intern(ident.text(self.tree)));
+                    let field_range = ident.range(self.tree);
+
+                    if fields.iter().any(|field: &FieldPattern| field.name == name) {
+                        self.diagnostics.push(LoweringDiagnostic {
+                            kind: LoweringDiagnosticKind::DuplicateFieldPattern { name: name.0 },
+                            range: field_range,
+                        });
+                        continue;
+                    }
+
+                    let field_value =
+                        self.bodies.exprs.alloc(Expr::Field { base: value, field: name });
+                    self.bodies.expr_ranges.insert(field_value, field_range);
+
+                    let pattern = match field.pattern(self.tree) {
+                        Some(pattern) => self.lower_pattern(pattern, field_value, local_def),
+
+                        // `x` is shorthand for `x: x`
+                        None => {
+                            let binding = self.lower_binding(ident, field_value, local_def);
+                            let pattern = self.bodies.patterns.alloc(Pattern::Binding(binding));
+                            self.bodies.pattern_ranges.insert(pattern, field_range);
+                            pattern
+                        }
+                    };
+
+                    fields.push(FieldPattern { name, range: field_range, pattern });
+                }
+
+                Pattern::Record { record, fields }
+            }
+
+            ast::Pattern::Tuple(tuple_pat) => {
+                let mut elements = Vec::new();
+
+                for (idx, element) in tuple_pat.elements(self.tree).enumerate() {
+                    let element_value =
+                        self.bodies.exprs.alloc(Expr::TupleField { base: value, idx: idx as u32 });
+                    self.bodies.expr_ranges.insert(element_value, element.range(self.tree));
+
+                    elements.push(self.lower_pattern(element, element_value, local_def));
+                }
+
+                Pattern::Tuple { elements }
+            }
+        };
+
+        let id = self.bodies.patterns.alloc(pattern);
+        self.bodies.pattern_ranges.insert(id, range);
+
+        id
+    }
+
+    fn lower_binding(
+        &mut self,
+        ident: ast::Ident,
+        value: Id<Expr>,
+        local_def: ast::LocalDef,
+    ) -> Id<LocalDef> {
+        let id = self.bodies.local_defs.alloc(LocalDef {
+            value,
+            ty: None,
+            ty_range: None,
+            pattern: None,
+            name: Some(ident),
+            ast: local_def,
+        });
+
+        let name = self.interner.intern(ident.text(self.tree));
+        self.insert_into_current_scope(name, id);
+        self.bodies.symbol_map.insert(ident, Symbol::Local(id));
+
+        id
+    }
+
+    fn lower_record_path(&mut self, path: Option<ast::Path>) -> Option<PathWithRange> {
+        let path = path?;
+        let ident = path.top_level_name(self.tree)?;
+
+        if let Some(nested_name_token) = path.nested_name(self.tree) {
+            let (module_name_tokens, record_name_token, module_name) =
+                self.lower_module_path(path, ident, nested_name_token);
+
+            let module_range = TextRange::new(
+                module_name_tokens[0].range(self.tree).start(),
+                module_name_tokens[module_name_tokens.len() - 1].range(self.tree).end(),
+            );
+
+            let record_name = self.interner.intern(record_name_token.text(self.tree));
+
+            let fqn = Fqn { module: Name(module_name), name: Name(record_name) };
+
+            match self.world_index.get_definition(fqn) {
+                Ok(Definition::Record(_)) => {
+                    self.bodies.other_module_references.insert(fqn);
+
+                    for module_name_token in module_name_tokens {
+                        self.bodies
+                            .symbol_map
+                            .insert(module_name_token, Symbol::Module(Name(module_name)));
+                    }
+                    self.bodies.tys.insert(record_name_token, Path::OtherModule(fqn));
+
+                    return Some(PathWithRange::OtherModule {
+                        fqn,
+                        module_range,
+                        name_range: record_name_token.range(self.tree),
+                    });
+                }
+
+                Err(GetDefinitionError::UnknownModule) => {
+                    self.diagnostics.push(LoweringDiagnostic {
+                        kind: LoweringDiagnosticKind::UndefinedModule { name: module_name },
+                        range: module_range,
+                    });
+
+                    for module_name_token in module_name_tokens {
+                        self.bodies.symbol_map.insert(module_name_token, Symbol::Unknown);
+                    }
+                    self.bodies.symbol_map.insert(record_name_token, Symbol::Unknown);
+
+                    return None;
+                }
+
+                Ok(Definition::Function(_) | Definition::Interface(_))
+                | Err(GetDefinitionError::UnknownDefinition) => {
+                    self.diagnostics.push(LoweringDiagnostic {
+                        kind: LoweringDiagnosticKind::UndefinedRecord { name: record_name },
+                        range: record_name_token.range(self.tree),
+                    });
+
+                    for module_name_token in module_name_tokens {
+                        self.bodies
+                            .symbol_map
+                            .insert(module_name_token, Symbol::Module(Name(module_name)));
+                    }
+                    self.bodies.symbol_map.insert(record_name_token, Symbol::Unknown);
+
+                    return None;
+                }
+            }
+        }
+
+        let key = self.interner.intern(ident.text(self.tree));
+        let name = self.look_up_item(key).unwrap_or(Name(key));
+
+        if let Some(Definition::Record(_)) = self.index.get_definition(name) {
+            self.bodies.tys.insert(ident, Path::ThisModule(name));
+            return Some(PathWithRange::ThisModule { name, range: ident.range(self.tree) });
+        }
+
+        self.diagnostics.push(LoweringDiagnostic {
+            kind: LoweringDiagnosticKind::UndefinedRecord { name: key },
+            range: ident.range(self.tree),
+        });
+        self.bodies.symbol_map.insert(ident, Symbol::Unknown);
+
+        None
+    }
+
     fn lower_expr(&mut self, expr: Option<ast::Expr>) -> Id<Expr> {
         let expr_ast = match expr {
             Some(expr) => expr,
@@ -202,6 +624,19 @@ impl<'a> Ctx<'a> {
 
         let expr = match expr_ast {
             ast::Expr::Binary(binary_expr) => self.lower_binary_expr(binary_expr),
+            ast::Expr::Paren(paren_expr) => return self.lower_expr(paren_expr.inner(self.tree)),
+            ast::Expr::Tuple(tuple_expr) => Expr::Tuple {
+                elements: tuple_expr
+                    .elements(self.tree)
+                    .map(|element| self.lower_expr(Some(element)))
+                    .collect(),
+            },
+            ast::Expr::TupleField(tuple_field_expr) => {
+                self.lower_tuple_field_expr(tuple_field_expr)
+            }
+            ast::Expr::Try(try_expr) => {
+                Expr::Try { inner: self.lower_expr(try_expr.inner(self.tree)) }
+            }
             ast::Expr::Block(block) => self.lower_block(block),
             ast::Expr::Call(call) => self.lower_local_or_call(call),
             ast::Expr::IntLiteral(int_literal) => self.lower_int_literal(int_literal),
@@ -223,6 +658,12 @@ impl<'a> Ctx<'a> {
             Some(ast::BinaryOperator::Sub(_)) => BinaryOperator::Sub,
             Some(ast::BinaryOperator::Mul(_)) => BinaryOperator::Mul,
             Some(ast::BinaryOperator::Div(_)) => BinaryOperator::Div,
+            Some(ast::BinaryOperator::Eq(_)) => BinaryOperator::Eq,
+            Some(ast::BinaryOperator::NotEq(_)) => BinaryOperator::NotEq,
+            Some(ast::BinaryOperator::Lt(_)) => BinaryOperator::Lt,
+            Some(ast::BinaryOperator::LtEq(_)) => BinaryOperator::LtEq,
+            Some(ast::BinaryOperator::Gt(_)) => BinaryOperator::Gt,
+            Some(ast::BinaryOperator::GtEq(_)) => BinaryOperator::GtEq,
             None => return Expr::Missing,
         };
 
@@ -232,6 +673,40 @@ impl<'a> Ctx<'a> {
     fn lower_block(&mut self, block: ast::Block) -> Expr {
         self.create_new_child_scope();
 
+        let mut item_scope = FxHashMap::default();
+        let mut local_functions = Vec::new();
+
+        for def in block.defs(self.tree) {
+            let ident = match def {
+                ast::Def::Function(function) => function.name(self.tree),
+                ast::Def::Record(record) => record.name(self.tree),
+                ast::Def::Test(_) | ast::Def::Interface(_) | ast::Def::Impl(_) => None,
+            };
+
+            // indexing has already given every local definition a name of its own
+            let local_name = match ident.and_then(|ident| self.index.local_definition_name(ident)) {
+                Some(local_name) => local_name,
+                None => continue,
+            };
+
+            item_scope.insert(self.interner.intern(ident.unwrap().text(self.tree)), local_name);
+
+            if let ast::Def::Function(function) = def {
+                local_functions.push((function, local_name));
+            }
+        }
+
+        self.item_scopes.push(item_scope);
+
+        // local functions can’t see the locals and parameters around them
+        for (function, local_name) in local_functions {
+            let params = std::mem::take(&mut self.params);
+            let scopes = std::mem::replace(&mut self.scopes, vec![FxHashMap::default()]);
+            self.lower_function(function, Some(local_name));
+            self.params = params;
+            self.scopes = scopes;
+        }
+
         let mut statements = Vec::new();
 
         for statement in block.statements(self.tree) {
@@ -243,6 +718,7 @@ impl<'a> Ctx<'a> {
             block.tail_expr(self.tree).map(|tail_expr| self.lower_expr(Some(tail_expr)));
 
         self.destroy_current_scope();
+        self.item_scopes.pop();
 
         Expr::Block { statements, tail_expr }
     }
@@ -258,10 +734,15 @@ impl<'a> Ctx<'a> {
             None => return Expr::Missing,
         };
 
-        if let Some(function_name_token) = path.nested_name(self.tree) {
-            let module_name_token = ident;
+        if let Some(nested_name_token) = path.nested_name(self.tree) {
+            let (module_name_tokens, function_name_token, module_name) =
+                self.lower_module_path(path, ident, nested_name_token);
+
+            let module_range = TextRange::new(
+                module_name_tokens[0].range(self.tree).start(),
+                module_name_tokens[module_name_tokens.len() - 1].range(self.tree).end(),
+            );
 
-            let module_name = self.interner.intern(module_name_token.text(self.tree));
             let function_name = self.interner.intern(function_name_token.text(self.tree));
 
             let fqn = Fqn { module: Name(module_name), name: Name(function_name) };
@@ -270,15 +751,17 @@ impl<'a> Ctx<'a> {
                 Ok(definition) => {
                     let path = PathWithRange::OtherModule {
                         fqn,
-                        module_range: module_name_token.range(self.tree),
+                        module_range,
                         name_range: function_name_token.range(self.tree),
                     };
 
                     self.bodies.other_module_references.insert(fqn);
 
-                    self.bodies
-                        .symbol_map
-                        .insert(module_name_token, Symbol::Module(Name(module_name)));
+                    for module_name_token in module_name_tokens {
+                        self.bodies
+                            .symbol_map
+                            .insert(module_name_token, Symbol::Module(Name(module_name)));
+                    }
 
                     match definition {
                         Definition::Function(function) => {
@@ -287,17 +770,26 @@ impl<'a> Ctx<'a> {
                                 .insert(function_name_token, Symbol::Function(path.path()));
                             return self.lower_call(call, function, path, function_name_token);
                         }
-                        Definition::Record(_) => todo!(),
+                        Definition::Record(_) | Definition::Interface(_) => {
+                            self.bodies.symbol_map.insert(function_name_token, Symbol::Unknown);
+                            return self.lower_non_function_call(
+                                definition,
+                                function_name,
+                                function_name_token,
+                            );
+                        }
                     }
                 }
 
                 Err(GetDefinitionError::UnknownModule) => {
                     self.diagnostics.push(LoweringDiagnostic {
                         kind: LoweringDiagnosticKind::UndefinedModule { name: module_name },
-                        range: module_name_token.range(self.tree),
+                        range: module_range,
                     });
 
-                    self.bodies.symbol_map.insert(module_name_token, Symbol::Unknown);
+                    for module_name_token in module_name_tokens {
+                        self.bodies.symbol_map.insert(module_name_token, Symbol::Unknown);
+                    }
                     self.bodies.symbol_map.insert(function_name_token, Symbol::Unknown);
 
                     return Expr::Missing;
@@ -309,9 +801,11 @@ impl<'a> Ctx<'a> {
                         range: function_name_token.range(self.tree),
                     });
 
-                    self.bodies
-                        .symbol_map
-                        .insert(module_name_token, Symbol::Module(Name(module_name)));
+                    for module_name_token in module_name_tokens {
+                        self.bodies
+                            .symbol_map
+                            .insert(module_name_token, Symbol::Module(Name(module_name)));
+                    }
                     self.bodies.symbol_map.insert(function_name_token, Symbol::Unknown);
 
                     return Expr::Missing;
@@ -327,6 +821,14 @@ impl<'a> Ctx<'a> {
             return Expr::Local(def);
         }
 
+        if let Some((return_value_name, ensures)) = self.return_value {
+            if name == return_value_name {
+                check_args_for_local(call, ident, self.tree, name, &mut self.diagnostics);
+                self.bodies.symbol_map.insert(ident, Symbol::ReturnValue(ensures));
+                return Expr::ReturnValue;
+            }
+        }
+
         if let Some((idx, ast)) = self.look_up_param(name) {
             check_args_for_local(call, ident, self.tree, name, &mut self.diagnostics);
             self.bodies.symbol_map.insert(ident, Symbol::Param(ast));
@@ -334,6 +836,7 @@ impl<'a> Ctx<'a> {
         }
 
         let name = Name(name);
+        let name = self.look_up_item(name.0).unwrap_or(name);
         if let Some(definition) = self.index.get_definition(name) {
             let path = PathWithRange::ThisModule { name, range: ident.range(self.tree) };
 
@@ -342,10 +845,19 @@ impl<'a> Ctx<'a> {
                     self.bodies.symbol_map.insert(ident, Symbol::Function(path.path()));
                     return self.lower_call(call, function, path, ident);
                 }
-                Definition::Record(_) => todo!(),
+                Definition::Record(_) | Definition::Interface(_) => {
+                    self.bodies.symbol_map.insert(ident, Symbol::Unknown);
+                    return self.lower_non_function_call(definition, name.0, ident);
+                }
             }
         }
 
+        // built-ins can be shadowed by any other definition
+        if let builtin @ ("panic" | "assert" | "none") = ident.text(self.tree) {
+            self.bodies.symbol_map.insert(ident, Symbol::Unknown);
+            return self.lower_builtin(call, builtin, name, ident);
+        }
+
         self.diagnostics.push(LoweringDiagnostic {
             kind: LoweringDiagnosticKind::UndefinedLocal { name: name.0 },
             range: ident.range(self.tree),
@@ -373,6 +885,57 @@ impl<'a> Ctx<'a> {
         }
     }
 
+    fn lower_module_path(
+        &mut self,
+        path: ast::Path,
+        ident: ast::Ident,
+        nested_name_token: ast::Ident,
+    ) -> (Vec<ast::Ident>, ast::Ident, Key) {
+        // `package.module.name` refers to a module of a dependency,
+        // while `module.name` refers to a module of the current package
+        match path.doubly_nested_name(self.tree) {
+            Some(doubly_nested_name_token) => {
+                let package_name = ident.text(self.tree);
+                let module_name = nested_name_token.text(self.tree);
+                (
+                    vec![ident, nested_name_token],
+                    doubly_nested_name_token,
+                    self.interner.intern(&format!("{package_name}.{module_name}")),
+                )
+            }
+            None => {
+                let module_name = match self.package {
+                    // every package shares the same standard library
+                    Some(_) if ident.text(self.tree) == STD_MODULE_NAME => {
+                        STD_MODULE_NAME.to_string()
+                    }
+                    Some(package) => {
+                        format!("{}.{}", self.interner.lookup(package.0), ident.text(self.tree))
+                    }
+                    None => ident.text(self.tree).to_string(),
+                };
+                (vec![ident], nested_name_token, self.interner.intern(&module_name))
+            }
+        }
+    }
+
+    fn lower_non_function_call(
+        &mut self,
+        definition: &Definition,
+        name: Key,
+        ident: ast::Ident,
+    ) -> Expr {
+        let kind = match definition {
+            Definition::Record(_) => LoweringDiagnosticKind::CalledRecord { name },
+            Definition::Interface(_) => LoweringDiagnosticKind::CalledInterface { name },
+            Definition::Function(_) => unreachable!(),
+        };
+
+        self.diagnostics.push(LoweringDiagnostic { kind, range: ident.range(self.tree) });
+
+        Expr::Missing
+    }
+
     fn lower_call(
         &mut self,
         call: ast::Call,
@@ -380,38 +943,200 @@ impl<'a> Ctx<'a> {
         path: PathWithRange,
         ident: ast::Ident,
     ) -> Expr {
-        let arg_list = call.arg_list(self.tree);
+        let function_name = match path {
+            PathWithRange::ThisModule { name, .. } => name.0,
+            PathWithRange::OtherModule { fqn, .. } => fqn.name.0,
+        };
 
-        let expected = function.params.len() as u32;
-        let got = match &arg_list {
-            Some(al) => al.args(self.tree).count() as u32,
-            None => 0,
+        let call_args: Vec<_> = match call.arg_list(self.tree) {
+            Some(arg_list) => arg_list.args(self.tree).collect(),
+            None => Vec::new(),
         };
 
-        if expected != got {
-            let name = match path {
-                PathWithRange::ThisModule { name, .. } => name.0,
-                PathWithRange::OtherModule { fqn, .. } => fqn.name.0,
-            };
+        let expected = function.params.len() as u32;
+        let got = call_args.iter().filter(|arg| arg.name(self.tree).is_none()).count() as u32;
+
+        let uses_named_args = got as usize != call_args.len();
+        let has_defaults = function.params.iter().any(|param| param.has_default);
 
+        // plain positional calls must supply every argument
+        if got > expected || (got != expected && !uses_named_args && !has_defaults) {
             self.diagnostics.push(LoweringDiagnostic {
-                kind: LoweringDiagnosticKind::MismatchedArgCount { name, expected, got },
+                kind: LoweringDiagnosticKind::MismatchedArgCount {
+                    name: function_name,
+                    expected,
+                    got,
+                },
                 range: ident.range(self.tree),
             });
 
             return Expr::Missing;
         }
 
-        let mut args = Vec::new();
+        // match up arguments with the parameters they are for,
+        // first by position and then by name
+        let mut slots: Vec<Option<ast::Arg>> = vec![None; function.params.len()];
+        let mut written_order = Vec::new();
+        let mut has_errors = false;
+        let mut seen_named_arg = false;
+        let mut positional_idx = 0;
+
+        for arg in call_args {
+            let arg_name = match arg.name(self.tree) {
+                Some(arg_name) => arg_name,
+                None => {
+                    if seen_named_arg {
+                        self.diagnostics.push(LoweringDiagnostic {
+                            kind: LoweringDiagnosticKind::PositionalArgAfterNamedArg,
+                            range: arg.range(self.tree),
+                        });
+                        has_errors = true;
+                    } else {
+                        slots[positional_idx] = Some(arg);
+                        written_order.push(positional_idx);
+                    }
+
+                    positional_idx += 1;
+                    continue;
+                }
+            };
+
+            seen_named_arg = true;
+            let name = self.interner.intern(arg_name.text(self.tree));
+
+            let idx = function.params.iter().position(|param| param.name == Some(Name(name)));
+
+            match idx {
+                Some(idx) if slots[idx].is_none() => {
+                    slots[idx] = Some(arg);
+                    written_order.push(idx);
+                }
+                Some(_) => {
+                    self.diagnostics.push(LoweringDiagnostic {
+                        kind: LoweringDiagnosticKind::DuplicateArg { name },
+                        range: arg_name.range(self.tree),
+                    });
+                    has_errors = true;
+                }
+                None => {
+                    self.diagnostics.push(LoweringDiagnostic {
+                        kind: LoweringDiagnosticKind::UnknownNamedArg {
+                            function: function_name,
+                            name,
+                        },
+                        range: arg_name.range(self.tree),
+                    });
+                    has_errors = true;
+                }
+            }
+        }
+
+        let mut lowered_args = vec![None; slots.len()];
+        for &idx in &written_order {
+            let value = slots[idx].as_ref().unwrap().value(self.tree);
+            lowered_args[idx] = Some(self.lower_expr(value));
+        }
 
-        if let Some(arg_list) = arg_list {
-            for arg in arg_list.args(self.tree) {
-                let expr = self.lower_expr(arg.value(self.tree));
-                args.push(expr);
+        let mut args = Vec::new();
+        let mut evaluation_order = written_order;
+
+        for (idx, (lowered_arg, param)) in
+            lowered_args.into_iter().zip(&function.params).enumerate()
+        {
+            match lowered_arg {
+                Some(arg) => args.push(arg),
+                None if param.has_default => {
+                    args.push(
+                        self.bodies
+                            .exprs
+                            .alloc(Expr::Default { function: path.path(), idx: idx as u32 }),
+                    );
+                    evaluation_order.push(idx);
+                }
+                None => {
+                    // earlier errors likely explain why the argument is missing
+                    if let (Some(param_name), false) = (param.name, has_errors) {
+                        self.diagnostics.push(LoweringDiagnostic {
+                            kind: LoweringDiagnosticKind::MissingArg {
+                                function: function_name,
+                                param: param_name.0,
+                            },
+                            range: ident.range(self.tree),
+                        });
+                    }
+                    has_errors = true;
+                }
             }
         }
 
-        Expr::Call { path, args }
+        if has_errors {
+            return Expr::Missing;
+        }
+
+        let evaluation_order = if evaluation_order.windows(2).all(|w| w[0] < w[1]) {
+            None
+        } else {
+            Some(evaluation_order.into_iter().map(|idx| idx as u32).collect())
+        };
+
+        Expr::Call { path, args, evaluation_order }
+    }
+
+    fn lower_builtin(
+        &mut self,
+        call: ast::Call,
+        builtin: &str,
+        name: Name,
+        ident: ast::Ident,
+    ) -> Expr {
+        let args: Vec<_> = match call.arg_list(self.tree) {
+            Some(arg_list) => arg_list.args(self.tree).collect(),
+            None => Vec::new(),
+        };
+
+        let expected = match builtin {
+            "none" => 0,
+            "panic" => 1,
+            _ => 2,
+        };
+        let got = args.len() as u32;
+
+        if expected != got {
+            self.diagnostics.push(LoweringDiagnostic {
+                kind: LoweringDiagnosticKind::MismatchedArgCount { name: name.0, expected, got },
+                range: ident.range(self.tree),
+            });
+
+            return Expr::Missing;
+        }
+
+        let mut args = args.into_iter().map(|arg| self.lower_expr(arg.value(self.tree)));
+
+        match builtin {
+            "none" => Expr::None,
+            "panic" => Expr::Panic { message: args.next().unwrap() },
+            _ => Expr::Assert { condition: args.next().unwrap(), message: args.next().unwrap() },
+        }
+    }
+
+    fn lower_tuple_field_expr(&mut self, tuple_field_expr: ast::TupleFieldExpr) -> Expr {
+        let base = self.lower_expr(tuple_field_expr.base(self.tree));
+
+        let int = match tuple_field_expr.idx(self.tree) {
+            Some(int) => int,
+            None => return Expr::Missing,
+        };
+
+        match int.text(self.tree).parse() {
+            Ok(idx) => Expr::TupleField { base, idx },
+            Err(_) => {
+                self.diagnostics.push(LoweringDiagnostic {
+                    kind: LoweringDiagnosticKind::OutOfRangeIntLiteral,
+                    range: int.range(self.tree),
+                });
+                Expr::Missing
+            }
+        }
     }
 
     fn lower_int_literal(&mut self, int_literal: ast::IntLiteral) -> Expr {
@@ -481,6 +1206,24 @@ impl<'a> Ctx<'a> {
         None
     }
 
+    fn lower_named_ty(&mut self, name: Name) -> Ty {
+        if self.generic_params.contains(&name) {
+            return Ty::Generic(name);
+        }
+
+        if let Some(self_ty) = &self.self_ty {
+            if self.interner.lookup(name.0) == "Self" {
+                return self_ty.clone();
+            }
+        }
+
+        Ty::Named(self.look_up_item(name.0).unwrap_or(name))
+    }
+
+    fn look_up_item(&self, name: Key) -> Option<Name> {
+        self.item_scopes.iter().rev().find_map(|scope| scope.get(&name).copied())
+    }
+
     fn look_up_param(&mut self, name: Key) -> Option<(u32, ast::Param)> {
         self.params.get(&name).copied()
     }
@@ -499,8 +1242,119 @@ impl Bodies {
         self.function_bodies[&name]
     }
 
-    pub fn range_for_expr(&self, expr: Id<Expr>) -> TextRange {
-        self.expr_ranges[expr]
+    /// The contract of the function with the given name, if it has one.
+    pub fn function_contract(&self, name: Name) -> Option<&Contract> {
+        self.function_contracts.get(&name)
+    }
+
+    pub fn test_body(&self, name: Name) -> Id<Expr> {
+        self.test_bodies[&name]
+    }
+
+    pub fn param_default(&self, function: Name, idx: u32) -> Option<Id<Expr>> {
+        self.param_defaults.get(&(function, idx)).copied()
+    }
+
+    /// Every expression making up the function’s body, contract and parameter defaults,
+    /// in the order they were lowered.
+    pub fn function_exprs(&self, name: Name) -> Vec<Id<Expr>> {
+        let mut roots: Vec<_> = self.function_bodies.get(&name).copied().into_iter().collect();
+
+        if let Some(contract) = self.function_contracts.get(&name) {
+            roots.extend(contract.requires.iter().chain(&contract.ensures));
+        }
+
+        roots.extend(
+            self.param_defaults
+                .iter()
+                .filter(|((function, _), _)| *function == name)
+                .map(|(_, expr)| *expr),
+        );
+
+        let mut exprs = Vec::new();
+        for root in roots {
+            self.collect_exprs(root, &mut exprs);
+        }
+        exprs.sort_unstable_by_key(|expr| expr.to_raw());
+        exprs.dedup();
+
+        exprs
+    }
+
+    fn collect_exprs(&self, expr: Id<Expr>, exprs: &mut Vec<Id<Expr>>) {
+        exprs.push(expr);
+
+        match &self[expr] {
+            Expr::Missing
+            | Expr::IntLiteral(_)
+            | Expr::StringLiteral(_)
+            | Expr::Local(_)
+            | Expr::Param { .. }
+            | Expr::None
+            | Expr::Default { .. }
+            | Expr::ReturnValue => {}
+            Expr::Binary { lhs, rhs, .. } => {
+                self.collect_exprs(*lhs, exprs);
+                self.collect_exprs(*rhs, exprs);
+            }
+            Expr::Block { statements, tail_expr } => {
+                for statement in statements {
+                    match self[*statement] {
+                        Statement::Expr(expr) => self.collect_exprs(expr, exprs),
+                        Statement::LocalDef(local_def) => {
+                            self.collect_exprs(self[local_def].value, exprs);
+
+                            // what a pattern binds is read out of the value by expressions of its own
+                            if let Some(pattern) = self[local_def].pattern {
+                                self.collect_pattern_exprs(pattern, exprs);
+                            }
+                        }
+                    }
+                }
+
+                if let Some(tail_expr) = tail_expr {
+                    self.collect_exprs(*tail_expr, exprs);
+                }
+            }
+            Expr::Try { inner: expr }
+            | Expr::Field { base: expr, .. }
+            | Expr::TupleField { base: expr, .. }
+            | Expr::Panic { message: expr } => self.collect_exprs(*expr, exprs),
+            Expr::Tuple { elements: exprs_inside } | Expr::Call { args: exprs_inside, .. } => {
+                for expr in exprs_inside {
+                    self.collect_exprs(*expr, exprs);
+                }
+            }
+            Expr::Assert { condition, message } => {
+                self.collect_exprs(*condition, exprs);
+                self.collect_exprs(*message, exprs);
+            }
+        }
+    }
+
+    fn collect_pattern_exprs(&self, pattern: Id<Pattern>, exprs: &mut Vec<Id<Expr>>) {
+        match &self[pattern] {
+            Pattern::Missing | Pattern::Wildcard => {}
+            Pattern::Binding(local_def) => self.collect_exprs(self[*local_def].value, exprs),
+            Pattern::Record { fields, .. } => {
+                for field in fields {
+                    self.collect_pattern_exprs(field.pattern, exprs);
+                }
+            }
+            Pattern::Tuple { elements } => {
+                for element in elements {
+                    self.collect_pattern_exprs(*element, exprs);
+                }
+            }
+        }
+    }
+
+    pub fn range_for_expr(&self, expr: Id<Expr>) -> TextRange {
+        self.expr_ranges[expr]
+    }
+
+    pub fn range_for_pattern(&self, pattern: Id<Pattern>) -> TextRange {
+        self.pattern_ranges[pattern]
     }
 
     pub fn other_module_references(&self) -> &FxHashSet<Fqn> {
@@ -511,24 +1365,44 @@ impl Bodies {
         self.symbol_map.get(&ident).copied()
     }
 
+    pub fn is_ident_ty(&self, ident: ast::Ident) -> bool {
+        self.tys.contains_key(&ident)
+    }
+
+    pub fn ty_ident_path(&self, ident: ast::Ident) -> Option<Path> {
+        self.tys.get(&ident).copied()
+    }
+
     fn shrink_to_fit(&mut self) {
         let Self {
             local_defs,
+            patterns,
+            pattern_ranges,
             statements,
             exprs,
             expr_ranges,
             function_bodies,
+            function_contracts,
+            test_bodies,
+            param_defaults,
             other_module_references,
             symbol_map,
+            tys,
         } = self;
 
         local_defs.shrink_to_fit();
+        patterns.shrink_to_fit();
+        pattern_ranges.shrink_to_fit();
         statements.shrink_to_fit();
         exprs.shrink_to_fit();
         expr_ranges.shrink_to_fit();
         function_bodies.shrink_to_fit();
+        function_contracts.shrink_to_fit();
+        test_bodies.shrink_to_fit();
+        param_defaults.shrink_to_fit();
         other_module_references.shrink_to_fit();
         symbol_map.shrink_to_fit();
+        tys.shrink_to_fit();
     }
 }
 
@@ -540,6 +1414,14 @@ impl std::ops::Index<Id<LocalDef>> for Bodies {
     }
 }
 
+impl std::ops::Index<Id<Pattern>> for Bodies {
+    type Output = Pattern;
+
+    fn index(&self, id: Id<Pattern>) -> &Self::Output {
+        &self.patterns[id]
+    }
+}
+
 impl std::ops::Index<Id<Statement>> for Bodies {
     type Output = Statement;
 
@@ -558,9 +1440,21 @@ impl std::ops::Index<Id<Expr>> for Bodies {
 
 impl Bodies {
     pub fn debug(&self, interner: &Interner) -> String {
+        self.debug_filtered(None, interner)
+    }
+
+    /// Like [`Bodies::debug`], but only showing the body, contract and parameter defaults
+    /// of the function with the given name.
+    pub fn debug_function(&self, name: Name, interner: &Interner) -> String {
+        self.debug_filtered(Some(name), interner)
+    }
+
+    fn debug_filtered(&self, function: Option<Name>, interner: &Interner) -> String {
         let mut s = String::new();
+        let is_shown = |name: Name| function.is_none_or(|function| name == function);
 
-        let mut function_bodies: Vec<_> = self.function_bodies.iter().collect();
+        let mut function_bodies: Vec<_> =
+            self.function_bodies.iter().filter(|(name, _)| is_shown(**name)).collect();
         function_bodies.sort_unstable_by_key(|(name, _)| *name);
 
         for (name, expr_id) in function_bodies {
@@ -569,6 +1463,47 @@ impl Bodies {
             s.push_str(";\n");
         }
 
+        let mut function_contracts: Vec<_> =
+            self.function_contracts.iter().filter(|(name, _)| is_shown(**name)).collect();
+        function_contracts.sort_unstable_by_key(|(name, _)| *name);
+
+        for (name, contract) in function_contracts {
+            for expr_id in &contract.requires {
+                s.push_str(&format!("requires {} -> ", interner.lookup(name.0)));
+                write_expr(*expr_id, self, &mut s, interner, 0);
+                s.push_str(";\n");
+            }
+
+            for expr_id in &contract.ensures {
+                s.push_str(&format!("ensures {} -> ", interner.lookup(name.0)));
+                write_expr(*expr_id, self, &mut s, interner, 0);
+                s.push_str(";\n");
+            }
+        }
+
+        let mut param_defaults: Vec<_> =
+            self.param_defaults.iter().filter(|((name, _), _)| is_shown(*name)).collect();
+        param_defaults.sort_unstable_by_key(|(key, _)| *key);
+
+        for ((name, idx), expr_id) in param_defaults {
+            s.push_str(&format!("default {} p{} -> ", interner.lookup(name.0), idx));
+            write_expr(*expr_id, self, &mut s, interner, 0);
+            s.push_str(";\n");
+        }
+
+        if function.is_some() {
+            return s;
+        }
+
+        let mut test_bodies: Vec<_> = self.test_bodies.iter().collect();
+        test_bodies.sort_unstable_by_key(|(name, _)| *name);
+
+        for (name, expr_id) in test_bodies {
+            s.push_str(&format!("test \"{}\" -> ", interner.lookup(name.0)));
+            write_expr(*expr_id, self, &mut s, interner, 0);
+            s.push_str(";\n");
+        }
+
         if !self.other_module_references.is_empty() {
             let mut other_module_references: Vec<_> = self.other_module_references.iter().collect();
             other_module_references.sort_unstable();
@@ -609,6 +1544,12 @@ impl Bodies {
                         BinaryOperator::Sub => s.push('-'),
                         BinaryOperator::Mul => s.push('*'),
                         BinaryOperator::Div => s.push('/'),
+                        BinaryOperator::Eq => s.push_str("=="),
+                        BinaryOperator::NotEq => s.push_str("!="),
+                        BinaryOperator::Lt => s.push('<'),
+                        BinaryOperator::LtEq => s.push_str("<="),
+                        BinaryOperator::Gt => s.push('>'),
+                        BinaryOperator::GtEq => s.push_str(">="),
                     }
 
                     s.push(' ');
@@ -653,7 +1594,38 @@ impl Bodies {
 
                 Expr::Param { idx } => s.push_str(&format!("p{}", idx)),
 
-                Expr::Call { path, args } => {
+                Expr::None => s.push_str("none"),
+
+                Expr::Try { inner } => {
+                    write_expr(*inner, bodies, s, interner, indentation);
+                    s.push('?');
+                }
+
+                Expr::Field { base, field } => {
+                    write_expr(*base, bodies, s, interner, indentation);
+                    s.push_str(&format!(".{}", interner.lookup(field.0)));
+                }
+
+                Expr::Tuple { elements } => {
+                    s.push('(');
+                    for (idx, element) in elements.iter().enumerate() {
+                        if idx != 0 {
+                            s.push_str(", ");
+                        }
+                        write_expr(*element, bodies, s, interner, indentation);
+                    }
+                    if elements.len() == 1 {
+                        s.push(',');
+                    }
+                    s.push(')');
+                }
+
+                Expr::TupleField { base, idx } => {
+                    write_expr(*base, bodies, s, interner, indentation);
+                    s.push_str(&format!(".{}", idx));
+                }
+
+                Expr::Call { path, args, .. } => {
                     match path {
                         PathWithRange::ThisModule { name, .. } => {
                             s.push_str(interner.lookup(name.0))
@@ -675,6 +1647,74 @@ impl Bodies {
                         write_expr(*arg, bodies, s, interner, indentation);
                     }
                 }
+
+                Expr::Default { idx, .. } => s.push_str(&format!("<default p{}>", idx)),
+
+                Expr::Panic { message } => {
+                    s.push_str("panic ");
+                    write_expr(*message, bodies, s, interner, indentation);
+                }
+
+                Expr::Assert { condition, message } => {
+                    s.push_str("assert ");
+                    write_expr(*condition, bodies, s, interner, indentation);
+                    s.push_str(", ");
+                    write_expr(*message, bodies, s, interner, indentation);
+                }
+
+                Expr::ReturnValue => s.push_str("<return value>"),
+            }
+        }
+
+        fn write_pattern(id: Id<Pattern>, bodies: &Bodies, s: &mut String, interner: &Interner) {
+            match &bodies[id] {
+                Pattern::Missing => s.push_str("<missing>"),
+
+                Pattern::Wildcard => s.push('_'),
+
+                Pattern::Binding(local_def_id) => {
+                    s.push_str(&format!("l{}", local_def_id.to_raw()))
+                }
+
+                Pattern::Record { record, fields } => {
+                    match record {
+                        Some(PathWithRange::ThisModule { name, .. }) => {
+                            s.push_str(interner.lookup(name.0))
+                        }
+                        Some(PathWithRange::OtherModule { fqn, .. }) => s.push_str(&format!(
+                            "{}.{}",
+                            interner.lookup(fqn.module.0),
+                            interner.lookup(fqn.name.0)
+                        )),
+                        None => s.push_str("<missing>"),
+                    }
+
+                    if fields.is_empty() {
+                        s.push_str(" {}");
+                        return;
+                    }
+
+                    s.push_str(" { ");
+                    for (idx, field) in fields.iter().enumerate() {
+                        if idx != 0 {
+                            s.push_str(", ");
+                        }
+                        s.push_str(&format!("{}: ", interner.lookup(field.name.0)));
+                        write_pattern(field.pattern, bodies, s, interner);
+                    }
+                    s.push_str(" }");
+                }
+
+                Pattern::Tuple { elements } => {
+                    s.push('(');
+                    for (idx, element) in elements.iter().enumerate() {
+                        if idx != 0 {
+                            s.push_str(", ");
+                        }
+                        write_pattern(*element, bodies, s, interner);
+                    }
+                    s.push(')');
+                }
             }
         }
 
@@ -691,8 +1731,17 @@ impl Bodies {
                     s.push(';');
                 }
                 Statement::LocalDef(local_def_id) => {
-                    s.push_str(&format!("let l{} = ", local_def_id.to_raw()));
-                    write_expr(bodies[*local_def_id].value, bodies, s, interner, indentation);
+                    let local_def = &bodies[*local_def_id];
+                    s.push_str(&format!("let l{}", local_def_id.to_raw()));
+                    if let Some(pattern) = local_def.pattern {
+                        s.push_str(" @ ");
+                        write_pattern(pattern, bodies, s, interner);
+                    }
+                    if let Some(ty) = &local_def.ty {
+                        s.push_str(&format!(": {}", ty.display(interner)));
+                    }
+                    s.push_str(" = ");
+                    write_expr(local_def.value, bodies, s, interner, indentation);
                     s.push(';');
                 }
             }
@@ -714,6 +1763,18 @@ mod tests {
         expected_diagnostics: impl Fn(
             &mut Interner,
         ) -> [(LoweringDiagnosticKind, std::ops::Range<u32>); N],
+    ) {
+        check_in_package(None, input, expect, expected_diagnostics);
+    }
+
+    #[track_caller]
+    fn check_in_package<const N: usize>(
+        package: Option<&str>,
+        input: &str,
+        expect: Expect,
+        expected_diagnostics: impl Fn(
+            &mut Interner,
+        ) -> [(LoweringDiagnosticKind, std::ops::Range<u32>); N],
     ) {
         let modules = utils::split_multi_module_test_data(input);
         let mut interner = Interner::default();
@@ -738,7 +1799,9 @@ mod tests {
         let root = ast::Root::cast(tree.root(), &tree).unwrap();
         let (index, _) = index(root, &tree, &mut interner);
 
-        let (bodies, actual_diagnostics) = lower(root, &tree, &index, &world_index, &mut interner);
+        let package = package.map(|package| Name(interner.intern(package)));
+        let (bodies, actual_diagnostics) =
+            lower(root, &tree, package, &index, &world_index, &mut interner);
 
         expect.assert_eq(&bodies.debug(&interner));
 
@@ -797,6 +1860,174 @@ mod tests {
         );
     }
 
+    #[test]
+    fn comparison() {
+        check(
+            r#"
+                fnc is_positive(n: s32): bool -> n > 0;
+            "#,
+            expect![[r#"
+                fnc is_positive -> p0 > 0;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn paren_expr() {
+        check(
+            r#"
+                fnc answer: s32 -> (((42)));
+            "#,
+            expect![[r#"
+                fnc answer -> 42;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn panic() {
+        check(
+            r#"
+                fnc fail -> panic "oh no";
+            "#,
+            expect![[r#"
+                fnc fail -> panic "oh no";
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn assert() {
+        check(
+            r#"
+                fnc check(n: s32) -> assert n != 0, "n was zero";
+            "#,
+            expect![[r#"
+                fnc check -> assert p0 != 0, "n was zero";
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn assert_without_message() {
+        check(
+            r#"
+                fnc check(n: s32) -> assert n != 0;
+            "#,
+            expect![[r#"
+                fnc check -> <missing>;
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::MismatchedArgCount {
+                        name: i.intern("assert"),
+                        expected: 2,
+                        got: 1,
+                    },
+                    38..44,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn function_shadows_panic() {
+        check(
+            r#"
+                fnc panic: s32 -> 0;
+                fnc f: s32 -> panic;
+            "#,
+            expect![[r#"
+                fnc panic -> 0;
+                fnc f -> panic;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn contract() {
+        check(
+            r#"
+                fnc div(a: s32, b: s32): s32
+                    requires b != 0
+                    ensures quotient: quotient * b <= a
+                    -> a / b;
+            "#,
+            expect![[r#"
+                fnc div -> p0 / p1;
+                requires div -> p1 != 0;
+                ensures div -> <return value> * p1 <= p0;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn return_value_shadows_params() {
+        check(
+            r#"
+                fnc double(n: s32): s32 ensures n: n > 0 -> n * 2;
+            "#,
+            expect![[r#"
+                fnc double -> p0 * 2;
+                ensures double -> <return value> > 0;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn return_value_is_only_visible_in_its_ensures_clause() {
+        check(
+            r#"
+                fnc f: s32
+                    requires result > 0
+                    ensures result: result > 0
+                    ensures result > 0
+                    -> result;
+            "#,
+            expect![[r#"
+                fnc f -> <missing>;
+                requires f -> <missing> > 0;
+                ensures f -> <return value> > 0;
+                ensures f -> <missing> > 0;
+            "#]],
+            |i| {
+                [
+                    (LoweringDiagnosticKind::UndefinedLocal { name: i.intern("result") }, 177..183),
+                    (LoweringDiagnosticKind::UndefinedLocal { name: i.intern("result") }, 57..63),
+                    (LoweringDiagnosticKind::UndefinedLocal { name: i.intern("result") }, 143..149),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn test() {
+        check(
+            r#"
+                fnc add(x: s32, y: s32): s32 -> x + y;
+                test "adds numbers" -> {
+                    let sum = add 1, 2;
+                    assert sum == 3, "wrong sum";
+                };
+            "#,
+            expect![[r#"
+                fnc add -> p0 + p1;
+                test "adds numbers" -> {
+                    let l0 = add 1, 2;
+                    assert l0 == 3, "wrong sum";
+                };
+            "#]],
+            |_| [],
+        );
+    }
+
     #[test]
     fn string_literal() {
         check(
@@ -895,6 +2126,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn local_def_with_ty() {
+        check(
+            r#"
+                fnc foo -> {
+                    let a: s32 = 7;
+                    let b: Point = a;
+                };
+            "#,
+            expect![[r#"
+                fnc foo -> {
+                    let l0: s32 = 7;
+                    let l1: Point = l0;
+                };
+            "#]],
+            |_| [],
+        );
+    }
+
     #[test]
     fn block_with_one_expr_statement() {
         check(
@@ -1141,13 +2391,276 @@ mod tests {
     }
 
     #[test]
-    fn locals_take_precedence_over_functions() {
+    fn call_with_default_arg() {
         check(
             r#"
-                fnc foo -> {};
-                fnc bar(n: s32) -> {};
-
-                fnc main -> {
+                fnc greet(greeting: string, name: string = "world"): string -> name;
+                fnc a: string -> greet "hello";
+            "#,
+            expect![[r#"
+                fnc greet -> p1;
+                fnc a -> greet "hello", <default p1>;
+                default greet p1 -> "world";
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn default_value_cannot_refer_to_params() {
+        check(
+            r#"
+                fnc f(x: s32, y: s32 = x) -> {};
+            "#,
+            expect![[r#"
+                fnc f -> {};
+                default f p1 -> <missing>;
+            "#]],
+            |i| [(LoweringDiagnosticKind::UndefinedLocal { name: i.intern("x") }, 40..41)],
+        );
+    }
+
+    #[test]
+    fn call_with_named_args() {
+        check(
+            r#"
+                fnc sub(x: s32, y: s32): s32 -> x - y;
+                fnc a: s32 -> sub y: 1, x: 2;
+            "#,
+            expect![[r#"
+                fnc sub -> p0 - p1;
+                fnc a -> sub 2, 1;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn call_with_positional_and_named_args() {
+        check(
+            r#"
+                fnc f(x: s32, y: s32 = 0, z: s32 = 0): s32 -> x + y + z;
+                fnc a: s32 -> f 1, z: 3;
+            "#,
+            expect![[r#"
+                fnc f -> p0 + p1 + p2;
+                fnc a -> f 1, <default p1>, 3;
+                default f p1 -> 0;
+                default f p2 -> 0;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn call_with_unknown_named_arg() {
+        check(
+            r#"
+                fnc f(x: s32) -> {};
+                fnc a -> f x: 1, y: 2;
+            "#,
+            expect![[r#"
+                fnc f -> {};
+                fnc a -> <missing>;
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::UnknownNamedArg {
+                        function: i.intern("f"),
+                        name: i.intern("y"),
+                    },
+                    71..72,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn call_with_duplicate_arg() {
+        check(
+            r#"
+                fnc f(x: s32) -> {};
+                fnc a -> f 1, x: 2;
+            "#,
+            expect![[r#"
+                fnc f -> {};
+                fnc a -> <missing>;
+            "#]],
+            |i| [(LoweringDiagnosticKind::DuplicateArg { name: i.intern("x") }, 68..69)],
+        );
+    }
+
+    #[test]
+    fn call_with_missing_arg() {
+        check(
+            r#"
+                fnc f(x: s32, y: s32 = 0) -> {};
+                fnc a -> f y: 1;
+            "#,
+            expect![[r#"
+                fnc f -> {};
+                fnc a -> <missing>;
+                default f p1 -> 0;
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::MissingArg {
+                        function: i.intern("f"),
+                        param: i.intern("x"),
+                    },
+                    75..76,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn call_with_positional_arg_after_named_arg() {
+        check(
+            r#"
+                fnc f(x: s32, y: s32) -> {};
+                fnc a -> f x: 1, 2;
+            "#,
+            expect![[r#"
+                fnc f -> {};
+                fnc a -> <missing>;
+            "#]],
+            |_| [(LoweringDiagnosticKind::PositionalArgAfterNamedArg, 79..80)],
+        );
+    }
+
+    #[test]
+    fn call_with_default_arg_from_other_module() {
+        check(
+            r#"
+                #- main
+                fnc a: s32 -> math.inc 1;
+                #- math
+                fnc inc(x: s32, by: s32 = 1): s32 -> x + by;
+            "#,
+            expect![[r#"
+                fnc a -> math.inc 1, <default p1>;
+
+                References to other modules:
+                - math.inc
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn local_function() {
+        check(
+            r#"
+                fnc quadruple(n: s32): s32 -> {
+                    fnc double(n: s32): s32 -> n * 2;
+                    double double n
+                };
+            "#,
+            expect![[r#"
+                fnc quadruple -> { quadruple::double quadruple::double p0 };
+                fnc quadruple::double -> p0 * 2;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn local_functions_are_visible_throughout_their_block() {
+        check(
+            r#"
+                fnc f: s32 -> {
+                    let a = one;
+                    fnc one: s32 -> two - 1;
+                    fnc two: s32 -> 2;
+                    a
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 = f::one;
+                    l0
+                };
+                fnc f::one -> f::two - 1;
+                fnc f::two -> 2;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn local_functions_are_not_visible_outside_their_block() {
+        check(
+            r#"
+                fnc f -> {
+                    { fnc g -> {}; };
+                    g;
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    {};
+                    <missing>;
+                };
+                fnc f::g -> {};
+            "#]],
+            |i| [(LoweringDiagnosticKind::UndefinedLocal { name: i.intern("g") }, 86..87)],
+        );
+    }
+
+    #[test]
+    fn local_functions_cannot_see_enclosing_locals() {
+        check(
+            r#"
+                fnc f(x: s32) -> {
+                    let y = 1;
+                    fnc g: s32 -> x + y;
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 = 1;
+                };
+                fnc f::g -> <missing> + <missing>;
+            "#]],
+            |i| {
+                [
+                    (LoweringDiagnosticKind::UndefinedLocal { name: i.intern("x") }, 101..102),
+                    (LoweringDiagnosticKind::UndefinedLocal { name: i.intern("y") }, 105..106),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn local_functions_shadow_module_functions() {
+        check(
+            r#"
+                fnc f: s32 -> {
+                    fnc g: s32 -> 1;
+                    g
+                };
+                fnc g: s32 -> 2;
+                fnc h: s32 -> g;
+            "#,
+            expect![[r#"
+                fnc f -> { f::g };
+                fnc g -> 2;
+                fnc f::g -> 1;
+                fnc h -> g;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn locals_take_precedence_over_functions() {
+        check(
+            r#"
+                fnc foo -> {};
+                fnc bar(n: s32) -> {};
+
+                fnc main -> {
                     let foo = 0;
                     let bar = 1;
                     foo;
@@ -1206,6 +2719,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn call_interface() {
+        check(
+            r#"
+                ifc Describe { fnc describe(value: Self): string; };
+                fnc main -> Describe 1;
+            "#,
+            expect![[r#"
+                fnc main -> <missing>;
+            "#]],
+            |i| [(LoweringDiagnosticKind::CalledInterface { name: i.intern("Describe") }, 98..106)],
+        );
+    }
+
+    #[test]
+    fn call_record() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                fnc main -> Point 1, 2;
+            "#,
+            expect![[r#"
+                fnc main -> <missing>;
+            "#]],
+            |i| [(LoweringDiagnosticKind::CalledRecord { name: i.intern("Point") }, 75..80)],
+        );
+    }
+
     #[test]
     fn nested_blocks() {
         check(
@@ -1265,6 +2806,303 @@ mod tests {
         );
     }
 
+    #[test]
+    fn destructure_record() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                fnc f(p: Point): s32 -> {
+                    let Point { x, y: _ } = p;
+                    x
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ Point { x: l1, y: _ } = p0;
+                    l1
+                };
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_nested_record() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                rec Line { start: Point, end: Point };
+                fnc f(l: Line): s32 -> {
+                    let Line { start: Point { x: a, y: _ }, end: Point { x: b, y: _ } } = l;
+                    b - a
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ Line { start: Point { x: l1, y: _ }, end: Point { x: l2, y: _ } } = p0;
+                    l2 - l1
+                };
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tuple() {
+        check(
+            r#"
+                fnc pair(n: s32): (s32, string) -> (n, "n");
+                fnc single: (s32,) -> (1,);
+            "#,
+            expect![[r#"
+                fnc pair -> (p0, "n");
+                fnc single -> (1,);
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tuple_field() {
+        check(
+            r#"
+                fnc f(pair: (s32, (s32, s32))): s32 -> pair.0 + pair.1.1;
+            "#,
+            expect![[r#"
+                fnc f -> p0.0 + p0.1.1;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tuple_field_out_of_range_int() {
+        check(
+            r#"
+                fnc f(pair: (s32, s32)): s32 -> pair.99999999999;
+            "#,
+            expect![[r#"
+                fnc f -> <missing>;
+            "#]],
+            |_| [(LoweringDiagnosticKind::OutOfRangeIntLiteral, 54..65)],
+        );
+    }
+
+    #[test]
+    fn destructure_tuple() {
+        check(
+            r#"
+                fnc f(triple: (s32, s32, (s32, s32))): s32 -> {
+                    let (a, _, (b, c)) = triple;
+                    a + b + c
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ (l1, _, (l2, l3)) = p0;
+                    l1 + l2 + l3
+                };
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn bindings_of_pattern_are_not_visible_in_value() {
+        check(
+            r#"
+                rec Wrapper { x: s32 };
+                fnc f(w: Wrapper): s32 -> {
+                    let Wrapper { x } = x;
+                    x
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ Wrapper { x: l1 } = <missing>;
+                    l1
+                };
+            "#]],
+            |i| [(LoweringDiagnosticKind::UndefinedLocal { name: i.intern("x") }, 125..126)],
+        );
+    }
+
+    #[test]
+    fn wildcard_let() {
+        check(
+            r#"
+                fnc f -> {
+                    let _ = 92;
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ _ = 92;
+                };
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_field_twice() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                fnc f(p: Point): s32 -> {
+                    let Point { y: b, y: c } = p;
+                    b
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ Point { y: l1 } = p0;
+                    l1
+                };
+            "#]],
+            |i| [(LoweringDiagnosticKind::DuplicateFieldPattern { name: i.intern("y") }, 127..128)],
+        );
+    }
+
+    #[test]
+    fn destructure_undefined_record() {
+        check(
+            r#"
+                fnc f(n: s32) -> {
+                    let Foo { a } = n;
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ <missing> { a: l1 } = p0;
+                };
+            "#]],
+            |i| [(LoweringDiagnosticKind::UndefinedRecord { name: i.intern("Foo") }, 60..63)],
+        );
+    }
+
+    #[test]
+    fn destructure_function_as_record() {
+        check(
+            r#"
+                fnc g -> {};
+                fnc f -> {
+                    let g {} = 1;
+                };
+            "#,
+            expect![[r#"
+                fnc g -> {};
+                fnc f -> {
+                    let l0 @ <missing> {} = 1;
+                };
+            "#]],
+            |i| [(LoweringDiagnosticKind::UndefinedRecord { name: i.intern("g") }, 81..82)],
+        );
+    }
+
+    #[test]
+    fn destructure_local_record() {
+        check(
+            r#"
+                fnc f: s32 -> {
+                    rec Pair { a: s32, b: s32 };
+                    fnc make: Pair -> make;
+                    let Pair { a, b } = make;
+                    a + b
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ f::Pair { a: l1, b: l2 } = f::make;
+                    l1 + l2
+                };
+                fnc f::make -> f::make;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_record_from_other_module() {
+        check(
+            r#"
+                #- main
+                fnc f: s32 -> {
+                    let geo.Point { x, y } = geo.origin;
+                    x + y
+                };
+                #- geo
+                rec Point { x: s32, y: s32 };
+                fnc origin: Point -> origin;
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0 @ geo.Point { x: l1, y: l2 } = geo.origin;
+                    l1 + l2
+                };
+
+                References to other modules:
+                - geo.Point
+                - geo.origin
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn none() {
+        check(
+            r#"
+                fnc f: s32? -> none;
+            "#,
+            expect![[r#"
+                fnc f -> none;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn none_with_args() {
+        check(
+            r#"
+                fnc f: s32? -> none 1;
+            "#,
+            expect![[r#"
+                fnc f -> <missing>;
+            "#]],
+            |i| {
+                [(
+                    LoweringDiagnosticKind::MismatchedArgCount {
+                        name: i.intern("none"),
+                        expected: 0,
+                        got: 1,
+                    },
+                    32..36,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn try_expr() {
+        check(
+            r#"
+                fnc f(n: s32?): s32? -> {
+                    let a: s32? = n;
+                    a? + 1
+                };
+            "#,
+            expect![[r#"
+                fnc f -> {
+                    let l0: s32? = p0;
+                    l0? + 1
+                };
+            "#]],
+            |_| [],
+        );
+    }
+
     #[test]
     fn functions_from_other_module() {
         check(
@@ -1355,6 +3193,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn function_from_module_of_dependency() {
+        check(
+            r#"
+                #- main
+                fnc a: s32 -> utils.math.double 4;
+                #- utils.math
+                fnc double(n: s32): s32 -> n * 2;
+            "#,
+            expect![[r#"
+                fnc a -> utils.math.double 4;
+
+                References to other modules:
+                - utils.math.double
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn function_from_undefined_module_of_dependency() {
+        check(
+            r#"
+                fnc a -> utils.math.double 4;
+            "#,
+            expect![[r#"
+                fnc a -> <missing>;
+            "#]],
+            |i| {
+                [(LoweringDiagnosticKind::UndefinedModule { name: i.intern("utils.math") }, 26..36)]
+            },
+        );
+    }
+
+    #[test]
+    fn function_from_other_module_in_same_package() {
+        check_in_package(
+            Some("utils"),
+            r#"
+                #- main
+                fnc quadruple(n: s32): s32 -> math.double math.double n;
+                #- utils.math
+                fnc double(n: s32): s32 -> n * 2;
+            "#,
+            expect![[r#"
+                fnc quadruple -> utils.math.double utils.math.double p0;
+
+                References to other modules:
+                - utils.math.double
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn std_from_other_package() {
+        check_in_package(
+            Some("utils"),
+            r#"
+                #- main
+                fnc a: s32 -> std.abs 4;
+                #- std
+                ext fnc abs(n: s32): s32;
+            "#,
+            expect![[r#"
+                fnc a -> std.abs 4;
+
+                References to other modules:
+                - std.abs
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn debug_one_function() {
+        let text = r#"
+            fnc one: s32 -> 1;
+            fnc add(a: s32, b: s32 = one): s32 ensures sum: sum > a -> a + b;
+            test "one" -> one;
+        "#;
+
+        let mut interner = Interner::default();
+        let world_index = WorldIndex::default();
+        let tokens = lexer::lex(text);
+        let tree = parser::parse_source_file(&tokens, text).into_syntax_tree();
+        let root = ast::Root::cast(tree.root(), &tree).unwrap();
+        let (index, _) = index(root, &tree, &mut interner);
+        let (bodies, _) = lower(root, &tree, None, &index, &world_index, &mut interner);
+
+        expect![[r#"
+            fnc add -> p0 + p1;
+            ensures add -> <return value> > p0;
+            default add p1 -> one;
+        "#]]
+        .assert_eq(&bodies.debug_function(Name(interner.intern("add")), &interner));
+    }
+
     #[test]
     fn functions_with_same_name() {
         check(
@@ -10,15 +10,19 @@ use text_size::TextRange;
 pub struct Index {
     pub(crate) definitions: FxHashMap<Name, Definition>,
     pub(crate) range_info: FxHashMap<Name, RangeInfo>,
+    tests: FxHashMap<Name, Test>,
+    impls: Vec<Impl>,
     docs: FxHashMap<Name, Docs>,
-    tys: FxHashSet<ast::Ident>,
+    tys: FxHashMap<ast::Ident, Name>,
+    local_names: FxHashMap<ast::Ident, Name>,
+    local_definitions: FxHashSet<Name>,
 }
 
 impl Index {
     pub fn functions(&self) -> impl Iterator<Item = (Name, &Function)> {
         self.definitions.iter().filter_map(|(name, definition)| match definition {
             Definition::Function(f) => Some((*name, f)),
-            Definition::Record(_) => None,
+            Definition::Record(_) | Definition::Interface(_) => None,
         })
     }
 
@@ -37,7 +41,7 @@ impl Index {
     pub fn function_names(&self) -> impl Iterator<Item = Name> + '_ {
         self.definitions.iter().filter_map(|(name, def)| match def {
             Definition::Function(_) => Some(*name),
-            Definition::Record(_) => None,
+            Definition::Record(_) | Definition::Interface(_) => None,
         })
     }
 
@@ -46,15 +50,61 @@ impl Index {
     }
 
     pub fn is_ident_ty(&self, ident: ast::Ident) -> bool {
-        self.tys.contains(&ident)
+        self.tys.contains_key(&ident)
+    }
+
+    pub fn ty_ident_name(&self, ident: ast::Ident) -> Option<Name> {
+        self.tys.get(&ident).copied()
+    }
+
+    pub fn tests(&self) -> impl Iterator<Item = (Name, &Test)> {
+        self.tests.iter().map(|(name, test)| (*name, test))
+    }
+
+    pub fn get_test(&self, name: Name) -> Option<&Test> {
+        self.tests.get(&name)
+    }
+
+    pub fn impls(&self) -> impl Iterator<Item = &Impl> {
+        self.impls.iter()
+    }
+
+    /// The mangled name of the definition inside a block or an implementation
+    /// which is named by the given identifier.
+    pub fn local_definition_name(&self, ident: ast::Ident) -> Option<Name> {
+        self.local_names.get(&ident).copied()
+    }
+
+    /// The doc comments of the definition with the given name, if it has any.
+    pub fn docs(&self, name: Name) -> Option<&Docs> {
+        self.docs.get(&name)
+    }
+
+    /// Whether the definition is local to a block or an implementation
+    /// rather than part of the module’s API.
+    pub fn is_local_definition(&self, name: Name) -> bool {
+        self.local_definitions.contains(&name)
     }
 
     fn shrink_to_fit(&mut self) {
-        let Self { definitions, range_info, docs, tys } = self;
+        let Self {
+            definitions,
+            range_info,
+            tests,
+            impls,
+            docs,
+            tys,
+            local_names,
+            local_definitions,
+        } = self;
         definitions.shrink_to_fit();
         range_info.shrink_to_fit();
+        tests.shrink_to_fit();
+        impls.shrink_to_fit();
         docs.shrink_to_fit();
         tys.shrink_to_fit();
+        local_names.shrink_to_fit();
+        local_definitions.shrink_to_fit();
     }
 }
 
@@ -62,12 +112,34 @@ impl Index {
 pub enum Definition {
     Function(Function),
     Record(Record),
+    Interface(Interface),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Function {
+    pub generic_params: Vec<GenericParam>,
     pub params: Vec<Param>,
     pub return_ty: Ty,
+    pub is_extern: bool,
+    /// The interface declaring the function, which each implementation provides a body for.
+    ///
+    /// The type being implemented for is the first generic parameter, named `Self`.
+    pub interface: Option<Name>,
+}
+
+impl Function {
+    pub fn has_body(&self) -> bool {
+        !self.is_extern && self.interface.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericParam {
+    pub name: Name,
+    /// The interfaces whatever type the parameter is instantiated with has to implement.
+    pub bounds: Vec<Name>,
+    /// Whether `T?` is written anywhere in the function, including its body.
+    pub used_as_optional: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -75,6 +147,38 @@ pub struct Record {
     pub fields: Vec<Field>,
 }
 
+/// An `ifc` definition.
+///
+/// The functions it declares are definitions of their own,
+/// so they are called just like any other function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub functions: Vec<Name>,
+}
+
+/// An `imp Interface for ty { ... };` definition.
+///
+/// Implementations have no name, so their functions are indexed under names nobody can write,
+/// made up of the interface, the type and the name of the function.
+#[derive(Debug, Clone)]
+pub struct Impl {
+    pub interface: Name,
+    pub interface_range: TextRange,
+    pub ty: Ty,
+    pub ty_range: Option<TextRange>,
+    /// The name of each function along with the name it is indexed under.
+    pub functions: Vec<(Name, Name)>,
+}
+
+/// A `test "name" -> ...;` definition.
+///
+/// Tests live in their own namespace, keyed by their name as written between the quotes.
+#[derive(Debug, Clone)]
+pub struct Test {
+    pub whole: TextRange,
+    pub name: TextRange,
+}
+
 #[derive(Debug, Clone)]
 pub struct RangeInfo {
     pub whole: TextRange,
@@ -84,14 +188,22 @@ pub struct RangeInfo {
 
 #[derive(Debug, Clone)]
 pub enum TysRangeInfo {
-    Function { return_ty: Option<TextRange>, param_tys: Vec<Option<TextRange>> },
-    Record { field_tys: Vec<Option<TextRange>> },
+    Function {
+        return_ty: Option<TextRange>,
+        param_tys: Vec<Option<TextRange>>,
+        bounds: Vec<Vec<TextRange>>,
+    },
+    Record {
+        field_tys: Vec<Option<TextRange>>,
+    },
+    Interface,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Param {
     pub name: Option<Name>,
     pub ty: Ty,
+    pub has_default: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -100,9 +212,12 @@ pub struct Field {
     pub ty: Ty,
 }
 
+/// The doc comments written above a definition.
 #[derive(Debug, Clone)]
-struct Docs {
-    paras: Vec<String>,
+pub struct Docs {
+    /// The text of each paragraph, with its lines joined by spaces.
+    /// Paragraphs are separated by an empty doc comment.
+    pub paras: Vec<String>,
 }
 
 pub fn index(
@@ -114,16 +229,28 @@ pub fn index(
         index: Index {
             definitions: FxHashMap::default(),
             range_info: FxHashMap::default(),
+            tests: FxHashMap::default(),
+            impls: Vec::new(),
             docs: FxHashMap::default(),
-            tys: FxHashSet::default(),
+            tys: FxHashMap::default(),
+            local_names: FxHashMap::default(),
+            local_definitions: FxHashSet::default(),
         },
         diagnostics: Vec::new(),
+        local_scopes: Vec::new(),
+        generic_params: Vec::new(),
+        interface: None,
+        self_ty: None,
         tree,
         interner,
     };
 
     for def in root.defs(tree) {
-        ctx.index_def(def);
+        // bodies of duplicate definitions are never lowered,
+        // so neither are the definitions inside them
+        if ctx.index_def(def, None) {
+            ctx.index_local_defs_in_def(def, None);
+        }
     }
 
     ctx.index.shrink_to_fit();
@@ -134,29 +261,41 @@ pub fn index(
 struct Ctx<'a> {
     index: Index,
     diagnostics: Vec<IndexingDiagnostic>,
+    local_scopes: Vec<FxHashMap<Name, Name>>,
+    generic_params: Vec<Name>,
+    interface: Option<(Name, TextRange)>,
+    self_ty: Option<Ty>,
     tree: &'a SyntaxTree,
     interner: &'a mut Interner,
 }
 
 impl Ctx<'_> {
-    fn index_def(&mut self, def: ast::Def) {
+    fn index_def(&mut self, def: ast::Def, local_name: Option<Name>) -> bool {
         let result = match def {
             ast::Def::Function(function) => self.index_function(function),
             ast::Def::Record(record) => self.index_record(record),
+            ast::Def::Interface(interface) => self.index_interface(interface),
+            ast::Def::Test(test) => return self.index_test(test),
+            ast::Def::Impl(imp) => return self.index_impl(imp),
         };
 
         let (definition, name, name_token, docs, tys_range_info) = match result {
             IndexDefinitionResult::Ok { definition, name, name_token, docs, tys_range_info } => {
                 (definition, name, name_token, docs, tys_range_info)
             }
-            IndexDefinitionResult::NoName => return,
+            IndexDefinitionResult::NoName => return false,
         };
 
-        match self.index.definitions.entry(name) {
-            Entry::Occupied(_) => self.diagnostics.push(IndexingDiagnostic {
-                kind: IndexingDiagnosticKind::AlreadyDefined { name: name.0 },
-                range: name_token.range(self.tree),
-            }),
+        let name = local_name.unwrap_or(name);
+
+        let is_new = match self.index.definitions.entry(name) {
+            Entry::Occupied(_) => {
+                self.diagnostics.push(IndexingDiagnostic {
+                    kind: IndexingDiagnosticKind::AlreadyDefined { name: name.0 },
+                    range: name_token.range(self.tree),
+                });
+                false
+            }
             Entry::Vacant(vacant_entry) => {
                 vacant_entry.insert(definition);
                 self.index.range_info.insert(
@@ -167,8 +306,9 @@ impl Ctx<'_> {
                         tys: tys_range_info,
                     },
                 );
+                true
             }
-        }
+        };
 
         if let Some(d) = docs {
             let mut paras = vec![String::new()];
@@ -191,6 +331,175 @@ impl Ctx<'_> {
 
             self.index.docs.insert(name, Docs { paras });
         }
+
+        is_new
+    }
+
+    fn index_local_defs_in_def(&mut self, def: ast::Def, local_name: Option<Name>) {
+        match def {
+            ast::Def::Function(function) => {
+                let name = match (local_name, function.name(self.tree)) {
+                    (Some(name), _) => self.interner.lookup(name.0).to_string(),
+                    (None, Some(ident)) => ident.text(self.tree).to_string(),
+                    (None, None) => return,
+                };
+
+                if let Some(param_list) = function.param_list(self.tree) {
+                    for param in param_list.params(self.tree) {
+                        self.index_local_defs_in_expr(param.default_value(self.tree), &name);
+                    }
+                }
+
+                self.index_local_defs_in_expr(function.body(self.tree), &name);
+
+                for requires in function.requires(self.tree) {
+                    self.index_local_defs_in_expr(requires.condition(self.tree), &name);
+                }
+
+                for ensures in function.ensures(self.tree) {
+                    self.index_local_defs_in_expr(ensures.condition(self.tree), &name);
+                }
+            }
+            ast::Def::Record(_) => {}
+            ast::Def::Interface(interface) => {
+                for function in interface.functions(self.tree) {
+                    self.index_local_defs_in_def(ast::Def::Function(function), None);
+                }
+            }
+            // the functions of an implementation are indexed along with it
+            ast::Def::Impl(_) => {}
+            ast::Def::Test(test) => {
+                if let Some(string_literal) = test.name(self.tree) {
+                    let name = test_name(string_literal, self.tree);
+                    self.index_local_defs_in_expr(test.body(self.tree), &name);
+                }
+            }
+        }
+    }
+
+    fn index_local_defs_in_expr(&mut self, expr: Option<ast::Expr>, parent: &str) {
+        let expr = match expr {
+            Some(expr) => expr,
+            None => return,
+        };
+
+        match expr {
+            ast::Expr::Binary(binary_expr) => {
+                self.index_local_defs_in_expr(binary_expr.lhs(self.tree), parent);
+                self.index_local_defs_in_expr(binary_expr.rhs(self.tree), parent);
+            }
+            ast::Expr::Paren(paren_expr) => {
+                self.index_local_defs_in_expr(paren_expr.inner(self.tree), parent);
+            }
+            ast::Expr::Tuple(tuple_expr) => {
+                for element in tuple_expr.elements(self.tree) {
+                    self.index_local_defs_in_expr(Some(element), parent);
+                }
+            }
+            ast::Expr::TupleField(tuple_field_expr) => {
+                self.index_local_defs_in_expr(tuple_field_expr.base(self.tree), parent);
+            }
+            ast::Expr::Try(try_expr) => {
+                self.index_local_defs_in_expr(try_expr.inner(self.tree), parent);
+            }
+            ast::Expr::Block(block) => self.index_local_defs_in_block(block, parent),
+            ast::Expr::Call(call) => {
+                if let Some(arg_list) = call.arg_list(self.tree) {
+                    for arg in arg_list.args(self.tree) {
+                        self.index_local_defs_in_expr(arg.value(self.tree), parent);
+                    }
+                }
+            }
+            ast::Expr::IntLiteral(_) | ast::Expr::StringLiteral(_) => {}
+        }
+    }
+
+    fn index_local_defs_in_block(&mut self, block: ast::Block, parent: &str) {
+        let mut scope = FxHashMap::default();
+        let mut defs = Vec::new();
+
+        for def in block.defs(self.tree) {
+            let ident = match def {
+                ast::Def::Function(function) => function.name(self.tree),
+                ast::Def::Record(record) => record.name(self.tree),
+                ast::Def::Test(_) | ast::Def::Interface(_) | ast::Def::Impl(_) => None,
+            };
+            let ident = match ident {
+                Some(ident) => ident,
+                None => continue,
+            };
+
+            let name = Name(self.interner.intern(ident.text(self.tree)));
+
+            if scope.contains_key(&name) {
+                self.diagnostics.push(IndexingDiagnostic {
+                    kind: IndexingDiagnosticKind::AlreadyDefined { name: name.0 },
+                    range: ident.range(self.tree),
+                });
+                continue;
+            }
+
+            // blocks next to each other can define things with the same name
+            let mut local_name = format!("{parent}::{}", ident.text(self.tree));
+            let mut n = 2;
+            while self.index.definitions.contains_key(&Name(self.interner.intern(&local_name))) {
+                local_name = format!("{parent}::{}#{n}", ident.text(self.tree));
+                n += 1;
+            }
+            let local_name = Name(self.interner.intern(&local_name));
+
+            scope.insert(name, local_name);
+            self.index.local_names.insert(ident, local_name);
+            self.index.local_definitions.insert(local_name);
+            defs.push((def, local_name));
+        }
+
+        // everything defined in a block is visible throughout it,
+        // including to the other definitions in the block
+        self.local_scopes.push(scope);
+
+        for (def, local_name) in &defs {
+            self.index_def(*def, Some(*local_name));
+        }
+
+        for (def, local_name) in defs {
+            self.index_local_defs_in_def(def, Some(local_name));
+        }
+
+        for statement in block.statements(self.tree) {
+            let expr = match statement {
+                ast::Statement::LocalDef(local_def) => local_def.value(self.tree),
+                ast::Statement::ExprStatement(expr_statement) => expr_statement.expr(self.tree),
+            };
+            self.index_local_defs_in_expr(expr, parent);
+        }
+
+        self.index_local_defs_in_expr(block.tail_expr(self.tree), parent);
+
+        self.local_scopes.pop();
+    }
+
+    fn index_test(&mut self, test: ast::Test) -> bool {
+        let name_literal = match test.name(self.tree) {
+            Some(string_literal) => string_literal,
+            None => return false,
+        };
+        let name = Name(self.interner.intern(&test_name(name_literal, self.tree)));
+        let name_range = name_literal.range(self.tree);
+
+        match self.index.tests.entry(name) {
+            Entry::Occupied(_) => {
+                self.diagnostics.push(IndexingDiagnostic {
+                    kind: IndexingDiagnosticKind::AlreadyDefined { name: name.0 },
+                    range: name_range,
+                });
+                false
+            }
+            Entry::Vacant(vacant_entry) => {
+                vacant_entry.insert(Test { whole: test.range(self.tree), name: name_range });
+                true
+            }
+        }
     }
 
     fn index_function(&mut self, function: ast::Function) -> IndexDefinitionResult {
@@ -200,6 +509,63 @@ impl Ctx<'_> {
         };
         let name = Name(self.interner.intern(name_token.text(self.tree)));
 
+        let mut generic_params = Vec::new();
+        let mut bound_ranges = Vec::new();
+
+        // functions declared by an interface are generic over the type implementing it
+        if let Some((interface, interface_range)) = self.interface {
+            let self_name = Name(self.interner.intern("Self"));
+            generic_params.push(GenericParam {
+                name: self_name,
+                bounds: vec![interface],
+                used_as_optional: false,
+            });
+            bound_ranges.push(vec![interface_range]);
+        }
+
+        if let Some(generic_param_list) = function.generic_param_list(self.tree) {
+            for generic_param in generic_param_list.params(self.tree) {
+                let ident = match generic_param.name(self.tree) {
+                    Some(ident) => ident,
+                    None => continue,
+                };
+                let name = Name(self.interner.intern(ident.text(self.tree)));
+
+                let mut bounds = Vec::new();
+                let mut ranges = Vec::new();
+                for bound in generic_param.bounds(self.tree) {
+                    if let Some(ident) = bound.top_level_name(self.tree) {
+                        bounds.push(Name(self.interner.intern(ident.text(self.tree))));
+                        ranges.push(ident.range(self.tree));
+                    }
+                }
+
+                generic_params.push(GenericParam { name, bounds, used_as_optional: false });
+                bound_ranges.push(ranges);
+            }
+        }
+
+        // optionals of optionals can't be represented,
+        // so calls must not instantiate these with an optional type
+        let optional_names: Vec<_> = function
+            .syntax()
+            .descendant_nodes(self.tree)
+            .filter_map(|node| ast::Ty::cast(node, self.tree))
+            .filter(|ty| ty.questions(self.tree).next().is_some())
+            .filter_map(|ty| ty.path(self.tree).and_then(|path| path.top_level_name(self.tree)))
+            .map(|ident| ident.text(self.tree))
+            .collect();
+        for generic_param in &mut generic_params {
+            let name = self.interner.lookup(generic_param.name.0);
+            generic_param.used_as_optional = optional_names.contains(&name);
+        }
+
+        // the parameter and return types can refer to the generic parameters
+        let outer_generic_params = std::mem::replace(
+            &mut self.generic_params,
+            generic_params.iter().map(|generic_param| generic_param.name).collect(),
+        );
+
         let mut params = Vec::new();
         let mut param_ty_ranges = Vec::new();
 
@@ -213,8 +579,9 @@ impl Ctx<'_> {
                 param_ty_ranges.push(ty.map(|ty| ty.range(self.tree)));
 
                 let ty = self.lower_ty(ty);
+                let has_default = param.default_value(self.tree).is_some();
 
-                params.push(Param { name, ty });
+                params.push(Param { name, ty, has_default });
             }
         }
 
@@ -227,18 +594,127 @@ impl Ctx<'_> {
             None => (Ty::Unit, None),
         };
 
+        self.generic_params = outer_generic_params;
+
         IndexDefinitionResult::Ok {
-            definition: Definition::Function(Function { params, return_ty }),
+            definition: Definition::Function(Function {
+                generic_params,
+                params,
+                return_ty,
+                is_extern: function.ext_kw(self.tree).is_some(),
+                interface: self.interface.map(|(interface, _)| interface),
+            }),
             name,
             name_token,
             docs: function.docs(self.tree),
             tys_range_info: TysRangeInfo::Function {
                 return_ty: return_ty_range,
                 param_tys: param_ty_ranges,
+                bounds: bound_ranges,
             },
         }
     }
 
+    fn index_interface(&mut self, interface: ast::Interface) -> IndexDefinitionResult {
+        let name_token = match interface.name(self.tree) {
+            Some(ident) => ident,
+            None => return IndexDefinitionResult::NoName,
+        };
+        let name = Name(self.interner.intern(name_token.text(self.tree)));
+
+        let mut functions = Vec::new();
+
+        self.interface = Some((name, name_token.range(self.tree)));
+        for function in interface.functions(self.tree) {
+            if self.index_def(ast::Def::Function(function), None) {
+                let ident = function.name(self.tree).unwrap();
+                functions.push(Name(self.interner.intern(ident.text(self.tree))));
+            }
+        }
+        self.interface = None;
+
+        IndexDefinitionResult::Ok {
+            definition: Definition::Interface(Interface { functions }),
+            name,
+            name_token,
+            docs: interface.docs(self.tree),
+            tys_range_info: TysRangeInfo::Interface,
+        }
+    }
+
+    fn index_impl(&mut self, imp: ast::Impl) -> bool {
+        let interface_ident =
+            match imp.interface(self.tree).and_then(|path| path.top_level_name(self.tree)) {
+                Some(ident) => ident,
+                None => return false,
+            };
+        let interface = Name(self.interner.intern(interface_ident.text(self.tree)));
+
+        let ty_ast = imp.ty(self.tree);
+        let ty = self.lower_ty(ty_ast);
+
+        let interface_range = interface_ident.range(self.tree);
+        if self.index.impls.iter().any(|imp| imp.interface == interface && imp.ty == ty) {
+            let end = ty_ast.map_or(interface_range, |ty| ty.range(self.tree)).end();
+            self.diagnostics.push(IndexingDiagnostic {
+                kind: IndexingDiagnosticKind::ConflictingImpl { interface: interface.0, ty },
+                range: TextRange::new(interface_range.start(), end),
+            });
+            return false;
+        }
+
+        let prefix =
+            format!("{} for {}", interface_ident.text(self.tree), ty.display(self.interner));
+
+        let mut functions = Vec::new();
+        let mut defs = Vec::new();
+
+        self.self_ty = Some(ty.clone());
+
+        for function in imp.functions(self.tree) {
+            let ident = match function.name(self.tree) {
+                Some(ident) => ident,
+                None => continue,
+            };
+            let name = Name(self.interner.intern(ident.text(self.tree)));
+            let local_name =
+                Name(self.interner.intern(&format!("{prefix}::{}", ident.text(self.tree))));
+
+            if functions.iter().any(|(other, _)| *other == name) {
+                self.diagnostics.push(IndexingDiagnostic {
+                    kind: IndexingDiagnosticKind::AlreadyDefined { name: name.0 },
+                    range: ident.range(self.tree),
+                });
+                continue;
+            }
+
+            if !self.index_def(ast::Def::Function(function), Some(local_name)) {
+                continue;
+            }
+
+            self.index.local_names.insert(ident, local_name);
+            self.index.local_definitions.insert(local_name);
+            functions.push((name, local_name));
+            defs.push((function, local_name));
+        }
+
+        for (function, local_name) in defs {
+            self.index_local_defs_in_def(ast::Def::Function(function), Some(local_name));
+        }
+
+        self.self_ty = None;
+
+        self.index.impls.push(Impl {
+            interface,
+            interface_range,
+            ty,
+            ty_range: ty_ast.map(|ty| ty.range(self.tree)),
+            functions,
+        });
+
+        true
+    }
+
     fn index_record(&mut self, record: ast::Record) -> IndexDefinitionResult {
         let name_token = match record.name(self.tree) {
             Some(ident) => ident,
@@ -271,25 +747,98 @@ impl Ctx<'_> {
     }
 
     fn lower_ty(&mut self, ty: Option<ast::Ty>) -> Ty {
-        let ident = match ty.and_then(|ty| ty.path(self.tree)?.top_level_name(self.tree)) {
-            Some(ident) => ident,
-            None => return Ty::Unknown,
-        };
+        let (ty, idents) = lower_ty(ty, self.tree, self.interner);
+        for ident in idents {
+            let name = Name(self.interner.intern(ident.text(self.tree)));
+            let local_name =
+                self.local_scopes.iter().rev().find_map(|scope| scope.get(&name).copied());
+            self.index.tys.insert(ident, local_name.unwrap_or(name));
+        }
 
-        self.index.tys.insert(ident);
+        ty.map_named(&mut |name| {
+            if self.generic_params.contains(&name) {
+                return Ty::Generic(name);
+            }
 
-        let name = Name(self.interner.intern(ident.text(self.tree)));
+            if let Some(self_ty) = &self.self_ty {
+                if self.interner.lookup(name.0) == "Self" {
+                    return self_ty.clone();
+                }
+            }
 
-        if name.0 == Key::s32() {
-            Ty::S32
-        } else if name.0 == Key::string() {
-            Ty::String
-        } else {
-            Ty::Named(name)
-        }
+            let local_name =
+                self.local_scopes.iter().rev().find_map(|scope| scope.get(&name).copied());
+            Ty::Named(local_name.unwrap_or(name))
+        })
     }
 }
 
+// definitions inside blocks and implementations are indexed under the names enclosing them
+// joined by `::`, which can't appear in identifiers, followed by `#n` if the name is reused
+pub fn source_name(name: &str) -> &str {
+    let name = name.rsplit("::").next().unwrap();
+    name.split('#').next().unwrap()
+}
+
+pub(crate) fn lower_ty(
+    ty: Option<ast::Ty>,
+    tree: &SyntaxTree,
+    interner: &mut Interner,
+) -> (Ty, Vec<ast::Ident>) {
+    let mut idents = Vec::new();
+    let ty = lower_ty_inner(ty, tree, interner, &mut idents);
+    (ty, idents)
+}
+
+fn lower_ty_inner(
+    ty: Option<ast::Ty>,
+    tree: &SyntaxTree,
+    interner: &mut Interner,
+    idents: &mut Vec<ast::Ident>,
+) -> Ty {
+    let ty_ast = match ty {
+        Some(ty_ast) => ty_ast,
+        None => return Ty::Unknown,
+    };
+
+    let ty = match ty_ast.tuple(tree) {
+        Some(tuple_ty) => Ty::Tuple(
+            tuple_ty
+                .elements(tree)
+                .map(|element| lower_ty_inner(Some(element), tree, interner, idents))
+                .collect(),
+        ),
+        None => match ty_ast.path(tree).and_then(|path| path.top_level_name(tree)) {
+            Some(ident) => {
+                idents.push(ident);
+                lower_named_ty(ident, tree, interner)
+            }
+            None => return Ty::Unknown,
+        },
+    };
+
+    ty_ast.questions(tree).fold(ty, |ty, _| Ty::Optional(Box::new(ty)))
+}
+
+fn lower_named_ty(ident: ast::Ident, tree: &SyntaxTree, interner: &mut Interner) -> Ty {
+    let name = Name(interner.intern(ident.text(tree)));
+
+    if name.0 == Key::s32() {
+        Ty::S32
+    } else if name.0 == Key::string() {
+        Ty::String
+    } else if name.0 == Key::bool() {
+        Ty::Bool
+    } else {
+        Ty::Named(name)
+    }
+}
+
+// escapes are left as they were written
+pub(crate) fn test_name(string_literal: ast::StringLiteral, tree: &SyntaxTree) -> String {
+    string_literal.components(tree).map(|component| component.text(tree)).collect()
+}
+
 enum IndexDefinitionResult {
     Ok {
         definition: Definition,
@@ -310,6 +859,7 @@ pub struct IndexingDiagnostic {
 #[derive(Debug, Clone, PartialEq)]
 pub enum IndexingDiagnosticKind {
     AlreadyDefined { name: Key },
+    ConflictingImpl { interface: Key, ty: Ty },
 }
 
 impl Index {
@@ -333,9 +883,45 @@ impl Index {
                 }
 
                 Definition::Record(record) => debug_record(&mut s, **name, record, interner),
+
+                Definition::Interface(interface) => {
+                    debug_interface(&mut s, **name, interface, interner)
+                }
             }
         }
 
+        for imp in &self.impls {
+            s.push_str(&format!(
+                "imp {} for {} {{",
+                interner.lookup(imp.interface.0),
+                imp.ty.display(interner)
+            ));
+
+            if !imp.functions.is_empty() {
+                s.push(' ');
+                for (idx, (name, local_name)) in imp.functions.iter().enumerate() {
+                    if idx != 0 {
+                        s.push_str(", ");
+                    }
+                    s.push_str(&format!(
+                        "{} = {}",
+                        interner.lookup(name.0),
+                        interner.lookup(local_name.0)
+                    ));
+                }
+                s.push(' ');
+            }
+
+            s.push_str("};\n");
+        }
+
+        let mut tests: Vec<_> = self.tests.keys().collect();
+        tests.sort_unstable();
+
+        for name in tests {
+            s.push_str(&format!("test \"{}\";\n", interner.lookup(name.0)));
+        }
+
         return s;
 
         fn debug_docs(s: &mut String, docs: &Docs) {
@@ -353,8 +939,31 @@ impl Index {
         }
 
         fn debug_function(s: &mut String, name: Name, function: &Function, interner: &Interner) {
+            if function.is_extern {
+                s.push_str("ext ");
+            }
+
             s.push_str(&format!("fnc {}", interner.lookup(name.0)));
 
+            if !function.generic_params.is_empty() {
+                s.push('<');
+
+                for (idx, generic_param) in function.generic_params.iter().enumerate() {
+                    if idx != 0 {
+                        s.push_str(", ");
+                    }
+
+                    s.push_str(interner.lookup(generic_param.name.0));
+
+                    for (idx, bound) in generic_param.bounds.iter().enumerate() {
+                        s.push_str(if idx == 0 { ": " } else { " + " });
+                        s.push_str(interner.lookup(bound.0));
+                    }
+                }
+
+                s.push('>');
+            }
+
             if !function.params.is_empty() {
                 s.push('(');
 
@@ -368,6 +977,10 @@ impl Index {
                         param.name.as_ref().map_or("?", |name| interner.lookup(name.0)),
                         param.ty.display(interner)
                     ));
+
+                    if param.has_default {
+                        s.push_str(" = ...");
+                    }
                 }
 
                 s.push(')');
@@ -380,6 +993,23 @@ impl Index {
             s.push_str(";\n");
         }
 
+        fn debug_interface(s: &mut String, name: Name, interface: &Interface, interner: &Interner) {
+            s.push_str(&format!("ifc {} {{", interner.lookup(name.0)));
+
+            if !interface.functions.is_empty() {
+                s.push(' ');
+                for (idx, function) in interface.functions.iter().enumerate() {
+                    if idx != 0 {
+                        s.push_str(", ");
+                    }
+                    s.push_str(interner.lookup(function.0));
+                }
+                s.push(' ');
+            }
+
+            s.push_str("};\n");
+        }
+
         fn debug_record(s: &mut String, name: Name, record: &Record, interner: &Interner) {
             s.push_str(&format!("rec {} {{", interner.lookup(name.0)));
 
@@ -467,6 +1097,230 @@ mod tests {
         );
     }
 
+    #[test]
+    fn function_with_default_param() {
+        check(
+            r#"
+                fnc greet(name: string = "world") -> {};
+            "#,
+            expect![[r#"
+                fnc greet(name: string = ...);
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn function_with_optional_tys() {
+        check(
+            r#"
+                fnc find(haystack: string, needle: string?): s32?? -> 0;
+            "#,
+            expect![[r#"
+                fnc find(haystack: string, needle: string?): s32??;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn function_with_tuple_tys() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                fnc split(pair: (s32, (Point, string?))?): (s32,) -> (0,);
+            "#,
+            expect![[r#"
+                rec Point { x: s32, y: s32 };
+                fnc split(pair: (s32, (Point, string?))?): (s32,);
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn extern_function() {
+        check(
+            r#"
+                ext fnc print(n: s32);
+            "#,
+            expect![[r#"
+                ext fnc print(n: s32);
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tests() {
+        check(
+            r#"
+                fnc add(x: s32, y: s32): s32 -> x + y;
+                test "adds numbers" -> {};
+                test "add" -> {};
+            "#,
+            expect![[r#"
+                fnc add(x: s32, y: s32): s32;
+                test "add";
+                test "adds numbers";
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tests_with_same_name() {
+        check(
+            r#"
+                test "works" -> {};
+                test "works" -> {};
+            "#,
+            expect![[r#"
+                test "works";
+            "#]],
+            |i| [(IndexingDiagnosticKind::AlreadyDefined { name: i.intern("works") }, 58..65)],
+        );
+    }
+
+    #[test]
+    fn local_definitions() {
+        check(
+            r#"
+                fnc outer(n: s32): s32 -> {
+                    rec Pair { a: s32, b: s32 };
+                    fnc sum(p: Pair): s32 -> {
+                        fnc id(n: s32): s32 -> n;
+                        id 0
+                    };
+                    n
+                };
+                test "local" -> { fnc helper -> {}; };
+            "#,
+            expect![[r#"
+                fnc outer(n: s32): s32;
+                rec outer::Pair { a: s32, b: s32 };
+                fnc outer::sum(p: outer::Pair): s32;
+                fnc outer::sum::id(n: s32): s32;
+                fnc local::helper;
+                test "local";
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn local_definitions_with_same_name_in_different_blocks() {
+        check(
+            r#"
+                fnc f -> {
+                    { fnc g -> {}; };
+                    { fnc g -> {}; };
+                };
+            "#,
+            expect![[r##"
+                fnc f;
+                fnc f::g;
+                fnc f::g#2;
+            "##]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn local_definitions_with_same_name_in_same_block() {
+        check(
+            r#"
+                fnc f -> {
+                    fnc g -> {};
+                    fnc g -> {};
+                };
+            "#,
+            expect![[r#"
+                fnc f;
+                fnc f::g;
+            "#]],
+            |i| [(IndexingDiagnosticKind::AlreadyDefined { name: i.intern("g") }, 85..86)],
+        );
+    }
+
+    #[test]
+    fn generic_function() {
+        check(
+            "fnc show<T: Describe + Compare, U>(value: T, other: U?): T -> value;",
+            expect![[r#"
+                fnc show<T: Describe + Compare, U>(value: T, other: U?): T;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn interface() {
+        check(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self): string;
+                    fnc name: string;
+                };
+            "#,
+            expect![[r#"
+                ifc Describe { describe, name };
+                fnc describe<Self: Describe>(value: Self): string;
+                fnc name<Self: Describe>: string;
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn impl_functions_get_names_of_their_own() {
+        check(
+            r#"
+                imp Describe for s32 {
+                    fnc describe(value: Self): string -> "number";
+                };
+                imp Describe for string? {
+                    fnc describe(value: string?): string -> "maybe a string";
+                    fnc describe -> {};
+                };
+            "#,
+            expect![[r#"
+                fnc Describe for s32::describe(value: s32): string;
+                fnc Describe for string?::describe(value: string?): string;
+                imp Describe for s32 { describe = Describe for s32::describe };
+                imp Describe for string? { describe = Describe for string?::describe };
+            "#]],
+            |i| [(IndexingDiagnosticKind::AlreadyDefined { name: i.intern("describe") }, 271..279)],
+        );
+    }
+
+    #[test]
+    fn conflicting_impls() {
+        check(
+            r#"
+                imp Describe for s32 {
+                    fnc describe(value: Self): string -> "number";
+                };
+                imp Describe for s32 {
+                    fnc describe(value: Self): string -> "integer";
+                };
+            "#,
+            expect![[r#"
+                fnc Describe for s32::describe(value: s32): string;
+                imp Describe for s32 { describe = Describe for s32::describe };
+            "#]],
+            |i| {
+                [(
+                    IndexingDiagnosticKind::ConflictingImpl {
+                        interface: i.intern("Describe"),
+                        ty: Ty::S32,
+                    },
+                    146..162,
+                )]
+            },
+        );
+    }
+
     #[test]
     fn multiple_functions() {
         check(
@@ -648,6 +1502,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_docs() {
+        let input =
+            "## Adds one.\n##\n## Never overflows.\nfnc inc(n: s32): s32 -> n + 1;\nfnc nil -> {};";
+        let mut interner = Interner::default();
+        let tokens = lexer::lex(input);
+        let tree = parser::parse_source_file(&tokens, input).into_syntax_tree();
+        let root = ast::Root::cast(tree.root(), &tree).unwrap();
+        let (index, _) = index(root, &tree, &mut interner);
+
+        let inc = index.docs(Name(interner.intern("inc"))).unwrap();
+        assert_eq!(inc.paras, ["Adds one.", "Never overflows."]);
+        assert!(index.docs(Name(interner.intern("nil"))).is_none());
+    }
+
     #[test]
     fn join_and_trim_lines_in_docs() {
         check(
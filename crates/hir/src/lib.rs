@@ -1,12 +1,16 @@
 mod body;
 mod index;
+mod manifest;
 mod nameres;
+mod prelude;
 mod project;
 mod world_index;
 
 pub use self::body::*;
 pub use self::index::*;
+pub use self::manifest::*;
 pub use self::nameres::*;
+pub use self::prelude::*;
 pub use self::project::*;
 pub use self::world_index::*;
 
@@ -22,23 +26,45 @@ pub struct Fqn {
     pub name: Name,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Ty {
     Unknown,
     S32,
     String,
+    Bool,
     Named(Name),
     Unit,
+    Optional(Box<Ty>),
+    Generic(Name),
+    Tuple(Vec<Ty>),
 }
 
 impl Ty {
-    pub fn display(self, interner: &Interner) -> &str {
+    pub fn display(&self, interner: &Interner) -> String {
         match self {
-            Self::Unknown => "?",
-            Self::S32 => "s32",
-            Self::String => "string",
-            Self::Named(n) => interner.lookup(n.0),
-            Self::Unit => "unit",
+            Self::Unknown => "?".to_string(),
+            Self::S32 => "s32".to_string(),
+            Self::String => "string".to_string(),
+            Self::Bool => "bool".to_string(),
+            Self::Named(n) => interner.lookup(n.0).to_string(),
+            Self::Unit => "unit".to_string(),
+            Self::Optional(ty) => format!("{}?", ty.display(interner)),
+            Self::Generic(n) => interner.lookup(n.0).to_string(),
+            // `(s32,)` has a trailing comma so it isn’t mistaken for `s32`
+            Self::Tuple(tys) if tys.len() == 1 => format!("({},)", tys[0].display(interner)),
+            Self::Tuple(tys) => {
+                let tys: Vec<_> = tys.iter().map(|ty| ty.display(interner)).collect();
+                format!("({})", tys.join(", "))
+            }
+        }
+    }
+
+    pub(crate) fn map_named(self, f: &mut impl FnMut(Name) -> Ty) -> Self {
+        match self {
+            Self::Named(name) => f(name),
+            Self::Optional(ty) => Self::Optional(Box::new(ty.map_named(f))),
+            Self::Tuple(tys) => Self::Tuple(tys.into_iter().map(|ty| ty.map_named(f)).collect()),
+            _ => self,
         }
     }
 }
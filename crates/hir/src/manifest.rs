@@ -0,0 +1,275 @@
+use ast::validation::ValidationDiagnosticKind;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    pub name: String,
+    pub source_root: PathBuf,
+    pub entry_point: EntryPoint,
+    pub lints: Lints,
+    pub options: CompilerOptions,
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryPoint {
+    pub module: String,
+    pub function: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Lints(FxHashMap<ValidationDiagnosticKind, LintLevel>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompilerOptions {
+    pub memory_pages: u32,
+}
+
+impl Default for CompilerOptions {
+    fn default() -> Self {
+        Self { memory_pages: 1 }
+    }
+}
+
+impl Lints {
+    pub fn level(&self, kind: ValidationDiagnosticKind) -> LintLevel {
+        self.0.get(&kind).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    pub fn set_level(&mut self, kind: ValidationDiagnosticKind, level: LintLevel) {
+        self.0.insert(kind, level);
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("malformed manifest: {0}")]
+    Malformed(#[from] toml::de::Error),
+
+    #[error("invalid entry point `{0}`; expected `module.function`")]
+    InvalidEntryPoint(String),
+
+    #[error("unknown lint `{0}`")]
+    UnknownLint(String),
+
+    #[error("invalid dependency name `{0}`; expected an identifier")]
+    InvalidDependencyName(String),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    package: RawPackage,
+    #[serde(default)]
+    lints: FxHashMap<String, LintLevel>,
+    #[serde(default)]
+    options: CompilerOptions,
+    #[serde(default)]
+    dependencies: BTreeMap<String, RawDependency>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawDependency {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPackage {
+    name: String,
+    #[serde(default = "default_source_root")]
+    source: PathBuf,
+    #[serde(default = "default_entry_point")]
+    entry: String,
+}
+
+fn default_source_root() -> PathBuf {
+    PathBuf::from(".")
+}
+
+fn default_entry_point() -> String {
+    "main.main".to_string()
+}
+
+impl Manifest {
+    pub fn parse(text: &str) -> Result<Self, ManifestError> {
+        let RawManifest { package, lints, options, dependencies } = toml::from_str(text)?;
+
+        let entry_point = match package.entry.split_once('.') {
+            Some((module, function)) if is_ident(module) && is_ident(function) => {
+                EntryPoint { module: module.to_string(), function: function.to_string() }
+            }
+            _ => return Err(ManifestError::InvalidEntryPoint(package.entry)),
+        };
+
+        let lints = lints
+            .into_iter()
+            .map(|(name, level)| match ValidationDiagnosticKind::from_lint_name(&name) {
+                Some(kind) => Ok((kind, level)),
+                None => Err(ManifestError::UnknownLint(name)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        let dependencies = dependencies
+            .into_iter()
+            .map(|(name, RawDependency { path })| {
+                if is_ident(&name) {
+                    Ok(Dependency { name, path })
+                } else {
+                    Err(ManifestError::InvalidDependencyName(name))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name: package.name,
+            source_root: package.source,
+            entry_point,
+            lints: Lints(lints),
+            options,
+            dependencies,
+        })
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        Some(_) | None => return false,
+    }
+
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimal() {
+        let manifest = Manifest::parse(
+            r#"
+                [package]
+                name = "hello"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.name, "hello");
+        assert_eq!(manifest.source_root, PathBuf::from("."));
+        assert_eq!(
+            manifest.entry_point,
+            EntryPoint { module: "main".to_string(), function: "main".to_string() }
+        );
+        assert_eq!(manifest.lints.level(ValidationDiagnosticKind::UnneededParens), LintLevel::Warn);
+        assert_eq!(manifest.options, CompilerOptions::default());
+        assert_eq!(manifest.dependencies, Vec::new());
+    }
+
+    #[test]
+    fn everything() {
+        let manifest = Manifest::parse(
+            r#"
+                [package]
+                name = "server"
+                source = "src"
+                entry = "app.start"
+
+                [lints]
+                unneeded_parens = "deny"
+
+                [options]
+                memory_pages = 4
+
+                [dependencies]
+                utils = { path = "../utils" }
+                http = { path = "vendor/http" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.name, "server");
+        assert_eq!(manifest.source_root, PathBuf::from("src"));
+        assert_eq!(
+            manifest.entry_point,
+            EntryPoint { module: "app".to_string(), function: "start".to_string() }
+        );
+        assert_eq!(manifest.lints.level(ValidationDiagnosticKind::UnneededParens), LintLevel::Deny);
+        assert_eq!(manifest.options, CompilerOptions { memory_pages: 4 });
+        assert_eq!(
+            manifest.dependencies,
+            vec![
+                Dependency { name: "http".to_string(), path: PathBuf::from("vendor/http") },
+                Dependency { name: "utils".to_string(), path: PathBuf::from("../utils") },
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_package_name() {
+        let result = Manifest::parse("[package]\n");
+        assert!(matches!(result, Err(ManifestError::Malformed(_))));
+    }
+
+    #[test]
+    fn entry_point_without_module() {
+        let result = Manifest::parse(
+            r#"
+                [package]
+                name = "hello"
+                entry = "main"
+            "#,
+        );
+        assert!(matches!(result, Err(ManifestError::InvalidEntryPoint(e)) if e == "main"));
+    }
+
+    #[test]
+    fn unknown_lint() {
+        let result = Manifest::parse(
+            r#"
+                [package]
+                name = "hello"
+
+                [lints]
+                unused_variables = "allow"
+            "#,
+        );
+        assert!(matches!(result, Err(ManifestError::UnknownLint(l)) if l == "unused_variables"));
+    }
+
+    #[test]
+    fn dependency_with_invalid_name() {
+        let result = Manifest::parse(
+            r#"
+                [package]
+                name = "hello"
+
+                [dependencies]
+                "my-utils" = { path = "../my-utils" }
+            "#,
+        );
+        assert!(matches!(result, Err(ManifestError::InvalidDependencyName(n)) if n == "my-utils"));
+    }
+}
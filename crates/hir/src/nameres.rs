@@ -1,7 +1,7 @@
 use crate::{Definition, Fqn, GetDefinitionError, Index, Name, WorldIndex};
 use text_size::TextRange;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Path {
     ThisModule(Name),
     OtherModule(Fqn),
@@ -0,0 +1,8 @@
+/// The name of the standard library module,
+/// which is implicitly part of every project.
+pub const STD_MODULE_NAME: &str = "std";
+
+/// The source code of the standard library module.
+///
+/// Functions declared with `ext` in it are provided by `eval`.
+pub const STD_SOURCE: &str = include_str!("std.gb");
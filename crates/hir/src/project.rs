@@ -1,18 +1,206 @@
-use crate::Name;
+use crate::{
+    CompilerOptions, Fqn, Lints, Manifest, ManifestError, Name, STD_MODULE_NAME, STD_SOURCE,
+};
 use interner::{Interner, Key};
 use rustc_hash::FxHashMap;
-use std::io;
 use std::path::{Path, PathBuf};
+use std::{fs, io};
+use thiserror::Error;
+
+pub const MANIFEST_FILE_NAME: &str = "gb.toml";
 
 pub struct Project {
-    modules: FxHashMap<Key, PathBuf>,
+    root: PathBuf,
+    manifest: Manifest,
+    entry_point: Fqn,
+    modules: FxHashMap<Key, Module>,
+}
+
+/// A source file belonging either to the project itself
+/// or to one of its (possibly transitive) dependencies.
+///
+/// Modules of dependencies are named `package.module`.
+#[derive(Debug)]
+pub struct Module {
+    pub name: Name,
+    pub package: Option<Name>,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Error)]
+pub enum ProjectError {
+    #[error("could not find `{MANIFEST_FILE_NAME}` in `{}` or any parent directory", .0.display())]
+    NoManifest(PathBuf),
+
+    #[error("could not read project: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("`{}`: {error}", .path.display())]
+    Manifest { path: PathBuf, error: ManifestError },
+
+    #[error("`{}`: `{STD_MODULE_NAME}` is reserved for the standard library", .0.display())]
+    ReservedModuleName(PathBuf),
+
+    #[error("could not find dependency `{name}` at `{}`", .path.display())]
+    DependencyNotFound { name: String, path: PathBuf },
+
+    #[error("cyclic dependency: {}", .0.join(" -> "))]
+    DependencyCycle(Vec<String>),
+
+    #[error("dependency `{name}` refers to both `{}` and `{}`", .first.display(), .second.display())]
+    ConflictingDependency { name: String, first: PathBuf, second: PathBuf },
 }
 
 impl Project {
-    pub fn new(root: &Path, interner: &mut Interner) -> io::Result<Self> {
-        let mut modules = FxHashMap::default();
+    pub fn discover(path: &Path, interner: &mut Interner) -> Result<Self, ProjectError> {
+        for dir in path.ancestors() {
+            if dir.join(MANIFEST_FILE_NAME).is_file() {
+                return Self::new(dir, interner);
+            }
+        }
+
+        Err(ProjectError::NoManifest(path.to_path_buf()))
+    }
+
+    pub fn new(root: &Path, interner: &mut Interner) -> Result<Self, ProjectError> {
+        let manifest = read_manifest(root)?;
+
+        let entry_point = Fqn {
+            module: Name(interner.intern(&manifest.entry_point.module)),
+            name: Name(interner.intern(&manifest.entry_point.function)),
+        };
+
+        let mut loader = Loader {
+            interner,
+            modules: FxHashMap::default(),
+            packages: FxHashMap::default(),
+            stack: vec![(manifest.name.clone(), root.canonicalize()?)],
+        };
+
+        loader.load_std();
+        loader.load_modules(&root.join(&manifest.source_root), None)?;
+        loader.load_dependencies(root, &manifest)?;
+
+        let modules = loader.modules;
 
-        for entry in root.read_dir()? {
+        Ok(Self { root: root.to_path_buf(), manifest, entry_point, modules })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    pub fn entry_point(&self) -> Fqn {
+        self.entry_point
+    }
+
+    pub fn lints(&self) -> &Lints {
+        &self.manifest.lints
+    }
+
+    pub fn options(&self) -> &CompilerOptions {
+        &self.manifest.options
+    }
+
+    pub fn is_module(&self, path: &Path) -> bool {
+        self.modules.values().any(|m| m.path == path)
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = &Module> {
+        self.modules.values()
+    }
+
+    pub fn module_path(&self, name: Name) -> Option<&Path> {
+        self.modules.get(&name.0).map(|m| m.path.as_path())
+    }
+}
+
+fn read_manifest(root: &Path) -> Result<Manifest, ProjectError> {
+    let manifest_path = root.join(MANIFEST_FILE_NAME);
+    let manifest = fs::read_to_string(&manifest_path)?;
+    Manifest::parse(&manifest)
+        .map_err(|error| ProjectError::Manifest { path: manifest_path, error })
+}
+
+struct Loader<'a> {
+    interner: &'a mut Interner,
+    modules: FxHashMap<Key, Module>,
+    packages: FxHashMap<String, PathBuf>,
+    stack: Vec<(String, PathBuf)>,
+}
+
+impl Loader<'_> {
+    fn load_std(&mut self) {
+        // the standard library is compiled into the binary and analyzed from there;
+        // we only write it out so editors have a file to navigate to,
+        // which is why failing to do so is fine
+        let dir = std::env::temp_dir().join(format!("gingerbread-{}", env!("CARGO_PKG_VERSION")));
+        let path = dir.join("std.gb");
+
+        if fs::read_to_string(&path).ok().as_deref() != Some(STD_SOURCE) {
+            let _ = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, STD_SOURCE));
+        }
+
+        let name = self.interner.intern(STD_MODULE_NAME);
+        self.modules.insert(name, Module { name: Name(name), package: None, path });
+    }
+
+    fn load_dependencies(&mut self, root: &Path, manifest: &Manifest) -> Result<(), ProjectError> {
+        for dependency in &manifest.dependencies {
+            let dependency_root = root.join(&dependency.path);
+            let canonical_root =
+                dependency_root.canonicalize().map_err(|_| ProjectError::DependencyNotFound {
+                    name: dependency.name.clone(),
+                    path: dependency_root.clone(),
+                })?;
+
+            if let Some(idx) = self.stack.iter().position(|(_, root)| *root == canonical_root) {
+                let mut cycle: Vec<_> =
+                    self.stack[idx..].iter().map(|(name, _)| name.clone()).collect();
+                cycle.push(dependency.name.clone());
+                return Err(ProjectError::DependencyCycle(cycle));
+            }
+
+            if let Some(first) = self.packages.get(&dependency.name) {
+                if *first == canonical_root {
+                    continue;
+                }
+
+                return Err(ProjectError::ConflictingDependency {
+                    name: dependency.name.clone(),
+                    first: first.clone(),
+                    second: canonical_root,
+                });
+            }
+
+            let dependency_manifest = read_manifest(&dependency_root)?;
+            let package = Name(self.interner.intern(&dependency.name));
+
+            self.packages.insert(dependency.name.clone(), canonical_root.clone());
+            self.stack.push((dependency.name.clone(), canonical_root));
+
+            self.load_modules(
+                &dependency_root.join(&dependency_manifest.source_root),
+                Some(package),
+            )?;
+            self.load_dependencies(&dependency_root, &dependency_manifest)?;
+
+            self.stack.pop();
+        }
+
+        Ok(())
+    }
+
+    fn load_modules(
+        &mut self,
+        source_root: &Path,
+        package: Option<Name>,
+    ) -> Result<(), ProjectError> {
+        for entry in source_root.read_dir()? {
             let entry = entry?;
             let metadata = entry.metadata()?;
 
@@ -31,21 +219,100 @@ impl Project {
                 Some(_) | None => continue,
             };
 
-            modules.insert(interner.intern(module_name), entry.path());
+            if module_name == STD_MODULE_NAME {
+                return Err(ProjectError::ReservedModuleName(entry.path()));
+            }
+
+            let name = match package {
+                Some(package) => {
+                    let package = self.interner.lookup(package.0);
+                    self.interner.intern(&format!("{package}.{module_name}"))
+                }
+                None => self.interner.intern(module_name),
+            };
+
+            self.modules.insert(name, Module { name: Name(name), package, path: entry.path() });
         }
 
-        Ok(Self { modules })
+        Ok(())
     }
+}
 
-    pub fn is_module(&self, path: &Path) -> bool {
-        self.modules.values().any(|p| p == path)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_dependencies() {
+        let root = utils::create_fixture(
+            "load_dependencies",
+            &[
+                (
+                    "app/gb.toml",
+                    "[package]\nname = \"app\"\n[dependencies]\nutils = { path = \"../utils\" }\n",
+                ),
+                ("app/main.gb", ""),
+                ("utils/gb.toml", "[package]\nname = \"utils\"\nsource = \"src\"\n"),
+                ("utils/src/strings.gb", ""),
+                ("utils/src/math.gb", ""),
+            ],
+        );
+
+        let mut interner = Interner::default();
+        let project = Project::new(&root.join("app"), &mut interner).unwrap();
+
+        let mut modules: Vec<_> = project
+            .modules()
+            .map(|m| (interner.lookup(m.name.0), m.package.map(|p| interner.lookup(p.0))))
+            .collect();
+        modules.sort_unstable();
+
+        assert_eq!(
+            modules,
+            [
+                ("main", None),
+                ("std", None),
+                ("utils.math", Some("utils")),
+                ("utils.strings", Some("utils")),
+            ]
+        );
     }
 
-    pub fn modules(&self) -> impl Iterator<Item = &Path> {
-        self.modules.values().map(|p| p.as_path())
+    #[test]
+    fn dependency_cycle() {
+        let root = utils::create_fixture(
+            "dependency_cycle",
+            &[
+                ("a/gb.toml", "[package]\nname = \"a\"\n[dependencies]\nb = { path = \"../b\" }\n"),
+                ("b/gb.toml", "[package]\nname = \"b\"\n[dependencies]\na = { path = \"../a\" }\n"),
+            ],
+        );
+
+        let result = Project::new(&root.join("a"), &mut Interner::default());
+        assert!(matches!(result, Err(ProjectError::DependencyCycle(c)) if c == ["a", "b", "a"]));
     }
 
-    pub fn module_path(&self, name: Name) -> Option<&Path> {
-        self.modules.get(&name.0).map(|p| p.as_path())
+    #[test]
+    fn module_named_std() {
+        let root = utils::create_fixture(
+            "module_named_std",
+            &[("a/gb.toml", "[package]\nname = \"a\"\n"), ("a/std.gb", "")],
+        );
+
+        let result = Project::new(&root.join("a"), &mut Interner::default());
+        assert!(matches!(result, Err(ProjectError::ReservedModuleName(_))));
+    }
+
+    #[test]
+    fn missing_dependency() {
+        let root = utils::create_fixture(
+            "missing_dependency",
+            &[("a/gb.toml", "[package]\nname = \"a\"\n[dependencies]\nb = { path = \"../b\" }\n")],
+        );
+
+        let result = Project::new(&root.join("a"), &mut Interner::default());
+        assert!(
+            matches!(result, Err(ProjectError::DependencyNotFound { name, .. }) if name == "b")
+        );
     }
 }
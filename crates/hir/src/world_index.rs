@@ -15,6 +15,10 @@ impl WorldIndex {
         }
     }
 
+    pub fn module_index(&self, module: Name) -> &Index {
+        &self.0[&module]
+    }
+
     pub fn range_info(&self, fqn: Fqn) -> &RangeInfo {
         &self.0[&fqn.module].range_info[&fqn.name]
     }
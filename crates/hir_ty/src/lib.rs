@@ -8,15 +8,21 @@ pub struct InferenceResult {
     signatures: FxHashMap<hir::Name, Signature>,
     expr_tys: ArenaMap<Id<hir::Expr>, ResolvedTy>,
     local_tys: ArenaMap<Id<hir::LocalDef>, ResolvedTy>,
+    wrapped_in_optional: ArenaMap<Id<hir::Expr>, ()>,
+    generic_args: ArenaMap<Id<hir::Expr>, Vec<ResolvedTy>>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ResolvedTy {
     Unknown,
     S32,
     String,
-    Named(hir::Fqn),
+    Bool,
+    Named(hir::Path),
     Unit,
+    Optional(Box<ResolvedTy>),
+    Generic(hir::Name),
+    Tuple(Vec<ResolvedTy>),
 }
 
 impl std::ops::Index<Id<hir::Expr>> for InferenceResult {
@@ -37,6 +43,8 @@ impl std::ops::Index<Id<hir::LocalDef>> for InferenceResult {
 
 #[derive(Clone)]
 struct Signature {
+    generic_params: Vec<(hir::Name, Vec<hir::Path>)>,
+    optional_generic_params: Vec<hir::Name>,
     return_ty: ResolvedTy,
     param_tys: Vec<ResolvedTy>,
 }
@@ -51,6 +59,22 @@ pub struct TyDiagnostic {
 pub enum TyDiagnosticKind {
     Mismatch { expected: ResolvedTy, found: ResolvedTy },
     Undefined { name: Key },
+    UnknownField { record: Key, field: Key },
+    MissingField { record: Key, field: Key },
+    TryOnNonOptional { found: ResolvedTy },
+    TryInNonOptionalFunction,
+    TryInContract,
+    NotAnInterface { name: Key },
+    InterfaceAsTy { name: Key },
+    UninferredGeneric { name: Key },
+    UnsatisfiedBound { ty: ResolvedTy, interface: Key },
+    MissingImplFunction { interface: Key, function: Key },
+    UnknownImplFunction { interface: Key, function: Key },
+    MismatchedImplParamCount { function: Key, expected: u32, found: u32 },
+    NotATuple { found: ResolvedTy },
+    TupleFieldOutOfRange { tuple: ResolvedTy, idx: u32 },
+    MismatchedTuplePatternLength { expected: u32, found: u32 },
+    NestedOptional { ty: ResolvedTy },
 }
 
 pub fn infer_all(
@@ -60,6 +84,8 @@ pub fn infer_all(
 ) -> (InferenceResult, Vec<TyDiagnostic>) {
     let mut expr_tys = ArenaMap::default();
     let mut local_tys = ArenaMap::default();
+    let mut wrapped_in_optional = ArenaMap::default();
+    let mut generic_args = ArenaMap::default();
     let mut diagnostics = Vec::new();
     let mut signatures = FxHashMap::default();
 
@@ -72,21 +98,73 @@ pub fn infer_all(
             &mut diagnostics,
         );
 
+        infer_param_defaults(
+            name,
+            &signature,
+            &mut expr_tys,
+            &mut local_tys,
+            &mut wrapped_in_optional,
+            &mut generic_args,
+            bodies,
+            index,
+            world_index,
+            &mut diagnostics,
+        );
+
+        // extern functions and those declared by interfaces have no body to infer
+        if function.has_body() {
+            FunctionInferenceCtx {
+                expr_tys: &mut expr_tys,
+                local_tys: &mut local_tys,
+                wrapped_in_optional: &mut wrapped_in_optional,
+                generic_args: &mut generic_args,
+                generic_params: &signature.generic_params,
+                param_tys: &signature.param_tys,
+                return_ty: Some(&signature.return_ty),
+                in_contract: false,
+                bodies,
+                index,
+                world_index,
+                diagnostics: &mut diagnostics,
+            }
+            .finish(name, &signature);
+        }
+
+        signatures.insert(name, signature);
+    }
+
+    // tests take no arguments and return nothing
+    let test_signature = Signature {
+        generic_params: Vec::new(),
+        optional_generic_params: Vec::new(),
+        return_ty: ResolvedTy::Unit,
+        param_tys: Vec::new(),
+    };
+
+    for (name, _) in index.tests() {
         FunctionInferenceCtx {
             expr_tys: &mut expr_tys,
             local_tys: &mut local_tys,
-            param_tys: &signature.param_tys,
+            wrapped_in_optional: &mut wrapped_in_optional,
+            generic_args: &mut generic_args,
+            generic_params: &test_signature.generic_params,
+            param_tys: &test_signature.param_tys,
+            return_ty: Some(&test_signature.return_ty),
+            in_contract: false,
             bodies,
             index,
             world_index,
             diagnostics: &mut diagnostics,
         }
-        .finish(name, &signature);
+        .finish_test(name);
+    }
 
-        signatures.insert(name, signature);
+    for imp in index.impls() {
+        check_impl(imp, index, world_index, &mut diagnostics);
     }
 
-    let mut result = InferenceResult { signatures, expr_tys, local_tys };
+    let mut result =
+        InferenceResult { signatures, expr_tys, local_tys, wrapped_in_optional, generic_args };
     result.shrink_to_fit();
 
     (result, diagnostics)
@@ -100,11 +178,15 @@ pub fn infer(
 ) -> (InferenceResult, Vec<TyDiagnostic>) {
     let function = match index.get_definition(function_name) {
         Some(hir::Definition::Function(f)) => f,
-        Some(hir::Definition::Record(_)) | None => panic!("passed non-function name"),
+        Some(hir::Definition::Record(_) | hir::Definition::Interface(_)) | None => {
+            panic!("passed non-function name")
+        }
     };
 
     let mut expr_tys = ArenaMap::default();
     let mut local_tys = ArenaMap::default();
+    let mut wrapped_in_optional = ArenaMap::default();
+    let mut generic_args = ArenaMap::default();
     let mut diagnostics = Vec::new();
 
     let signature = get_signature(
@@ -115,30 +197,220 @@ pub fn infer(
         &mut diagnostics,
     );
 
-    FunctionInferenceCtx {
-        expr_tys: &mut expr_tys,
-        local_tys: &mut local_tys,
-        param_tys: &signature.param_tys,
+    infer_param_defaults(
+        function_name,
+        &signature,
+        &mut expr_tys,
+        &mut local_tys,
+        &mut wrapped_in_optional,
+        &mut generic_args,
         bodies,
         index,
         world_index,
-        diagnostics: &mut diagnostics,
+        &mut diagnostics,
+    );
+
+    if function.has_body() {
+        FunctionInferenceCtx {
+            expr_tys: &mut expr_tys,
+            local_tys: &mut local_tys,
+            wrapped_in_optional: &mut wrapped_in_optional,
+            generic_args: &mut generic_args,
+            generic_params: &signature.generic_params,
+            param_tys: &signature.param_tys,
+            return_ty: Some(&signature.return_ty),
+            in_contract: false,
+            bodies,
+            index,
+            world_index,
+            diagnostics: &mut diagnostics,
+        }
+        .finish(function_name, &signature);
     }
-    .finish(function_name, &signature);
 
     let mut signatures = FxHashMap::default();
     signatures.insert(function_name, signature);
 
-    let mut result = InferenceResult { signatures, expr_tys, local_tys };
+    let mut result =
+        InferenceResult { signatures, expr_tys, local_tys, wrapped_in_optional, generic_args };
     result.shrink_to_fit();
 
     (result, diagnostics)
 }
 
+#[allow(clippy::too_many_arguments)]
+fn infer_param_defaults(
+    function_name: hir::Name,
+    signature: &Signature,
+    expr_tys: &mut ArenaMap<Id<hir::Expr>, ResolvedTy>,
+    local_tys: &mut ArenaMap<Id<hir::LocalDef>, ResolvedTy>,
+    wrapped_in_optional: &mut ArenaMap<Id<hir::Expr>, ()>,
+    generic_args: &mut ArenaMap<Id<hir::Expr>, Vec<ResolvedTy>>,
+    bodies: &hir::Bodies,
+    index: &hir::Index,
+    world_index: &hir::WorldIndex,
+    diagnostics: &mut Vec<TyDiagnostic>,
+) {
+    for (idx, param_ty) in signature.param_tys.iter().enumerate() {
+        let default_value = match bodies.param_default(function_name, idx as u32) {
+            Some(default_value) => default_value,
+            None => continue,
+        };
+
+        // default values can’t refer to any parameters,
+        // and aren’t part of a function they could return early from
+        let mut ctx = FunctionInferenceCtx {
+            expr_tys,
+            local_tys,
+            wrapped_in_optional,
+            generic_args,
+            generic_params: &[],
+            param_tys: &[],
+            return_ty: None,
+            in_contract: false,
+            bodies,
+            index,
+            world_index,
+            diagnostics,
+        };
+        let default_value_ty = ctx.infer_expr(default_value);
+        ctx.expect_match(default_value_ty, param_ty.clone(), default_value);
+    }
+}
+
+fn check_impl(
+    imp: &hir::Impl,
+    index: &hir::Index,
+    world_index: &hir::WorldIndex,
+    diagnostics: &mut Vec<TyDiagnostic>,
+) {
+    let interface = match index.get_definition(imp.interface) {
+        Some(hir::Definition::Interface(interface)) => interface,
+        Some(_) => {
+            diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::NotAnInterface { name: imp.interface.0 },
+                range: imp.interface_range,
+            });
+            return;
+        }
+        None => {
+            diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::Undefined { name: imp.interface.0 },
+                range: imp.interface_range,
+            });
+            return;
+        }
+    };
+
+    let ty = resolve_ty(&imp.ty, imp.ty_range, index, diagnostics);
+
+    for function_name in &interface.functions {
+        if !imp.functions.iter().any(|(name, _)| name == function_name) {
+            diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::MissingImplFunction {
+                    interface: imp.interface.0,
+                    function: function_name.0,
+                },
+                range: imp.interface_range,
+            });
+        }
+    }
+
+    // the signatures themselves are checked where they are inferred
+    let mut signature_diagnostics = Vec::new();
+
+    for (name, local_name) in &imp.functions {
+        let range_info = index.range_info(*local_name);
+
+        if !interface.functions.contains(name) {
+            diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::UnknownImplFunction {
+                    interface: imp.interface.0,
+                    function: name.0,
+                },
+                range: range_info.name,
+            });
+            continue;
+        }
+
+        let get = |name| match index.get_definition(name) {
+            Some(hir::Definition::Function(function)) => function,
+            _ => unreachable!(),
+        };
+
+        let expected = get_signature(
+            get(*name),
+            hir::Path::ThisModule(*name),
+            index,
+            world_index,
+            &mut signature_diagnostics,
+        );
+        let found = get_signature(
+            get(*local_name),
+            hir::Path::ThisModule(*local_name),
+            index,
+            world_index,
+            &mut signature_diagnostics,
+        );
+
+        // `Self` is always the first generic parameter of a function declared by an interface
+        let mut generic_args = FxHashMap::default();
+        generic_args.insert(expected.generic_params[0].0, ty.clone());
+        for (generic_param, _) in &expected.generic_params[1..] {
+            generic_args.insert(*generic_param, ResolvedTy::Generic(*generic_param));
+        }
+
+        if expected.param_tys.len() != found.param_tys.len() {
+            diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::MismatchedImplParamCount {
+                    function: name.0,
+                    expected: expected.param_tys.len() as u32,
+                    found: found.param_tys.len() as u32,
+                },
+                range: range_info.name,
+            });
+            continue;
+        }
+
+        let (return_ty_range, param_ty_ranges) = match &range_info.tys {
+            hir::TysRangeInfo::Function { return_ty, param_tys, .. } => (return_ty, param_tys),
+            hir::TysRangeInfo::Record { .. } | hir::TysRangeInfo::Interface => unreachable!(),
+        };
+
+        let tys = expected
+            .param_tys
+            .iter()
+            .zip(&found.param_tys)
+            .zip(param_ty_ranges)
+            .chain(std::iter::once(((&expected.return_ty, &found.return_ty), return_ty_range)));
+
+        for ((expected, found), range) in tys {
+            let expected = expected.substitute(&generic_args);
+
+            if expected == ResolvedTy::Unknown
+                || *found == ResolvedTy::Unknown
+                || expected == *found
+            {
+                continue;
+            }
+
+            diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::Mismatch { expected, found: found.clone() },
+                range: range.unwrap_or(range_info.name),
+            });
+        }
+    }
+}
+
 struct FunctionInferenceCtx<'a> {
     expr_tys: &'a mut ArenaMap<Id<hir::Expr>, ResolvedTy>,
     local_tys: &'a mut ArenaMap<Id<hir::LocalDef>, ResolvedTy>,
+    wrapped_in_optional: &'a mut ArenaMap<Id<hir::Expr>, ()>,
+    generic_args: &'a mut ArenaMap<Id<hir::Expr>, Vec<ResolvedTy>>,
+    generic_params: &'a [(hir::Name, Vec<hir::Path>)],
     param_tys: &'a [ResolvedTy],
+    return_ty: Option<&'a ResolvedTy>,
+    in_contract: bool,
     bodies: &'a hir::Bodies,
     index: &'a hir::Index,
     world_index: &'a hir::WorldIndex,
@@ -149,7 +421,22 @@ impl FunctionInferenceCtx<'_> {
     fn finish(mut self, function_name: hir::Name, signature: &Signature) {
         let function_body = self.bodies.function_body(function_name);
         let actual_return_ty = self.infer_expr(function_body);
-        self.expect_match(actual_return_ty, signature.return_ty, function_body);
+        self.expect_match(actual_return_ty, signature.return_ty.clone(), function_body);
+
+        if let Some(contract) = self.bodies.function_contract(function_name) {
+            self.in_contract = true;
+
+            for condition in contract.requires.iter().chain(&contract.ensures) {
+                let condition_ty = self.infer_expr(*condition);
+                self.expect_match(condition_ty, ResolvedTy::Bool, *condition);
+            }
+        }
+    }
+
+    fn finish_test(mut self, test_name: hir::Name) {
+        let test_body = self.bodies.test_body(test_name);
+        let actual_ty = self.infer_expr(test_body);
+        self.expect_match(actual_ty, ResolvedTy::Unit, test_body);
     }
 
     fn infer_statement(&mut self, statement_id: Id<hir::Statement>) {
@@ -159,10 +446,181 @@ impl FunctionInferenceCtx<'_> {
             }
 
             hir::Statement::LocalDef(local_def) => {
-                let ty = self.infer_expr(self.bodies[*local_def].value);
+                let hir::LocalDef { value, ref ty, ty_range, pattern, .. } =
+                    self.bodies[*local_def];
+                let value_ty = self.infer_expr(value);
+
+                let ty = match ty {
+                    Some(annotation) => {
+                        let annotation_ty =
+                            resolve_ty(annotation, ty_range, self.index, self.diagnostics);
+                        self.expect_match(value_ty.clone(), annotation_ty.clone(), value);
+
+                        // the annotation takes precedence over the value,
+                        // unless the annotation itself is broken
+                        if annotation_ty == ResolvedTy::Unknown {
+                            value_ty
+                        } else {
+                            annotation_ty
+                        }
+                    }
+                    None => value_ty,
+                };
+
+                self.local_tys.insert(*local_def, ty.clone());
+
+                if let Some(pattern) = pattern {
+                    self.infer_pattern(pattern, ty);
+                }
+            }
+        }
+    }
+
+    fn infer_pattern(&mut self, pattern: Id<hir::Pattern>, ty: ResolvedTy) {
+        match &self.bodies[pattern] {
+            hir::Pattern::Missing | hir::Pattern::Wildcard => {}
+
+            hir::Pattern::Binding(local_def) => {
+                // the binding’s value reads a field out of the destructured value
+                self.infer_destructured(self.bodies[*local_def].value);
                 self.local_tys.insert(*local_def, ty);
             }
+
+            hir::Pattern::Tuple { elements } => {
+                let tys = match ty {
+                    ResolvedTy::Tuple(tys) => {
+                        if tys.len() != elements.len() {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::MismatchedTuplePatternLength {
+                                    expected: tys.len() as u32,
+                                    found: elements.len() as u32,
+                                },
+                                range: self.bodies.range_for_pattern(pattern),
+                            });
+                        }
+                        tys
+                    }
+                    ResolvedTy::Unknown => Vec::new(),
+                    found => {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::NotATuple { found },
+                            range: self.bodies.range_for_pattern(pattern),
+                        });
+                        Vec::new()
+                    }
+                };
+
+                for (idx, element) in elements.iter().enumerate() {
+                    let ty = tys.get(idx).cloned().unwrap_or(ResolvedTy::Unknown);
+                    self.infer_pattern(*element, ty);
+                }
+            }
+
+            hir::Pattern::Record { record, fields } => {
+                let record = match record {
+                    Some(record) => record.path(),
+                    None => {
+                        for field in fields {
+                            self.infer_pattern(field.pattern, ResolvedTy::Unknown);
+                        }
+                        return;
+                    }
+                };
+
+                let expected = ResolvedTy::Named(record);
+                if ty != ResolvedTy::Unknown && ty != expected {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::Mismatch { expected, found: ty },
+                        range: self.bodies.range_for_pattern(pattern),
+                    });
+                }
+
+                let record_name = match record {
+                    hir::Path::ThisModule(name) => name.0,
+                    hir::Path::OtherModule(fqn) => fqn.name.0,
+                };
+                let record_fields = self.record_fields(record);
+
+                for field in fields {
+                    match record_fields.iter().find(|(name, _)| *name == field.name) {
+                        Some((_, field_ty)) => self.infer_pattern(field.pattern, field_ty.clone()),
+                        None => {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::UnknownField {
+                                    record: record_name,
+                                    field: field.name.0,
+                                },
+                                range: field.range,
+                            });
+                            self.infer_pattern(field.pattern, ResolvedTy::Unknown);
+                        }
+                    }
+                }
+
+                for (name, _) in &record_fields {
+                    if !fields.iter().any(|field| field.name == *name) {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::MissingField {
+                                record: record_name,
+                                field: name.0,
+                            },
+                            range: self.bodies.range_for_pattern(pattern),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn infer_destructured(&mut self, expr: Id<hir::Expr>) -> ResolvedTy {
+        if let Some(ty) = self.expr_tys.get(expr) {
+            return ty.clone();
         }
+
+        let ty = match self.bodies[expr] {
+            hir::Expr::TupleField { base, idx } => match self.infer_destructured(base) {
+                ResolvedTy::Tuple(tys) => tys.get(idx as usize).cloned(),
+                _ => None,
+            },
+            _ => return self.infer_expr(expr),
+        };
+
+        let ty = ty.unwrap_or(ResolvedTy::Unknown);
+        self.expr_tys.insert(expr, ty.clone());
+        ty
+    }
+
+    fn record_fields(&self, record: hir::Path) -> Vec<(hir::Name, ResolvedTy)> {
+        let (index, name) = match record {
+            hir::Path::ThisModule(name) => (self.index, name),
+            hir::Path::OtherModule(fqn) => (self.world_index.module_index(fqn.module), fqn.name),
+        };
+
+        let fields = match index.get_definition(name) {
+            Some(hir::Definition::Record(record)) => &record.fields,
+            _ => return Vec::new(),
+        };
+
+        let field_ty_ranges = match &index.range_info(name).tys {
+            hir::TysRangeInfo::Record { field_tys } => field_tys,
+            hir::TysRangeInfo::Function { .. } | hir::TysRangeInfo::Interface => unreachable!(),
+        };
+
+        // problems with the types of fields are reported where the record is defined
+        let mut diagnostics = Vec::new();
+
+        fields
+            .iter()
+            .zip(field_ty_ranges)
+            .filter_map(|(field, ty_range)| {
+                let ty = resolve_ty(&field.ty, *ty_range, index, &mut diagnostics);
+                let ty = match record {
+                    hir::Path::ThisModule(_) => ty,
+                    hir::Path::OtherModule(fqn) => ty.qualify(fqn.module),
+                };
+                Some((field.name?, ty))
+            })
+            .collect()
     }
 
     fn infer_expr(&mut self, expr: Id<hir::Expr>) -> ResolvedTy {
@@ -170,14 +628,28 @@ impl FunctionInferenceCtx<'_> {
             hir::Expr::Missing => ResolvedTy::Unknown,
             hir::Expr::IntLiteral(_) => ResolvedTy::S32,
             hir::Expr::StringLiteral(_) => ResolvedTy::String,
-            hir::Expr::Binary { lhs, rhs, .. } => {
+            hir::Expr::Binary { lhs, rhs, operator } => {
                 let lhs_ty = self.infer_expr(*lhs);
                 let rhs_ty = self.infer_expr(*rhs);
 
-                self.expect_match(lhs_ty, ResolvedTy::S32, *lhs);
-                self.expect_match(rhs_ty, ResolvedTy::S32, *rhs);
+                // bools can only be compared for equality with other bools
+                let operand_ty = match operator {
+                    hir::BinaryOperator::Eq | hir::BinaryOperator::NotEq
+                        if lhs_ty == ResolvedTy::Bool =>
+                    {
+                        ResolvedTy::Bool
+                    }
+                    _ => ResolvedTy::S32,
+                };
+
+                self.expect_match(lhs_ty, operand_ty.clone(), *lhs);
+                self.expect_match(rhs_ty, operand_ty, *rhs);
 
-                ResolvedTy::S32
+                if operator.is_comparison() {
+                    ResolvedTy::Bool
+                } else {
+                    ResolvedTy::S32
+                }
             }
             hir::Expr::Block { statements, tail_expr, .. } => {
                 for statement in statements {
@@ -189,9 +661,74 @@ impl FunctionInferenceCtx<'_> {
                     None => ResolvedTy::Unit,
                 }
             }
-            hir::Expr::Local(local_def) => self.local_tys[*local_def],
-            hir::Expr::Param { idx } => self.param_tys[*idx as usize],
-            hir::Expr::Call { path, args } => {
+            hir::Expr::Local(local_def) => self.local_tys[*local_def].clone(),
+            hir::Expr::Param { idx } => self.param_tys[*idx as usize].clone(),
+            hir::Expr::None => ResolvedTy::Optional(Box::new(ResolvedTy::Unknown)),
+            hir::Expr::Try { inner } => {
+                let inner_ty = match self.infer_expr(*inner) {
+                    ResolvedTy::Optional(ty) => *ty,
+                    ResolvedTy::Unknown => ResolvedTy::Unknown,
+                    found => {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::TryOnNonOptional { found },
+                            range: self.bodies.range_for_expr(*inner),
+                        });
+                        ResolvedTy::Unknown
+                    }
+                };
+
+                // the absence of a value is returned as is,
+                // so the enclosing function has to return an optional too,
+                // while contracts are checked around the function and can’t return from it
+                match self.return_ty {
+                    _ if self.in_contract => self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::TryInContract,
+                        range: self.bodies.range_for_expr(expr),
+                    }),
+                    Some(ResolvedTy::Optional(_) | ResolvedTy::Unknown) => {}
+                    _ => self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::TryInNonOptionalFunction,
+                        range: self.bodies.range_for_expr(expr),
+                    }),
+                }
+
+                inner_ty
+            }
+            hir::Expr::Field { base, field } => match self.infer_expr(*base) {
+                ResolvedTy::Named(record) => self
+                    .record_fields(record)
+                    .into_iter()
+                    .find(|(name, _)| name == field)
+                    .map_or(ResolvedTy::Unknown, |(_, ty)| ty),
+                _ => ResolvedTy::Unknown,
+            },
+            hir::Expr::Tuple { elements } => ResolvedTy::Tuple(
+                elements.iter().map(|element| self.infer_expr(*element)).collect(),
+            ),
+            hir::Expr::TupleField { base, idx } => match self.infer_expr(*base) {
+                ResolvedTy::Tuple(tys) => match tys.get(*idx as usize) {
+                    Some(ty) => ty.clone(),
+                    None => {
+                        self.diagnostics.push(TyDiagnostic {
+                            kind: TyDiagnosticKind::TupleFieldOutOfRange {
+                                tuple: ResolvedTy::Tuple(tys),
+                                idx: *idx,
+                            },
+                            range: self.bodies.range_for_expr(expr),
+                        });
+                        ResolvedTy::Unknown
+                    }
+                },
+                ResolvedTy::Unknown => ResolvedTy::Unknown,
+                found => {
+                    self.diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::NotATuple { found },
+                        range: self.bodies.range_for_expr(*base),
+                    });
+                    ResolvedTy::Unknown
+                }
+            },
+            hir::Expr::Call { path, args, .. } => {
                 let definition = match *path {
                     hir::PathWithRange::ThisModule { name, .. } => {
                         self.index.get_definition(name).unwrap()
@@ -203,7 +740,8 @@ impl FunctionInferenceCtx<'_> {
 
                 let function = match definition {
                     hir::Definition::Function(f) => f,
-                    hir::Definition::Record(_) => todo!(),
+                    // lowering only produces calls to functions
+                    hir::Definition::Record(_) | hir::Definition::Interface(_) => unreachable!(),
                 };
 
                 let signature = get_signature(
@@ -214,25 +752,162 @@ impl FunctionInferenceCtx<'_> {
                     self.diagnostics,
                 );
 
-                for (idx, arg) in args.iter().enumerate() {
-                    let arg_ty = self.infer_expr(*arg);
-                    self.expect_match(arg_ty, signature.param_tys[idx], *arg);
+                // default values are checked against the parameter type
+                // where they are defined
+                let arg_tys: Vec<_> = args
+                    .iter()
+                    .map(|arg| match self.bodies[*arg] {
+                        hir::Expr::Default { .. } => None,
+                        _ => Some(self.infer_expr(*arg)),
+                    })
+                    .collect();
+
+                let mut generic_args = FxHashMap::default();
+                for (param_ty, arg_ty) in signature.param_tys.iter().zip(&arg_tys) {
+                    if let Some(arg_ty) = arg_ty {
+                        infer_generic_args(param_ty, arg_ty, &mut generic_args);
+                    }
+                }
+
+                for ((arg, arg_ty), param_ty) in args.iter().zip(arg_tys).zip(&signature.param_tys)
+                {
+                    let param_ty = param_ty.substitute(&generic_args);
+
+                    match arg_ty {
+                        Some(arg_ty) => self.expect_match(arg_ty, param_ty, *arg),
+                        None => {
+                            self.expr_tys.insert(*arg, param_ty);
+                        }
+                    }
+                }
+
+                if !signature.generic_params.is_empty() {
+                    for generic_param in &signature.optional_generic_params {
+                        if let Some(ty @ ResolvedTy::Optional(_)) = generic_args.get(generic_param)
+                        {
+                            self.diagnostics.push(TyDiagnostic {
+                                kind: TyDiagnosticKind::NestedOptional {
+                                    ty: ResolvedTy::Optional(Box::new(ty.clone())),
+                                },
+                                range: self.bodies.range_for_expr(expr),
+                            });
+                        }
+                    }
+
+                    let mut instantiation = Vec::new();
+
+                    for (generic_param, bounds) in &signature.generic_params {
+                        let ty = match generic_args.get(generic_param) {
+                            Some(ty) => ty.clone(),
+                            None => {
+                                self.diagnostics.push(TyDiagnostic {
+                                    kind: TyDiagnosticKind::UninferredGeneric {
+                                        name: generic_param.0,
+                                    },
+                                    range: self.bodies.range_for_expr(expr),
+                                });
+                                ResolvedTy::Unknown
+                            }
+                        };
+
+                        for bound in bounds {
+                            self.check_bound(&ty, *bound, expr);
+                        }
+
+                        instantiation.push(ty);
+                    }
+
+                    self.generic_args.insert(expr, instantiation);
                 }
 
-                signature.return_ty
+                signature.return_ty.substitute(&generic_args)
+            }
+            hir::Expr::Default { .. } => unreachable!("default values are inferred by their call"),
+            hir::Expr::Panic { message } => {
+                let message_ty = self.infer_expr(*message);
+                self.expect_match(message_ty, ResolvedTy::String, *message);
+
+                ResolvedTy::Unit
             }
+            hir::Expr::Assert { condition, message } => {
+                let condition_ty = self.infer_expr(*condition);
+                let message_ty = self.infer_expr(*message);
+                self.expect_match(condition_ty, ResolvedTy::Bool, *condition);
+                self.expect_match(message_ty, ResolvedTy::String, *message);
+
+                ResolvedTy::Unit
+            }
+            hir::Expr::ReturnValue => self.return_ty.cloned().unwrap_or(ResolvedTy::Unknown),
         };
 
-        self.expr_tys.insert(expr, ty);
+        self.expr_tys.insert(expr, ty.clone());
 
         ty
     }
 
+    fn check_bound(&mut self, ty: &ResolvedTy, interface: hir::Path, call: Id<hir::Expr>) {
+        let is_satisfied = match ty {
+            ResolvedTy::Unknown => true,
+
+            // generic parameters of the function being inferred
+            // only implement the interfaces they are bounded by
+            ResolvedTy::Generic(name) => {
+                self.generic_params.iter().any(|(generic_param, bounds)| {
+                    generic_param == name && bounds.contains(&interface)
+                })
+            }
+
+            _ => find_impl(interface, ty, self.index, self.world_index).is_some(),
+        };
+
+        if !is_satisfied {
+            let interface = match interface {
+                hir::Path::ThisModule(name) => name,
+                hir::Path::OtherModule(fqn) => fqn.name,
+            };
+
+            self.diagnostics.push(TyDiagnostic {
+                kind: TyDiagnosticKind::UnsatisfiedBound { ty: ty.clone(), interface: interface.0 },
+                range: self.bodies.range_for_expr(call),
+            });
+        }
+    }
+
     fn expect_match(&mut self, found: ResolvedTy, expected: ResolvedTy, expr: Id<hir::Expr>) {
         if found == ResolvedTy::Unknown || expected == ResolvedTy::Unknown {
             return;
         }
 
+        // each element of a tuple written out in place is checked on its own,
+        // so elements can be wrapped in optionals as needed
+        let bodies = self.bodies;
+        if let (
+            ResolvedTy::Tuple(found_tys),
+            ResolvedTy::Tuple(expected_tys),
+            hir::Expr::Tuple { elements },
+        ) = (&found, &expected, &bodies[expr])
+        {
+            if found_tys.len() == expected_tys.len() {
+                for ((found, expected), element) in found_tys.iter().zip(expected_tys).zip(elements)
+                {
+                    self.expect_match(found.clone(), expected.clone(), *element);
+                }
+                return;
+            }
+        }
+
+        if let ResolvedTy::Optional(expected_inner) = &expected {
+            // `none` is an optional of every type
+            if found == ResolvedTy::Optional(Box::new(ResolvedTy::Unknown)) {
+                return;
+            }
+
+            if found == **expected_inner {
+                self.wrapped_in_optional.insert(expr, ());
+                return;
+            }
+        }
+
         if found != expected {
             // if the erroneous expression is a block with a tail expression,
             // attach the error to the tail instead of the whole block
@@ -256,30 +931,152 @@ fn get_signature(
     world_index: &hir::WorldIndex,
     diagnostics: &mut Vec<TyDiagnostic>,
 ) -> Signature {
-    let range_info = match path {
-        hir::Path::ThisModule(name) => index.range_info(name),
-        hir::Path::OtherModule(fqn) => world_index.range_info(fqn),
+    // the types in the signature of a function from another module
+    // are resolved in that module, which reports any problems with them
+    let mut other_module_diagnostics = Vec::new();
+    let (index, range_info, diagnostics) = match path {
+        hir::Path::ThisModule(name) => (index, index.range_info(name), diagnostics),
+        hir::Path::OtherModule(fqn) => (
+            world_index.module_index(fqn.module),
+            world_index.range_info(fqn),
+            &mut other_module_diagnostics,
+        ),
+    };
+
+    let (return_ty_range, param_ty_ranges, bound_ranges) = match &range_info.tys {
+        hir::TysRangeInfo::Function { return_ty, param_tys, bounds } => {
+            (return_ty, param_tys, bounds)
+        }
+        hir::TysRangeInfo::Record { .. } | hir::TysRangeInfo::Interface => unreachable!(),
     };
 
-    let (return_ty_range, param_ty_ranges) = match &range_info.tys {
-        hir::TysRangeInfo::Function { return_ty, param_tys } => (return_ty, param_tys),
-        hir::TysRangeInfo::Record { .. } => unreachable!(),
+    let mut generic_params = Vec::new();
+
+    for (generic_param, ranges) in function.generic_params.iter().zip(bound_ranges) {
+        let mut bounds = Vec::new();
+
+        for (bound, range) in generic_param.bounds.iter().zip(ranges) {
+            let kind = match index.get_definition(*bound) {
+                Some(hir::Definition::Interface(_)) => {
+                    bounds.push(match path {
+                        hir::Path::ThisModule(_) => hir::Path::ThisModule(*bound),
+                        hir::Path::OtherModule(fqn) => {
+                            hir::Path::OtherModule(hir::Fqn { module: fqn.module, name: *bound })
+                        }
+                    });
+                    continue;
+                }
+                Some(_) => TyDiagnosticKind::NotAnInterface { name: bound.0 },
+                None => TyDiagnosticKind::Undefined { name: bound.0 },
+            };
+
+            diagnostics.push(TyDiagnostic { kind, range: *range });
+        }
+
+        generic_params.push((generic_param.name, bounds));
+    }
+
+    let mut resolve = |ty, range| {
+        let ty = resolve_ty(ty, range, index, diagnostics);
+        match path {
+            hir::Path::ThisModule(_) => ty,
+            hir::Path::OtherModule(fqn) => ty.qualify(fqn.module),
+        }
     };
 
-    let return_ty = resolve_ty(function.return_ty, *return_ty_range, index, diagnostics);
+    let return_ty = resolve(&function.return_ty, *return_ty_range);
 
     let param_tys: Vec<_> = function
         .params
         .iter()
         .zip(param_ty_ranges)
-        .map(|(param, ty_range)| resolve_ty(param.ty, *ty_range, index, diagnostics))
+        .map(|(param, ty_range)| resolve(&param.ty, *ty_range))
+        .collect();
+
+    let optional_generic_params = function
+        .generic_params
+        .iter()
+        .filter(|generic_param| generic_param.used_as_optional)
+        .map(|generic_param| generic_param.name)
         .collect();
 
-    Signature { return_ty, param_tys }
+    Signature { generic_params, optional_generic_params, return_ty, param_tys }
+}
+
+fn infer_generic_args(
+    param_ty: &ResolvedTy,
+    arg_ty: &ResolvedTy,
+    generic_args: &mut FxHashMap<hir::Name, ResolvedTy>,
+) {
+    match (param_ty, arg_ty) {
+        (_, ResolvedTy::Unknown) => {}
+        (ResolvedTy::Generic(name), _) => {
+            generic_args.entry(*name).or_insert_with(|| arg_ty.clone());
+        }
+        (ResolvedTy::Optional(param_ty), ResolvedTy::Optional(arg_ty)) => {
+            infer_generic_args(param_ty, arg_ty, generic_args);
+        }
+        // values are wrapped in optionals as needed
+        (ResolvedTy::Optional(param_ty), _) => infer_generic_args(param_ty, arg_ty, generic_args),
+        (ResolvedTy::Tuple(param_tys), ResolvedTy::Tuple(arg_tys))
+            if param_tys.len() == arg_tys.len() =>
+        {
+            for (param_ty, arg_ty) in param_tys.iter().zip(arg_tys) {
+                infer_generic_args(param_ty, arg_ty, generic_args);
+            }
+        }
+        _ => {}
+    }
+}
+
+// implementations are always in the same module as their interface
+fn find_impl<'a>(
+    interface: hir::Path,
+    ty: &ResolvedTy,
+    index: &'a hir::Index,
+    world_index: &'a hir::WorldIndex,
+) -> Option<&'a hir::Impl> {
+    // problems with the implementation are reported where it is defined
+    let mut diagnostics = Vec::new();
+
+    match interface {
+        hir::Path::ThisModule(interface) => index.impls().find(|imp| {
+            imp.interface == interface
+                && resolve_ty(&imp.ty, imp.ty_range, index, &mut diagnostics) == *ty
+        }),
+        hir::Path::OtherModule(fqn) => {
+            let index = world_index.module_index(fqn.module);
+            index.impls().find(|imp| {
+                imp.interface == fqn.name
+                    && resolve_ty(&imp.ty, imp.ty_range, index, &mut diagnostics)
+                        .qualify(fqn.module)
+                        == *ty
+            })
+        }
+    }
+}
+
+/// The function implementing `function` of `interface` for `ty`.
+///
+/// Named types have to refer to their module explicitly,
+/// as `interface` does.
+pub fn impl_function(
+    interface: hir::Fqn,
+    ty: &ResolvedTy,
+    function: hir::Name,
+    world_index: &hir::WorldIndex,
+) -> Option<hir::Fqn> {
+    let index = world_index.module_index(interface.module);
+    let imp = find_impl(hir::Path::OtherModule(interface), ty, index, world_index)?;
+
+    imp.functions
+        .iter()
+        .find(|(name, _)| *name == function)
+        .map(|(_, local_name)| hir::Fqn { module: interface.module, name: *local_name })
 }
 
 fn resolve_ty(
-    ty: hir::Ty,
+    ty: &hir::Ty,
     range: Option<TextRange>,
     index: &hir::Index,
     diagnostics: &mut Vec<TyDiagnostic>,
@@ -288,10 +1085,18 @@ fn resolve_ty(
         hir::Ty::Unknown => ResolvedTy::Unknown,
         hir::Ty::S32 => ResolvedTy::S32,
         hir::Ty::String => ResolvedTy::String,
-        hir::Ty::Named(name) => match index.get_definition(name) {
+        hir::Ty::Bool => ResolvedTy::Bool,
+        hir::Ty::Named(name) => match index.get_definition(*name) {
             Some(definition) => match definition {
                 hir::Definition::Function(_) => todo!(),
-                hir::Definition::Record(_) => todo!(),
+                hir::Definition::Record(_) => ResolvedTy::Named(hir::Path::ThisModule(*name)),
+                hir::Definition::Interface(_) => {
+                    diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::InterfaceAsTy { name: name.0 },
+                        range: range.unwrap(),
+                    });
+                    ResolvedTy::Unknown
+                }
             },
             None => {
                 diagnostics.push(TyDiagnostic {
@@ -302,23 +1107,84 @@ fn resolve_ty(
             }
         },
         hir::Ty::Unit => ResolvedTy::Unit,
+        hir::Ty::Optional(ty) => match resolve_ty(ty, range, index, diagnostics) {
+            ResolvedTy::Unknown => ResolvedTy::Unknown,
+            ty @ ResolvedTy::Optional(_) => {
+                if let Some(range) = range {
+                    diagnostics.push(TyDiagnostic {
+                        kind: TyDiagnosticKind::NestedOptional {
+                            ty: ResolvedTy::Optional(Box::new(ty.clone())),
+                        },
+                        range,
+                    });
+                }
+                ty
+            }
+            ty => ResolvedTy::Optional(Box::new(ty)),
+        },
+        hir::Ty::Generic(name) => ResolvedTy::Generic(*name),
+        hir::Ty::Tuple(tys) => ResolvedTy::Tuple(
+            tys.iter().map(|ty| resolve_ty(ty, range, index, diagnostics)).collect(),
+        ),
     }
 }
 
 impl InferenceResult {
+    pub fn is_wrapped_in_optional(&self, expr: Id<hir::Expr>) -> bool {
+        self.wrapped_in_optional.get(expr).is_some()
+    }
+
+    /// The types a call instantiates the generic parameters of the function it calls with,
+    /// in the order they were defined in.
+    pub fn generic_args(&self, call: Id<hir::Expr>) -> &[ResolvedTy] {
+        self.generic_args.get(call).map_or(&[], |generic_args| generic_args)
+    }
+
     fn shrink_to_fit(&mut self) {
-        let Self { signatures, expr_tys, local_tys } = self;
+        let Self { signatures, expr_tys, local_tys, wrapped_in_optional, generic_args } = self;
         signatures.shrink_to_fit();
         expr_tys.shrink_to_fit();
         local_tys.shrink_to_fit();
+        wrapped_in_optional.shrink_to_fit();
+        generic_args.shrink_to_fit();
     }
 }
 
 impl InferenceResult {
     pub fn debug(&self, interner: &Interner) -> String {
+        self.debug_filtered(None, interner)
+    }
+
+    /// Like [`InferenceResult::debug`], but only showing the signature of the function
+    /// with the given name and the types of the expressions and locals inside it.
+    pub fn debug_function(
+        &self,
+        name: hir::Name,
+        bodies: &hir::Bodies,
+        interner: &Interner,
+    ) -> String {
+        let exprs = bodies.function_exprs(name);
+        self.debug_filtered(Some((name, bodies, &exprs)), interner)
+    }
+
+    fn debug_filtered(
+        &self,
+        function: Option<(hir::Name, &hir::Bodies, &[Id<hir::Expr>])>,
+        interner: &Interner,
+    ) -> String {
         let mut s = String::new();
 
-        for (name, signature) in &self.signatures {
+        // the expressions of a function are sorted, so they can be searched through quickly
+        let contains = |exprs: &[Id<hir::Expr>], expr: Id<hir::Expr>| {
+            exprs.binary_search_by_key(&expr.to_raw(), |expr| expr.to_raw()).is_ok()
+        };
+
+        let signatures = self
+            .signatures
+            .iter()
+            .filter(|(name, _)| function.is_none_or(|(function, _, _)| **name == function));
+
+        for (name, signature) in signatures {
             s.push_str(&format!("{}(", interner.lookup(name.0)));
             for (idx, param_ty) in signature.param_tys.iter().enumerate() {
                 if idx != 0 {
@@ -331,17 +1197,39 @@ impl InferenceResult {
             s.push_str(&format!(": {}\n", signature.return_ty.display(interner)));
         }
 
+        let expr_tys = self
+            .expr_tys
+            .iter()
+            .filter(|(expr, _)| function.is_none_or(|(_, _, exprs)| contains(exprs, *expr)));
+
         s.push('\n');
-        for (expr_id, ty) in self.expr_tys.iter() {
-            s.push_str(&format!("{}: {}\n", expr_id.to_raw(), ty.display(interner)));
+        for (expr_id, ty) in expr_tys {
+            s.push_str(&format!("{}: {}", expr_id.to_raw(), ty.display(interner)));
+            if self.is_wrapped_in_optional(expr_id) {
+                s.push_str(" (wrapped in optional)");
+            }
+            if let Some(generic_args) = self.generic_args.get(expr_id) {
+                let generic_args: Vec<_> =
+                    generic_args.iter().map(|ty| ty.display(interner)).collect();
+                s.push_str(&format!(" (with {})", generic_args.join(", ")));
+            }
+            s.push('\n');
         }
 
-        if self.local_tys.is_empty() {
+        let local_tys: Vec<_> = self
+            .local_tys
+            .iter()
+            .filter(|(local_def, _)| {
+                function.is_none_or(|(_, bodies, exprs)| contains(exprs, bodies[*local_def].value))
+            })
+            .collect();
+
+        if local_tys.is_empty() {
             return s;
         }
 
         s.push('\n');
-        for (local_def_id, ty) in self.local_tys.iter() {
+        for (local_def_id, ty) in local_tys {
             s.push_str(&format!("l{}: {}\n", local_def_id.to_raw(), ty.display(interner)));
         }
 
@@ -350,20 +1238,60 @@ impl InferenceResult {
 }
 
 impl ResolvedTy {
-    pub fn display(self, interner: &Interner) -> String {
+    pub fn display(&self, interner: &Interner) -> String {
         match self {
             Self::Unknown => "<unknown>".to_string(),
             Self::S32 => "s32".to_string(),
             Self::String => "string".to_string(),
-            Self::Named(fqn) => {
+            Self::Bool => "bool".to_string(),
+            Self::Named(hir::Path::ThisModule(name)) => {
+                hir::source_name(interner.lookup(name.0)).to_string()
+            }
+            Self::Named(hir::Path::OtherModule(fqn)) => {
                 format!("{}.{}", interner.lookup(fqn.module.0), interner.lookup(fqn.name.0))
             }
             Self::Unit => "unit".to_string(),
+            Self::Optional(ty) => format!("{}?", ty.display(interner)),
+            Self::Generic(name) => interner.lookup(name.0).to_string(),
+            Self::Tuple(tys) if tys.len() == 1 => format!("({},)", tys[0].display(interner)),
+            Self::Tuple(tys) => {
+                let tys: Vec<_> = tys.iter().map(|ty| ty.display(interner)).collect();
+                format!("({})", tys.join(", "))
+            }
         }
     }
-}
 
-#[cfg(test)]
+    /// Replaces generic parameters with the types they were instantiated with,
+    /// or with an unknown type if they weren’t.
+    pub fn substitute(&self, generic_args: &FxHashMap<hir::Name, ResolvedTy>) -> ResolvedTy {
+        match self {
+            Self::Generic(name) => generic_args.get(name).cloned().unwrap_or(Self::Unknown),
+            Self::Optional(ty) => match (&**ty, ty.substitute(generic_args)) {
+                (Self::Generic(_), Self::Unknown) => Self::Unknown,
+                (_, ty @ Self::Optional(_)) => ty,
+                (_, ty) => Self::Optional(Box::new(ty)),
+            },
+            Self::Tuple(tys) => {
+                Self::Tuple(tys.iter().map(|ty| ty.substitute(generic_args)).collect())
+            }
+            _ => self.clone(),
+        }
+    }
+
+    /// Makes a type resolved in `module` refer to it from the outside.
+    pub fn qualify(self, module: hir::Name) -> ResolvedTy {
+        match self {
+            Self::Named(hir::Path::ThisModule(name)) => {
+                Self::Named(hir::Path::OtherModule(hir::Fqn { module, name }))
+            }
+            Self::Optional(ty) => Self::Optional(Box::new(ty.qualify(module))),
+            Self::Tuple(tys) => Self::Tuple(tys.into_iter().map(|ty| ty.qualify(module)).collect()),
+            _ => self,
+        }
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
     use ast::AstNode;
@@ -399,7 +1327,7 @@ mod tests {
         let tree = parser::parse_source_file(&tokens, text).into_syntax_tree();
         let root = ast::Root::cast(tree.root(), &tree).unwrap();
         let (index, _) = hir::index(root, &tree, &mut interner);
-        let (bodies, _) = hir::lower(root, &tree, &index, &world_index, &mut interner);
+        let (bodies, _) = hir::lower(root, &tree, None, &index, &world_index, &mut interner);
 
         let (inference_result, actual_diagnostics) =
             infer(hir::Name(interner.intern(function_name)), &bodies, &index, &world_index);
@@ -459,17 +1387,10 @@ mod tests {
             "one",
             expect![[r#"
                 one(): <unknown>
-                two(): <unknown>
 
                 0: s32
-                1: s32
             "#]],
-            |i| {
-                [
-                    (TyDiagnosticKind::Undefined { name: i.intern("foo") }, 26..29),
-                    (TyDiagnosticKind::Undefined { name: i.intern("foo") }, 26..29),
-                ]
-            },
+            |i| [(TyDiagnosticKind::Undefined { name: i.intern("foo") }, 26..29)],
         );
     }
 
@@ -507,6 +1428,292 @@ mod tests {
         );
     }
 
+    #[test]
+    fn comparison() {
+        check(
+            r#"
+                fnc is_small(n: s32): bool -> n < 10;
+            "#,
+            "is_small",
+            expect![[r#"
+                is_small(s32): bool
+
+                0: s32
+                1: s32
+                2: bool
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn compare_bools_for_equality() {
+        check(
+            r#"
+                fnc same_sign(a: s32, b: s32): bool -> (a < 0) == (b < 0);
+            "#,
+            "same_sign",
+            expect![[r#"
+                same_sign(s32, s32): bool
+
+                0: s32
+                1: s32
+                2: bool
+                3: s32
+                4: s32
+                5: bool
+                6: bool
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn order_bools() {
+        check(
+            r#"
+                fnc f(a: bool, b: bool): bool -> a < b;
+            "#,
+            "f",
+            expect![[r#"
+                f(bool, bool): bool
+
+                0: bool
+                1: bool
+                2: bool
+            "#]],
+            |_| {
+                [
+                    (
+                        TyDiagnosticKind::Mismatch {
+                            expected: ResolvedTy::S32,
+                            found: ResolvedTy::Bool,
+                        },
+                        50..51,
+                    ),
+                    (
+                        TyDiagnosticKind::Mismatch {
+                            expected: ResolvedTy::S32,
+                            found: ResolvedTy::Bool,
+                        },
+                        54..55,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn panic() {
+        check(
+            r#"
+                fnc fail -> panic "oh no";
+            "#,
+            "fail",
+            expect![[r#"
+                fail(): unit
+
+                0: string
+                1: unit
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn assert_with_non_bool_condition() {
+        check(
+            r#"
+                fnc check(n: s32) -> assert n, "n was zero";
+            "#,
+            "check",
+            expect![[r#"
+                check(s32): unit
+
+                0: s32
+                1: string
+                2: unit
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::Bool,
+                        found: ResolvedTy::S32,
+                    },
+                    45..46,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn contract() {
+        check(
+            r#"
+                fnc div(a: s32, b: s32): s32
+                    requires b != 0
+                    ensures quotient: quotient * b <= a
+                    -> a / b;
+            "#,
+            "div",
+            expect![[r#"
+                div(s32, s32): s32
+
+                0: s32
+                1: s32
+                2: s32
+                3: s32
+                4: s32
+                5: bool
+                6: s32
+                7: s32
+                8: s32
+                9: s32
+                10: bool
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn contract_with_non_bool_conditions() {
+        check(
+            r#"
+                fnc describe(n: s32): string
+                    requires n
+                    ensures description: description
+                    -> "number";
+            "#,
+            "describe",
+            expect![[r#"
+                describe(s32): string
+
+                0: string
+                1: s32
+                2: string
+            "#]],
+            |_| {
+                [
+                    (
+                        TyDiagnosticKind::Mismatch {
+                            expected: ResolvedTy::Bool,
+                            found: ResolvedTy::S32,
+                        },
+                        75..76,
+                    ),
+                    (
+                        TyDiagnosticKind::Mismatch {
+                            expected: ResolvedTy::Bool,
+                            found: ResolvedTy::String,
+                        },
+                        118..129,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn try_in_contract() {
+        check(
+            r#"
+                fnc first(a: s32?): s32? requires a? > 0 -> a;
+            "#,
+            "first",
+            expect![[r#"
+                first(s32?): s32?
+
+                0: s32?
+                1: s32?
+                2: s32
+                3: s32
+                4: bool
+            "#]],
+            |_| [(TyDiagnosticKind::TryInContract, 51..53)],
+        );
+    }
+
+    #[test]
+    fn debug_one_function() {
+        let text = r#"
+            fnc one: s32 -> 1;
+            fnc pair(s: string): (s32, string) requires one > 0 -> {
+                let (a, b) = (one, s);
+                (a, b)
+            };
+        "#;
+
+        let mut interner = Interner::default();
+        let world_index = hir::WorldIndex::default();
+        let tokens = lexer::lex(text);
+        let tree = parser::parse_source_file(&tokens, text).into_syntax_tree();
+        let root = ast::Root::cast(tree.root(), &tree).unwrap();
+        let (index, _) = hir::index(root, &tree, &mut interner);
+        let (bodies, _) = hir::lower(root, &tree, None, &index, &world_index, &mut interner);
+
+        let (inference_result, diagnostics) = infer_all(&bodies, &index, &world_index);
+        assert!(diagnostics.is_empty());
+
+        expect![[r#"
+            pair(string): (s32, string)
+
+            1: s32
+            2: string
+            3: (s32, string)
+            4: (s32, string)
+            5: s32
+            6: string
+            7: s32
+            8: string
+            9: (s32, string)
+            10: (s32, string)
+            11: s32
+            12: s32
+            13: bool
+
+            l0: (s32, string)
+            l1: s32
+            l2: string
+        "#]]
+        .assert_eq(&inference_result.debug_function(
+            hir::Name(interner.intern("pair")),
+            &bodies,
+            &interner,
+        ));
+    }
+
+    #[test]
+    fn test_returning_value() {
+        let text = r#"test "returns a number" -> 42;"#;
+
+        let mut interner = Interner::default();
+        let world_index = hir::WorldIndex::default();
+        let tokens = lexer::lex(text);
+        let tree = parser::parse_source_file(&tokens, text).into_syntax_tree();
+        let root = ast::Root::cast(tree.root(), &tree).unwrap();
+        let (index, _) = hir::index(root, &tree, &mut interner);
+        let (bodies, _) = hir::lower(root, &tree, None, &index, &world_index, &mut interner);
+
+        let (inference_result, diagnostics) = infer_all(&bodies, &index, &world_index);
+
+        expect![[r#"
+
+            0: s32
+        "#]]
+        .assert_eq(&inference_result.debug(&interner));
+
+        assert_eq!(
+            diagnostics,
+            [TyDiagnostic {
+                kind: TyDiagnosticKind::Mismatch {
+                    expected: ResolvedTy::Unit,
+                    found: ResolvedTy::S32
+                },
+                range: TextRange::new(27.into(), 29.into()),
+            }]
+        );
+    }
+
     #[test]
     fn function_with_params() {
         check(
@@ -548,6 +1755,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn local_definition_with_ty() {
+        check(
+            r#"
+                fnc main -> {
+                    let a: bool = 1 < 2;
+                    a;
+                };
+            "#,
+            "main",
+            expect![[r#"
+                main(): unit
+
+                0: s32
+                1: s32
+                2: bool
+                3: bool
+                4: unit
+
+                l0: bool
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn local_definition_with_mismatched_ty() {
+        check(
+            r#"
+                fnc main -> {
+                    let a: string = {
+                        let b = 5;
+                        b + 1
+                    };
+                    a;
+                };
+            "#,
+            "main",
+            expect![[r#"
+                main(): unit
+
+                0: s32
+                1: s32
+                2: s32
+                3: s32
+                4: s32
+                5: string
+                6: unit
+
+                l0: s32
+                l1: string
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::String,
+                        found: ResolvedTy::S32,
+                    },
+                    128..133,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn local_definition_with_undefined_ty() {
+        check(
+            r#"
+                fnc main -> {
+                    let a: foo = 5;
+                    a;
+                };
+            "#,
+            "main",
+            expect![[r#"
+                main(): unit
+
+                0: s32
+                1: s32
+                2: unit
+
+                l0: s32
+            "#]],
+            |i| [(TyDiagnosticKind::Undefined { name: i.intern("foo") }, 58..61)],
+        );
+    }
+
     #[test]
     fn local_shadowing() {
         check(
@@ -731,58 +2025,1186 @@ mod tests {
     }
 
     #[test]
-    fn call_function_from_other_module() {
+    fn call_with_default_arg() {
         check(
             r#"
-                #- main
-                fnc a: string -> greetings.informal 10;
-                #- greetings
-                fnc informal(n: s32): string -> "Hello!";
+                fnc main: s32 -> add 1;
+                fnc add(x: s32, y: s32 = 2): s32 -> x + y;
             "#,
-            "a",
+            "main",
             expect![[r#"
-                a(): string
+                main(): s32
 
                 0: s32
-                1: string
+                1: s32
+                2: s32
             "#]],
             |_| [],
         );
     }
 
     #[test]
-    fn attach_mismatch_diagnostics_to_block_tail_expr() {
+    fn mismatched_default_value_ty() {
         check(
             r#"
-                fnc main -> take_s32 {
-                    let a = 10 + 10;
-                    "foo"
-                };
+                fnc greet(name: string = 5) -> {};
+            "#,
+            "greet",
+            expect![[r#"
+                greet(string): unit
 
-                fnc take_s32(n: s32) -> {};
+                0: s32
+                1: unit
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::String,
+                        found: ResolvedTy::S32,
+                    },
+                    42..43,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn call_function_from_other_module() {
+        check(
+            r#"
+                #- main
+                fnc a: string -> greetings.informal 10;
+                #- greetings
+                fnc informal(n: s32): string -> "Hello!";
             "#,
-            "main",
+            "a",
             expect![[r#"
-                main(): unit
+                a(): string
 
                 0: s32
-                1: s32
+                1: string
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_record() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                fnc f(p: Point): s32 -> {
+                    let Point { x, y } = p;
+                    x + y
+                };
+            "#,
+            "f",
+            expect![[r#"
+                f(Point): s32
+
+                0: Point
+                1: Point
                 2: s32
+                3: s32
+                4: s32
+                5: s32
+                6: s32
+                7: s32
+
+                l0: Point
+                l1: s32
+                l2: s32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_nested_record() {
+        check(
+            r#"
+                rec Name { first: string, last: string };
+                rec Person { name: Name, age: s32 };
+                fnc f(p: Person): string -> {
+                    let Person { name: Name { first, last: _ }, age: _ } = p;
+                    first
+                };
+            "#,
+            "f",
+            expect![[r#"
+                f(Person): string
+
+                0: Person
+                1: Person
+                2: Name
                 3: string
-                4: string
-                5: unit
+                6: string
+                7: string
+
+                l0: Person
+                l1: string
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_mismatched_record() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                fnc f(n: s32) -> {
+                    let Point { x, y } = n;
+                };
+            "#,
+            "f",
+            expect![[r#"
+                f(s32): unit
+
+                0: s32
+                1: s32
+                2: <unknown>
+                3: <unknown>
+                4: unit
 
                 l0: s32
+                l1: s32
+                l2: s32
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::Named(hir::Path::ThisModule(hir::Name(
+                            i.intern("Point"),
+                        ))),
+                        found: ResolvedTy::S32,
+                    },
+                    106..120,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn destructure_record_with_unknown_and_missing_fields() {
+        check(
+            r#"
+                rec Point { x: s32, y: s32 };
+                fnc f(p: Point) -> {
+                    let Point { x, z } = p;
+                };
+            "#,
+            "f",
+            expect![[r#"
+                f(Point): unit
+
+                0: Point
+                1: Point
+                2: s32
+                3: <unknown>
+                4: unit
+
+                l0: Point
+                l1: s32
+                l2: <unknown>
+            "#]],
+            |i| {
+                [
+                    (
+                        TyDiagnosticKind::UnknownField {
+                            record: i.intern("Point"),
+                            field: i.intern("z"),
+                        },
+                        119..120,
+                    ),
+                    (
+                        TyDiagnosticKind::MissingField {
+                            record: i.intern("Point"),
+                            field: i.intern("y"),
+                        },
+                        108..122,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn destructure_record_from_other_module() {
+        check(
+            r#"
+                #- main
+                fnc f: s32 -> {
+                    let geo.Point { x, y } = geo.origin;
+                    x + y
+                };
+                #- geo
+                rec Point { x: s32, y: s32 };
+                fnc origin: Point -> origin;
+            "#,
+            "f",
+            expect![[r#"
+                f(): s32
+
+                0: geo.Point
+                1: geo.Point
+                2: s32
+                3: s32
+                4: s32
+                5: s32
+                6: s32
+                7: s32
+
+                l0: geo.Point
+                l1: s32
+                l2: s32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn wrap_value_in_optional() {
+        check(
+            r#"
+                fnc f(n: s32): s32? -> n;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32): s32?
+
+                0: s32 (wrapped in optional)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn none_is_optional_of_any_ty() {
+        check(
+            r#"
+                fnc f: string? -> {
+                    let a: s32? = none;
+                    none
+                };
+            "#,
+            "f",
+            expect![[r#"
+                f(): string?
+
+                0: <unknown>?
+                1: <unknown>?
+                2: <unknown>?
+
+                l0: s32?
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn mismatched_optional_ty() {
+        check(
+            r#"
+                fnc f: s32? -> "foo";
+            "#,
+            "f",
+            expect![[r#"
+                f(): s32?
+
+                0: string
             "#]],
             |_| {
                 [(
                     TyDiagnosticKind::Mismatch {
-                        expected: ResolvedTy::S32,
+                        expected: ResolvedTy::Optional(Box::new(ResolvedTy::S32)),
                         found: ResolvedTy::String,
                     },
-                    97..102,
+                    32..37,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn optional_is_not_value() {
+        check(
+            r#"
+                fnc f(n: s32?): s32 -> n;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32?): s32
+
+                0: s32?
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::S32,
+                        found: ResolvedTy::Optional(Box::new(ResolvedTy::S32)),
+                    },
+                    40..41,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn try_expr() {
+        check(
+            r#"
+                fnc f(n: s32?): string? -> {
+                    let a = n? + 1;
+                    "foo"
+                };
+            "#,
+            "f",
+            expect![[r#"
+                f(s32?): string?
+
+                0: s32?
+                1: s32
+                2: s32
+                3: s32
+                4: string
+                5: string (wrapped in optional)
+
+                l0: s32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn try_on_non_optional() {
+        check(
+            r#"
+                fnc f(n: s32): s32? -> n?;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32): s32?
+
+                0: s32
+                1: <unknown>
+            "#]],
+            |_| [(TyDiagnosticKind::TryOnNonOptional { found: ResolvedTy::S32 }, 40..41)],
+        );
+    }
+
+    #[test]
+    fn try_in_non_optional_function() {
+        check(
+            r#"
+                fnc f(n: s32?): s32 -> n?;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32?): s32
+
+                0: s32?
+                1: s32
+            "#]],
+            |_| [(TyDiagnosticKind::TryInNonOptionalFunction, 40..42)],
+        );
+    }
+
+    #[test]
+    fn nested_optional_ty() {
+        check(
+            r#"
+                fnc f(n: s32??): s32? -> n;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32?): s32?
+
+                0: s32?
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::NestedOptional {
+                        ty: ResolvedTy::Optional(Box::new(ResolvedTy::Optional(Box::new(
+                            ResolvedTy::S32,
+                        )))),
+                    },
+                    26..31,
                 )]
             },
         );
     }
+
+    #[test]
+    fn instantiate_optional_generic_param_with_optional() {
+        check(
+            r#"
+                fnc wrap<T>(v: T): T? -> v;
+                fnc f(n: s32?): s32? -> wrap n;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32?): s32?
+
+                1: s32?
+                2: s32? (with s32?)
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::NestedOptional {
+                        ty: ResolvedTy::Optional(Box::new(ResolvedTy::Optional(Box::new(
+                            ResolvedTy::S32,
+                        )))),
+                    },
+                    85..91,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn instantiate_generic_param_made_optional_in_body_with_optional() {
+        check(
+            r#"
+                fnc keep<T>(v: T): T -> {
+                    let a: T? = v;
+                    v
+                };
+                fnc f(n: s32?): s32? -> keep n;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32?): s32?
+
+                3: s32?
+                4: s32? (with s32?)
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::NestedOptional {
+                        ty: ResolvedTy::Optional(Box::new(ResolvedTy::Optional(Box::new(
+                            ResolvedTy::S32,
+                        )))),
+                    },
+                    159..165,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn instantiate_generic_param_with_optional() {
+        check(
+            r#"
+                fnc id<T>(v: T): T -> v;
+                fnc f(n: s32?): s32? -> id n;
+            "#,
+            "f",
+            expect![[r#"
+                f(s32?): s32?
+
+                1: s32?
+                2: s32? (with s32?)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn call_extern_function() {
+        check(
+            r#"
+                fnc main: s32 -> square 4;
+                ext fnc square(n: s32): s32;
+            "#,
+            "main",
+            expect![[r#"
+                main(): s32
+
+                0: s32
+                1: s32
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn infer_extern_function() {
+        check(
+            r#"
+                ext fnc square(n: s32): s32;
+            "#,
+            "square",
+            expect![[r#"
+                square(s32): s32
+
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn attach_mismatch_diagnostics_to_block_tail_expr() {
+        check(
+            r#"
+                fnc main -> take_s32 {
+                    let a = 10 + 10;
+                    "foo"
+                };
+
+                fnc take_s32(n: s32) -> {};
+            "#,
+            "main",
+            expect![[r#"
+                main(): unit
+
+                0: s32
+                1: s32
+                2: s32
+                3: string
+                4: string
+                5: unit
+
+                l0: s32
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::S32,
+                        found: ResolvedTy::String,
+                    },
+                    97..102,
+                )]
+            },
+        );
+    }
+
+    #[track_caller]
+    fn check_impls<const N: usize>(
+        input: &str,
+        expected_diagnostics: impl Fn(&mut Interner) -> [(TyDiagnosticKind, std::ops::Range<u32>); N],
+    ) {
+        let mut interner = Interner::default();
+        let world_index = hir::WorldIndex::default();
+        let tokens = lexer::lex(input);
+        let tree = parser::parse_source_file(&tokens, input).into_syntax_tree();
+        let root = ast::Root::cast(tree.root(), &tree).unwrap();
+        let (index, _) = hir::index(root, &tree, &mut interner);
+        let (bodies, _) = hir::lower(root, &tree, None, &index, &world_index, &mut interner);
+
+        let (_, actual_diagnostics) = infer_all(&bodies, &index, &world_index);
+
+        let expected_diagnostics: Vec<_> = expected_diagnostics(&mut interner)
+            .into_iter()
+            .map(|(kind, range)| TyDiagnostic {
+                kind,
+                range: TextRange::new(range.start.into(), range.end.into()),
+            })
+            .collect();
+
+        assert_eq!(expected_diagnostics, actual_diagnostics);
+    }
+
+    #[test]
+    fn call_generic_function() {
+        check(
+            r#"
+                fnc id<T>(value: T): T -> value;
+                fnc main: s32 -> id 5;
+            "#,
+            "main",
+            expect![[r#"
+                main(): s32
+
+                1: s32
+                2: s32 (with s32)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn generic_param_in_optional() {
+        check(
+            r#"
+                fnc unwrap_or<T>(value: T?, fallback: T): T -> fallback;
+                fnc main: string -> unwrap_or none, "fallback";
+            "#,
+            "main",
+            expect![[r#"
+                main(): string
+
+                1: <unknown>?
+                2: string
+                3: string (with string)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn generic_args_have_to_match() {
+        check(
+            r#"
+                fnc pick<T>(a: T, b: T): T -> a;
+                fnc main: s32 -> pick 1, "two";
+            "#,
+            "main",
+            expect![[r#"
+                main(): s32
+
+                1: s32
+                2: string
+                3: s32 (with s32)
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::S32,
+                        found: ResolvedTy::String,
+                    },
+                    91..96,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn uninferred_generic() {
+        check(
+            r#"
+                fnc nothing<T>: T? -> none;
+                fnc main -> { nothing; };
+            "#,
+            "main",
+            expect![[r#"
+                main(): unit
+
+                1: <unknown> (with <unknown>)
+                2: unit
+            "#]],
+            |i| [(TyDiagnosticKind::UninferredGeneric { name: i.intern("T") }, 75..82)],
+        );
+    }
+
+    #[test]
+    fn generic_param_is_opaque() {
+        check(
+            r#"
+                fnc double<T>(value: T): T -> value + value;
+            "#,
+            "double",
+            expect![[r#"
+                double(T): T
+
+                0: T
+                1: T
+                2: s32
+            "#]],
+            |i| {
+                let t = ResolvedTy::Generic(hir::Name(i.intern("T")));
+                [
+                    (
+                        TyDiagnosticKind::Mismatch { expected: ResolvedTy::S32, found: t.clone() },
+                        47..52,
+                    ),
+                    (
+                        TyDiagnosticKind::Mismatch { expected: ResolvedTy::S32, found: t.clone() },
+                        55..60,
+                    ),
+                    (TyDiagnosticKind::Mismatch { expected: t, found: ResolvedTy::S32 }, 47..60),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn call_interface_function() {
+        check(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self): string;
+                };
+                imp Describe for s32 {
+                    fnc describe(value: s32): string -> "a number";
+                };
+                fnc main: string -> describe 5;
+            "#,
+            "main",
+            expect![[r#"
+                main(): string
+
+                1: s32
+                2: string (with s32)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn call_interface_function_without_impl() {
+        check(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self): string;
+                };
+                imp Describe for s32 {
+                    fnc describe(value: s32): string -> "a number";
+                };
+                fnc main: string -> describe "text";
+            "#,
+            "main",
+            expect![[r#"
+                main(): string
+
+                1: string
+                2: string (with string)
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::UnsatisfiedBound {
+                        ty: ResolvedTy::String,
+                        interface: i.intern("Describe"),
+                    },
+                    268..283,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn call_interface_function_on_bounded_generic_param() {
+        check(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self): string;
+                };
+                fnc show<T: Describe>(value: T): string -> describe value;
+            "#,
+            "show",
+            expect![[r#"
+                show(T): string
+
+                0: T
+                1: string (with T)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn call_interface_function_on_unbounded_generic_param() {
+        check(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self): string;
+                };
+                fnc show<T>(value: T): string -> describe value;
+            "#,
+            "show",
+            expect![[r#"
+                show(T): string
+
+                0: T
+                1: string (with T)
+            "#]],
+            |i| {
+                [(
+                    TyDiagnosticKind::UnsatisfiedBound {
+                        ty: ResolvedTy::Generic(hir::Name(i.intern("T"))),
+                        interface: i.intern("Describe"),
+                    },
+                    155..169,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn bounds_that_are_not_interfaces() {
+        check(
+            r#"
+                rec Point {};
+                fnc show<T: Point, U: Nothing>(a: T, b: U) -> {};
+            "#,
+            "show",
+            expect![[r#"
+                show(T, U): unit
+
+                0: unit
+            "#]],
+            |i| {
+                [
+                    (TyDiagnosticKind::NotAnInterface { name: i.intern("Point") }, 59..64),
+                    (TyDiagnosticKind::Undefined { name: i.intern("Nothing") }, 69..76),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn interface_as_ty() {
+        check(
+            r#"
+                ifc Describe {};
+                fnc show(value: Describe) -> {};
+            "#,
+            "show",
+            expect![[r#"
+                show(<unknown>): unit
+
+                0: unit
+            "#]],
+            |i| [(TyDiagnosticKind::InterfaceAsTy { name: i.intern("Describe") }, 66..74)],
+        );
+    }
+
+    #[test]
+    fn complete_impl() {
+        check_impls(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self, verbose: bool): string;
+                };
+                imp Describe for s32 {
+                    fnc describe(value: Self, verbose: bool): string -> "a number";
+                };
+                imp Describe for string? {
+                    fnc describe(value: string?, verbose: bool): string -> "maybe text";
+                };
+            "#,
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn impl_with_missing_and_unknown_functions() {
+        check_impls(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self): string;
+                };
+                imp Describe for s32 {
+                    fnc explain(value: s32): string -> "a number";
+                };
+            "#,
+            |i| {
+                [
+                    (
+                        TyDiagnosticKind::MissingImplFunction {
+                            interface: i.intern("Describe"),
+                            function: i.intern("describe"),
+                        },
+                        126..134,
+                    ),
+                    (
+                        TyDiagnosticKind::UnknownImplFunction {
+                            interface: i.intern("Describe"),
+                            function: i.intern("explain"),
+                        },
+                        169..176,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn impl_with_mismatched_signature() {
+        check_impls(
+            r#"
+                ifc Describe {
+                    fnc describe(value: Self): string;
+                    fnc compare(a: Self, b: Self): bool;
+                };
+                imp Describe for s32 {
+                    fnc describe(value: string): s32 -> 5;
+                    fnc compare(a: s32): bool -> true;
+                };
+            "#,
+            |i| {
+                [
+                    (
+                        TyDiagnosticKind::Mismatch {
+                            expected: ResolvedTy::S32,
+                            found: ResolvedTy::String,
+                        },
+                        242..248,
+                    ),
+                    (
+                        TyDiagnosticKind::Mismatch {
+                            expected: ResolvedTy::String,
+                            found: ResolvedTy::S32,
+                        },
+                        251..254,
+                    ),
+                    (
+                        TyDiagnosticKind::MismatchedImplParamCount {
+                            function: i.intern("compare"),
+                            expected: 2,
+                            found: 1,
+                        },
+                        285..292,
+                    ),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn impl_of_non_interface() {
+        check_impls(
+            r#"
+                rec Point {};
+                imp Point for s32 {};
+                imp Nothing for s32 {};
+            "#,
+            |i| {
+                [
+                    (TyDiagnosticKind::NotAnInterface { name: i.intern("Point") }, 51..56),
+                    (TyDiagnosticKind::Undefined { name: i.intern("Nothing") }, 89..96),
+                ]
+            },
+        );
+    }
+
+    #[test]
+    fn tuple() {
+        check(
+            r#"
+                fnc pair: (s32, string) -> (1, "one");
+            "#,
+            "pair",
+            expect![[r#"
+                pair(): (s32, string)
+
+                0: s32
+                1: string
+                2: (s32, string)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tuple_field() {
+        check(
+            r#"
+                fnc second(pair: (s32, (string, bool))): bool -> pair.1.1;
+            "#,
+            "second",
+            expect![[r#"
+                second((s32, (string, bool))): bool
+
+                0: (s32, (string, bool))
+                1: (string, bool)
+                2: bool
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn tuple_field_out_of_range() {
+        check(
+            r#"
+                fnc third(pair: (s32, s32)): s32 -> pair.2;
+            "#,
+            "third",
+            expect![[r#"
+                third((s32, s32)): s32
+
+                0: (s32, s32)
+                1: <unknown>
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::TupleFieldOutOfRange {
+                        tuple: ResolvedTy::Tuple(vec![ResolvedTy::S32, ResolvedTy::S32]),
+                        idx: 2,
+                    },
+                    53..59,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn tuple_field_on_non_tuple() {
+        check(
+            r#"
+                fnc first(n: s32): s32 -> n.0;
+            "#,
+            "first",
+            expect![[r#"
+                first(s32): s32
+
+                0: s32
+                1: <unknown>
+            "#]],
+            |_| [(TyDiagnosticKind::NotATuple { found: ResolvedTy::S32 }, 43..44)],
+        );
+    }
+
+    #[test]
+    fn tuple_elements_are_wrapped_in_optionals() {
+        check(
+            r#"
+                fnc pair: (s32?, string?) -> (1, none);
+            "#,
+            "pair",
+            expect![[r#"
+                pair(): (s32?, string?)
+
+                0: s32 (wrapped in optional)
+                1: <unknown>?
+                2: (s32, <unknown>?)
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn mismatched_tuple() {
+        check(
+            r#"
+                fnc pair: (s32, string) -> ("one", 1, 2);
+            "#,
+            "pair",
+            expect![[r#"
+                pair(): (s32, string)
+
+                0: string
+                1: s32
+                2: s32
+                3: (string, s32, s32)
+            "#]],
+            |_| {
+                [(
+                    TyDiagnosticKind::Mismatch {
+                        expected: ResolvedTy::Tuple(vec![ResolvedTy::S32, ResolvedTy::String]),
+                        found: ResolvedTy::Tuple(vec![
+                            ResolvedTy::String,
+                            ResolvedTy::S32,
+                            ResolvedTy::S32,
+                        ]),
+                    },
+                    44..57,
+                )]
+            },
+        );
+    }
+
+    #[test]
+    fn destructure_tuple() {
+        check(
+            r#"
+                fnc sum(triple: (s32, s32, (s32, string))): s32 -> {
+                    let (a, _, (b, c)) = triple;
+                    a + b
+                };
+            "#,
+            "sum",
+            expect![[r#"
+                sum((s32, s32, (s32, string))): s32
+
+                0: (s32, s32, (s32, string))
+                1: (s32, s32, (s32, string))
+                2: s32
+                4: (s32, string)
+                5: s32
+                6: string
+                7: s32
+                8: s32
+                9: s32
+                10: s32
+
+                l0: (s32, s32, (s32, string))
+                l1: s32
+                l2: s32
+                l3: string
+            "#]],
+            |_| [],
+        );
+    }
+
+    #[test]
+    fn destructure_tuple_with_wrong_length() {
+        check(
+            r#"
+                fnc sum(pair: (s32, s32)): s32 -> {
+                    let (a, b, c) = pair;
+                    a + b
+                };
+            "#,
+            "sum",
+            expect![[r#"
+                sum((s32, s32)): s32
+
+                0: (s32, s32)
+                1: (s32, s32)
+                2: s32
+                3: s32
+                4: <unknown>
+                5: s32
+                6: s32
+                7: s32
+                8: s32
+
+                l0: (s32, s32)
+                l1: s32
+                l2: s32
+                l3: <unknown>
+            "#]],
+            |_| {
+                [(TyDiagnosticKind::MismatchedTuplePatternLength { expected: 2, found: 3 }, 77..86)]
+            },
+        );
+    }
+
+    #[test]
+    fn destructure_non_tuple_as_tuple() {
+        check(
+            r#"
+                fnc f(n: s32) -> {
+                    let (a, b) = n;
+                };
+            "#,
+            "f",
+            expect![[r#"
+                f(s32): unit
+
+                0: s32
+                1: s32
+                2: <unknown>
+                3: <unknown>
+                4: unit
+
+                l0: s32
+                l1: <unknown>
+                l2: <unknown>
+            "#]],
+            |_| [(TyDiagnosticKind::NotATuple { found: ResolvedTy::S32 }, 60..66)],
+        );
+    }
+
+    #[test]
+    fn generic_param_in_tuple() {
+        check(
+            r#"
+                fnc swap<A, B>(pair: (A, B)): (B, A) -> (pair.1, pair.0);
+                fnc main: (string, s32) -> swap (1, "one");
+            "#,
+            "main",
+            expect![[r#"
+                main(): (string, s32)
+
+                5: s32
+                6: string
+                7: (s32, string)
+                8: (string, s32) (with s32, string)
+            "#]],
+            |_| [],
+        );
+    }
 }
@@ -7,7 +7,7 @@ use parser::Parse;
 use rustc_hash::FxHashMap;
 use std::ops::BitOrAssign;
 use std::path::Path;
-use std::{fs, io, mem};
+use std::{fs, mem};
 use syntax::{Event, NodeKind, SyntaxElement, SyntaxNode, SyntaxTree, TokenKind};
 use text_size::{TextRange, TextSize};
 use url::Url;
@@ -25,6 +25,7 @@ pub struct Analysis {
     content: String,
     line_index: LineIndex,
     module_name: hir::Name,
+    package: Option<hir::Name>,
     parse: Parse,
     ast: ast::Root,
     validation_diagnostics: Vec<ValidationDiagnostic>,
@@ -37,7 +38,7 @@ pub struct Analysis {
 }
 
 impl GlobalState {
-    pub fn open_file(&mut self, uri: Url) -> io::Result<Result<(), ()>> {
+    pub fn open_file(&mut self, uri: Url) -> Result<Result<(), ()>, hir::ProjectError> {
         let path = "/".to_string() + &uri.path_segments().unwrap().collect::<Vec<_>>().join("/");
         let path = Path::new(&path);
         assert_eq!(path.extension().unwrap(), "gb");
@@ -50,19 +51,39 @@ impl GlobalState {
             return Ok(Err(()));
         }
 
-        let project = hir::Project::new(path.parent().unwrap(), &mut self.interner)?;
+        let project = hir::Project::discover(path.parent().unwrap(), &mut self.interner)?;
 
-        for module in project.modules() {
-            let uri = path_to_uri(module);
-            let content = fs::read_to_string(module)?;
+        if !project.is_module(path) {
+            return Ok(Err(()));
+        }
+
+        self.load_project(project)?;
+
+        Ok(Ok(()))
+    }
 
-            let filename = uri.path_segments().unwrap().next_back().unwrap();
-            let module_name = filename.find('.').map_or(filename, |dot| &filename[..dot]);
+    /// Analyzes every module of the project containing `dir`.
+    pub fn open_project(&mut self, dir: &Path) -> Result<(), hir::ProjectError> {
+        let project = hir::Project::discover(dir, &mut self.interner)?;
+        self.load_project(project)
+    }
+
+    fn load_project(&mut self, project: hir::Project) -> Result<(), hir::ProjectError> {
+        for module in project.modules() {
+            let uri = path_to_uri(&module.path);
+            let is_std = module.package.is_none()
+                && self.interner.lookup(module.name.0) == hir::STD_MODULE_NAME;
+            let content = if is_std {
+                hir::STD_SOURCE.to_string()
+            } else {
+                fs::read_to_string(&module.path)?
+            };
 
             let analysis = Analysis::new(
                 uri.clone(),
                 content,
-                hir::Name(self.interner.intern(module_name)),
+                module.name,
+                module.package,
                 &mut self.interner,
                 &mut self.world_index,
             );
@@ -76,15 +97,16 @@ impl GlobalState {
 
         self.project = Some(project);
 
-        Ok(Ok(()))
+        Ok(())
     }
 
-    pub fn update_contents(&mut self, uri: &Url, f: impl FnOnce(&mut String, &LineIndex)) {
-        self.analyses.get_mut(uri).unwrap().update_contents(
-            f,
-            &mut self.interner,
-            &mut self.world_index,
-        );
+    pub fn update_contents(&mut self, uri: &Url, f: impl FnOnce(&mut String, &LineIndex)) -> bool {
+        // files outside of a project are never analyzed
+        let analysis = match self.analyses.get_mut(uri) {
+            Some(analysis) => analysis,
+            None => return false,
+        };
+        analysis.update_contents(f, &mut self.interner, &mut self.world_index);
 
         for (analysis_uri, analysis) in &mut self.analyses {
             if analysis_uri == uri {
@@ -93,6 +115,12 @@ impl GlobalState {
 
             analysis.recheck(&mut self.world_index, &mut self.interner);
         }
+
+        true
+    }
+
+    pub fn is_analyzed(&self, uri: &Url) -> bool {
+        self.analyses.contains_key(uri)
     }
 
     pub fn parent_ranges(&self, uri: &Url, offset: TextSize) -> Vec<TextRange> {
@@ -111,6 +139,10 @@ impl GlobalState {
         let project = self.project.as_ref().unwrap();
         self.world_index
             .ranges()
+            // definitions inside blocks and implementations have names nobody can write
+            .filter(|(fqn, _)| {
+                !self.world_index.module_index(fqn.module).is_local_definition(fqn.name)
+            })
             .map(|(fqn, range_info)| {
                 let module_name = self.interner.lookup(fqn.module.0);
                 let definition_name = self.interner.lookup(fqn.name.0);
@@ -128,7 +160,17 @@ impl GlobalState {
     }
 
     pub fn diagnostics(&self) -> impl Iterator<Item = (&Url, Vec<Diagnostic>)> {
-        self.analyses.iter().map(|(uri, analysis)| (uri, analysis.diagnostics()))
+        let lints = self.project.as_ref().unwrap().lints();
+
+        self.analyses.iter().map(move |(uri, analysis)| {
+            let diagnostics = analysis
+                .diagnostics()
+                .into_iter()
+                .filter_map(|diagnostic| diagnostic.with_lint_levels(lints))
+                .collect();
+
+            (uri, diagnostics)
+        })
     }
 
     pub fn line_index(&self, uri: &Url) -> &LineIndex {
@@ -138,6 +180,116 @@ impl GlobalState {
     pub fn interner(&self) -> &Interner {
         &self.interner
     }
+
+    pub fn project(&self) -> Option<&hir::Project> {
+        self.project.as_ref()
+    }
+
+    pub fn world_index(&self) -> &hir::WorldIndex {
+        &self.world_index
+    }
+
+    pub fn content(&self, uri: &Url) -> &str {
+        &self.analyses[uri].content
+    }
+
+    pub fn module_uri(&self, module: hir::Name) -> Option<Url> {
+        self.project.as_ref()?.module_path(module).map(path_to_uri)
+    }
+
+    /// The lowered bodies of every module, as `eval` expects them.
+    pub fn bodies_map(&self) -> FxHashMap<hir::Name, hir::Bodies> {
+        self.analyses
+            .values()
+            .map(|analysis| (analysis.module_name, analysis.bodies.clone()))
+            .collect()
+    }
+
+    /// The inference results of every module, as `eval` expects them.
+    pub fn tys_map(&self) -> FxHashMap<hir::Name, hir_ty::InferenceResult> {
+        self.analyses
+            .values()
+            .map(|analysis| (analysis.module_name, analysis.inference_result.clone()))
+            .collect()
+    }
+
+    /// Every test of the project itself, sorted by module and then by name.
+    ///
+    /// Tests in dependencies aren’t included.
+    pub fn tests(&self) -> Vec<hir::Fqn> {
+        let mut tests: Vec<_> = self
+            .analyses
+            .values()
+            .filter(|analysis| analysis.package.is_none())
+            .flat_map(|analysis| {
+                analysis
+                    .index
+                    .tests()
+                    .map(|(name, _)| hir::Fqn { module: analysis.module_name, name })
+            })
+            .collect();
+
+        tests.sort_unstable_by_key(|fqn| {
+            (self.interner.lookup(fqn.module.0), self.interner.lookup(fqn.name.0))
+        });
+
+        tests
+    }
+}
+
+/// A program analyzed from sources held in memory rather than from a project on disk.
+pub struct Program {
+    pub interner: Interner,
+    pub world_index: hir::WorldIndex,
+    pub bodies_map: FxHashMap<hir::Name, hir::Bodies>,
+    pub tys_map: FxHashMap<hir::Name, hir_ty::InferenceResult>,
+    pub diagnostics: FxHashMap<hir::Name, Vec<Diagnostic>>,
+}
+
+/// Analyzes the given modules together with `std`.
+///
+/// Modules of dependencies are named `package.module`, just like in projects.
+pub fn analyze_sources(modules: &[(&str, &str)]) -> Program {
+    let mut interner = Interner::default();
+    let mut world_index = hir::WorldIndex::default();
+    let mut analyses = Vec::new();
+
+    let std = (hir::STD_MODULE_NAME, hir::STD_SOURCE);
+    for &(module, content) in modules.iter().chain([&std]) {
+        let package =
+            module.split_once('.').map(|(package, _)| hir::Name(interner.intern(package)));
+        let uri = Url::parse(&format!("memory:///{module}.gb")).unwrap();
+
+        analyses.push(Analysis::new(
+            uri,
+            content.to_string(),
+            hir::Name(interner.intern(module)),
+            package,
+            &mut interner,
+            &mut world_index,
+        ));
+    }
+
+    // modules lowered before the ones they refer to were indexed have to be lowered again
+    for analysis in &mut analyses {
+        analysis.recheck(&mut world_index, &mut interner);
+    }
+
+    let mut program = Program {
+        interner,
+        world_index,
+        bodies_map: FxHashMap::default(),
+        tys_map: FxHashMap::default(),
+        diagnostics: FxHashMap::default(),
+    };
+
+    for analysis in analyses {
+        program.diagnostics.insert(analysis.module_name, analysis.diagnostics());
+        program.bodies_map.insert(analysis.module_name, analysis.bodies);
+        program.tys_map.insert(analysis.module_name, analysis.inference_result);
+    }
+
+    program
 }
 
 fn path_to_uri(path: &Path) -> Url {
@@ -149,6 +301,7 @@ impl Analysis {
         uri: Url,
         content: String,
         module_name: hir::Name,
+        package: Option<hir::Name>,
         interner: &mut Interner,
         world_index: &mut hir::WorldIndex,
     ) -> Self {
@@ -159,7 +312,8 @@ impl Analysis {
         let tree = parse.syntax_tree();
         let ast = ast::Root::cast(tree.root(), tree).unwrap();
         let (index, indexing_diagnostics) = hir::index(ast, tree, interner);
-        let (bodies, lowering_diagnostics) = hir::lower(ast, tree, &index, world_index, interner);
+        let (bodies, lowering_diagnostics) =
+            hir::lower(ast, tree, package, &index, world_index, interner);
         let (inference_result, ty_diagnostics) = hir_ty::infer_all(&bodies, &index, world_index);
 
         world_index.add_module(module_name, index.clone());
@@ -169,6 +323,7 @@ impl Analysis {
             content,
             line_index: LineIndex::default(),
             module_name,
+            package,
             parse,
             ast,
             validation_diagnostics: Vec::new(),
@@ -250,14 +405,33 @@ impl Analysis {
         let tree = self.parse.syntax_tree();
         let ident = ident_at_offset(tree, self.ast.syntax(), offset)?;
 
-        let (definition_range, name_range, file) = match self.bodies.symbol(ident)? {
+        let ty_path = match self.index.ty_ident_name(ident) {
+            Some(name) => Some(hir::Path::ThisModule(name)),
+            None => self.bodies.ty_ident_path(ident),
+        };
+
+        // types resolve like functions do, except that built-in types
+        // and generic parameters have no definition to go to
+        let symbol = match ty_path {
+            Some(hir::Path::ThisModule(name)) => {
+                self.index.get_definition(name)?;
+                hir::Symbol::Function(hir::Path::ThisModule(name))
+            }
+            Some(path) => hir::Symbol::Function(path),
+            None => self.bodies.symbol(ident)?,
+        };
+
+        let (definition_range, name_range, file) = match symbol {
             hir::Symbol::Local(local_def) => {
-                let local_def = self.bodies[local_def].ast;
-                (local_def.range(tree), local_def.name(tree)?.range(tree), self.uri.clone())
+                let local_def = &self.bodies[local_def];
+                (local_def.ast.range(tree), local_def.name?.range(tree), self.uri.clone())
             }
             hir::Symbol::Param(ast) => {
                 (ast.range(tree), ast.name(tree)?.range(tree), self.uri.clone())
             }
+            hir::Symbol::ReturnValue(ensures) => {
+                (ensures.range(tree), ensures.result_name(tree)?.range(tree), self.uri.clone())
+            }
             hir::Symbol::Function(path) => match path {
                 hir::Path::ThisModule(name) => {
                     let range_info = self.index.range_info(name);
@@ -303,41 +477,76 @@ impl Analysis {
             let mut modifiers = HighlightModifiers(0);
 
             let kind = match token.kind(self.parse.syntax_tree()) {
-                TokenKind::LetKw | TokenKind::FncKw => HighlightKind::Keyword,
+                TokenKind::LetKw
+                | TokenKind::FncKw
+                | TokenKind::ExtKw
+                | TokenKind::TestKw
+                | TokenKind::IfcKw
+                | TokenKind::ImpKw
+                | TokenKind::ForKw
+                | TokenKind::RequiresKw
+                | TokenKind::EnsuresKw => HighlightKind::Keyword,
                 TokenKind::Int => HighlightKind::Number,
                 TokenKind::Quote => HighlightKind::Quote,
                 TokenKind::Escape => HighlightKind::Escape,
                 TokenKind::StringContents => HighlightKind::String,
-                TokenKind::Plus | TokenKind::Hyphen | TokenKind::Asterisk | TokenKind::Slash => {
-                    HighlightKind::Operator
-                }
+                TokenKind::Plus
+                | TokenKind::Hyphen
+                | TokenKind::Asterisk
+                | TokenKind::Slash
+                | TokenKind::DoubleEq
+                | TokenKind::BangEq
+                | TokenKind::Less
+                | TokenKind::LessEq
+                | TokenKind::Greater
+                | TokenKind::GreaterEq => HighlightKind::Operator,
                 TokenKind::CommentContents => HighlightKind::CommentContents,
                 TokenKind::CommentLeader => HighlightKind::CommentLeader,
                 TokenKind::DocCommentContents => HighlightKind::DocCommentContents,
                 TokenKind::DocCommentLeader => HighlightKind::DocCommentLeader,
 
                 TokenKind::Ident => match parent_node_kinds[parent_node_kinds.len() - 1] {
-                    NodeKind::LocalDef => {
+                    NodeKind::LocalDef | NodeKind::IdentPat => {
                         modifiers |= HighlightModifier::Declaration;
                         HighlightKind::Local
                     }
+                    // the name of a field, which also declares a local in `Point { x }`
+                    NodeKind::RecordPatField => {
+                        let ident = ast::Ident::cast(token, self.parse.syntax_tree()).unwrap();
+                        match self.bodies.symbol(ident) {
+                            Some(hir::Symbol::Local(_)) => {
+                                modifiers |= HighlightModifier::Declaration;
+                                HighlightKind::Local
+                            }
+                            _ => continue,
+                        }
+                    }
                     NodeKind::Param => {
                         modifiers |= HighlightModifier::Declaration;
                         HighlightKind::Param
                     }
+                    // the name an `ensures` clause gives the return value
+                    NodeKind::Ensures => {
+                        modifiers |= HighlightModifier::Declaration;
+                        HighlightKind::Local
+                    }
                     NodeKind::Function => {
                         modifiers |= HighlightModifier::Declaration;
                         HighlightKind::Function
                     }
+                    // the name of a named argument
+                    NodeKind::Arg => HighlightKind::Param,
                     _ => {
                         let ident = ast::Ident::cast(token, self.parse.syntax_tree()).unwrap();
                         match self.bodies.symbol(ident) {
-                            Some(hir::Symbol::Local(_)) => HighlightKind::Local,
+                            Some(hir::Symbol::Local(_) | hir::Symbol::ReturnValue(_)) => {
+                                HighlightKind::Local
+                            }
                             Some(hir::Symbol::Param(_)) => HighlightKind::Param,
                             Some(hir::Symbol::Function(_)) => HighlightKind::Function,
                             Some(hir::Symbol::Module(_)) => HighlightKind::Module,
                             Some(hir::Symbol::Unknown) => HighlightKind::UnresolvedReference,
-                            None if self.index.is_ident_ty(ident) => HighlightKind::Ty,
+                            None if self.is_ident_ty(ident) => HighlightKind::Ty,
                             None => continue,
                         }
                     }
@@ -354,6 +563,12 @@ impl Analysis {
         tokens
     }
 
+    // types are named in signatures, which are indexed,
+    // and in local annotations, which are lowered with bodies
+    fn is_ident_ty(&self, ident: ast::Ident) -> bool {
+        self.index.is_ident_ty(ident) || self.bodies.is_ident_ty(ident)
+    }
+
     pub fn diagnostics(&self) -> Vec<Diagnostic> {
         let syntax_errors =
             self.parse.errors().iter().copied().map(diagnostics::Diagnostic::from_syntax);
@@ -403,8 +618,14 @@ impl Analysis {
     }
 
     fn lower(&mut self, world_index: &hir::WorldIndex, interner: &mut Interner) {
-        let (bodies, diagnostics) =
-            hir::lower(self.ast, self.parse.syntax_tree(), &self.index, world_index, interner);
+        let (bodies, diagnostics) = hir::lower(
+            self.ast,
+            self.parse.syntax_tree(),
+            self.package,
+            &self.index,
+            world_index,
+            interner,
+        );
         self.bodies = bodies;
         self.lowering_diagnostics = diagnostics;
     }
@@ -536,3 +757,56 @@ fn ident_at_offset(tree: &SyntaxTree, node: SyntaxNode, offset: TextSize) -> Opt
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn goto_local_record_ty() {
+        let source = "fnc main -> {
+    rec Point { x: s32 };
+    fnc x_of(p: Point): s32 -> {
+        let q: Point = p;
+        q.x
+    };
+};
+";
+        let root = utils::create_fixture(
+            "goto_local_record_ty",
+            &[("gb.toml", "[package]\nname = \"app\"\n"), ("main.gb", source)],
+        );
+
+        let mut global_state = GlobalState::default();
+        global_state.open_project(&root).unwrap();
+        let uri = path_to_uri(&root.join("main.gb"));
+
+        let record_start = source.find("rec Point").unwrap();
+        let param_ty = source.find("p: Point").unwrap() + "p: ".len();
+        let local_ty = source.find("q: Point").unwrap() + "q: ".len();
+
+        for offset in [param_ty, local_ty] {
+            let definition =
+                global_state.goto_definition(&uri, TextSize::from(offset as u32)).unwrap();
+            assert_eq!(definition.file, uri);
+            assert_eq!(u32::from(definition.definition_range.start()) as usize, record_start);
+            assert_eq!(&source[definition.name_range], "Point");
+        }
+
+        let builtin_ty = source.find("s32").unwrap();
+        assert!(global_state.goto_definition(&uri, TextSize::from(builtin_ty as u32)).is_none());
+    }
+
+    #[test]
+    fn change_file_outside_project() {
+        let root = utils::create_fixture("change_file_outside_project", &[("loose.gb", "")]);
+        let uri = path_to_uri(&root.join("loose.gb"));
+
+        let mut global_state = GlobalState::default();
+        assert!(global_state.open_file(uri.clone()).is_err());
+        assert!(!global_state.is_analyzed(&uri));
+
+        let updated = global_state.update_contents(&uri, |content, _| content.push_str("fnc"));
+        assert!(!updated);
+    }
+}
@@ -7,6 +7,7 @@ impl Default for Interner {
         let mut interner = Self(lasso::Rodeo::default());
         interner.intern("s32");
         interner.intern("string");
+        interner.intern("bool");
         interner
     }
 }
@@ -31,9 +32,12 @@ impl Key {
     pub fn string() -> Self {
         Self::from_raw(2)
     }
+    pub fn bool() -> Self {
+        Self::from_raw(3)
+    }
 
     pub fn from_raw(raw: u32) -> Self {
-        unsafe { Self(mem::transmute(raw)) }
+        unsafe { Self(mem::transmute::<u32, lasso::Spur>(raw)) }
     }
 
     pub fn to_raw(self) -> u32 {
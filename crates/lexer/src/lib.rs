@@ -22,7 +22,7 @@ pub fn lex(text: &str) -> Tokens {
             LexerTokenKind::__InternalString => lex_string(lexer.slice(), start, handler),
             LexerTokenKind::__InternalComment => lex_comment(start, range.len(), handler),
             LexerTokenKind::__InternalDocComment => lex_doc_comment(start, range.len(), handler),
-            _ => handler(unsafe { mem::transmute(kind) }, start),
+            _ => handler(unsafe { mem::transmute::<LexerTokenKind, TokenKind>(kind) }, start),
         }
     }
 
@@ -94,9 +94,33 @@ enum LexerTokenKind {
     #[token("rec")]
     RecKw,
 
+    #[token("ext")]
+    ExtKw,
+
+    #[token("test")]
+    TestKw,
+
+    #[token("ifc")]
+    IfcKw,
+
+    #[token("imp")]
+    ImpKw,
+
+    #[token("for")]
+    ForKw,
+
+    #[token("requires")]
+    RequiresKw,
+
+    #[token("ensures")]
+    EnsuresKw,
+
     #[regex("[a-zA-Z_]+[a-zA-Z0-9_]*")]
     Ident,
 
+    #[token("_")]
+    Underscore,
+
     #[regex("[0-9]+")]
     Int,
 
@@ -118,6 +142,24 @@ enum LexerTokenKind {
     #[token("/")]
     Slash,
 
+    #[token("==")]
+    DoubleEq,
+
+    #[token("!=")]
+    BangEq,
+
+    #[token("<")]
+    Less,
+
+    #[token("<=")]
+    LessEq,
+
+    #[token(">")]
+    Greater,
+
+    #[token(">=")]
+    GreaterEq,
+
     #[token("=")]
     Eq,
 
@@ -127,6 +169,9 @@ enum LexerTokenKind {
     #[token(":")]
     Colon,
 
+    #[token("?")]
+    Question,
+
     #[token(",")]
     Comma,
 
@@ -258,6 +303,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lex_ext_keyword() {
+        check(
+            "ext",
+            expect![[r#"
+                ExtKw@0..3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_test_keyword() {
+        check(
+            "test",
+            expect![[r#"
+                TestKw@0..4
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_ifc_keyword() {
+        check(
+            "ifc",
+            expect![[r#"
+                IfcKw@0..3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_imp_keyword() {
+        check(
+            "imp",
+            expect![[r#"
+                ImpKw@0..3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_for_keyword() {
+        check(
+            "for",
+            expect![[r#"
+                ForKw@0..3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_requires_keyword() {
+        check(
+            "requires",
+            expect![[r#"
+                RequiresKw@0..8
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_ensures_keyword() {
+        check(
+            "ensures",
+            expect![[r#"
+                EnsuresKw@0..7
+            "#]],
+        );
+    }
+
     #[test]
     fn lex_lowercase_alphabetic_ident() {
         check(
@@ -318,6 +433,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lex_underscore() {
+        check(
+            "_",
+            expect![[r#"
+                Underscore@0..1
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_ident_of_underscore_and_letter() {
+        check(
+            "_a",
+            expect![[r#"
+                Ident@0..2
+            "#]],
+        );
+    }
+
     #[test]
     fn lex_int() {
         check(
@@ -412,7 +547,7 @@ baz",
     #[test]
     fn lex_backslash_at_end_of_string() {
         check(
-            r#""\\" test"#,
+            r#""\\" text"#,
             expect![[r#"
                 Quote@0..1
                 Escape@1..3
@@ -483,6 +618,66 @@ baz",
         );
     }
 
+    #[test]
+    fn lex_double_eq() {
+        check(
+            "==",
+            expect![[r#"
+                DoubleEq@0..2
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_bang_eq() {
+        check(
+            "!=",
+            expect![[r#"
+                BangEq@0..2
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_less() {
+        check(
+            "<",
+            expect![[r#"
+                Less@0..1
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_less_eq() {
+        check(
+            "<=",
+            expect![[r#"
+                LessEq@0..2
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_greater() {
+        check(
+            ">",
+            expect![[r#"
+                Greater@0..1
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lex_greater_eq() {
+        check(
+            ">=",
+            expect![[r#"
+                GreaterEq@0..2
+            "#]],
+        );
+    }
+
     #[test]
     fn lex_eq() {
         check(
@@ -513,6 +708,16 @@ baz",
         );
     }
 
+    #[test]
+    fn lex_question() {
+        check(
+            "?",
+            expect![[r#"
+                Question@0..1
+            "#]],
+        );
+    }
+
     #[test]
     fn lex_comma() {
         check(
@@ -1,6 +1,7 @@
 mod def;
 mod expr;
 mod path;
+mod pattern;
 mod statement;
 mod ty;
 
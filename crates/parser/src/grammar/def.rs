@@ -1,13 +1,31 @@
 mod function;
+mod imp;
+mod interface;
 mod record;
+mod test;
 
 use self::function::parse_function;
+use self::imp::parse_impl;
+use self::interface::parse_interface;
 use self::record::parse_record;
+use self::test::parse_test;
 use crate::parser::{CompletedMarker, Parser};
 use crate::token_set::TokenSet;
 use syntax::{NodeKind, TokenKind};
 
-pub(super) const DEF_FIRST: TokenSet =
+pub(super) const DEF_FIRST: TokenSet = TokenSet::new([
+    TokenKind::FncKw,
+    TokenKind::ExtKw,
+    TokenKind::RecKw,
+    TokenKind::TestKw,
+    TokenKind::IfcKw,
+    TokenKind::ImpKw,
+    TokenKind::DocCommentLeader,
+]);
+
+// blocks still stop at any other definition,
+// which lets us recover from unclosed blocks at the top level
+pub(super) const LOCAL_DEF_FIRST: TokenSet =
     TokenSet::new([TokenKind::FncKw, TokenKind::RecKw, TokenKind::DocCommentLeader]);
 
 pub(super) fn parse_def(p: &mut Parser<'_>) -> Option<CompletedMarker> {
@@ -15,23 +33,64 @@ pub(super) fn parse_def(p: &mut Parser<'_>) -> Option<CompletedMarker> {
 
     let _guard = p.expected_syntax_name("definition");
 
-    if p.at(TokenKind::FncKw) {
+    if p.at(TokenKind::FncKw) || p.at(TokenKind::ExtKw) {
         let m = match docs_cm {
             Some(cm) => cm.precede(p),
             None => p.start(),
         };
-        return Some(parse_function(p, m));
+        return Some(parse_function(p, m, false));
     } else if p.at(TokenKind::RecKw) {
         let m = match docs_cm {
             Some(cm) => cm.precede(p),
             None => p.start(),
         };
         return Some(parse_record(p, m));
+    } else if p.at(TokenKind::TestKw) {
+        let m = match docs_cm {
+            Some(cm) => cm.precede(p),
+            None => p.start(),
+        };
+        return Some(parse_test(p, m));
+    } else if p.at(TokenKind::IfcKw) {
+        let m = match docs_cm {
+            Some(cm) => cm.precede(p),
+            None => p.start(),
+        };
+        return Some(parse_interface(p, m));
+    } else if p.at(TokenKind::ImpKw) {
+        let m = match docs_cm {
+            Some(cm) => cm.precede(p),
+            None => p.start(),
+        };
+        return Some(parse_impl(p, m));
     }
 
     p.error_with_recovery_set_no_default(TokenSet::default())
 }
 
+fn parse_function_block(p: &mut Parser<'_>, in_interface: bool) {
+    p.expect(TokenKind::LBrace);
+
+    while p.at(TokenKind::FncKw) || p.at(TokenKind::DocCommentLeader) {
+        let docs_cm = if p.at(TokenKind::DocCommentLeader) { Some(parse_docs(p)) } else { None };
+
+        if !p.at(TokenKind::FncKw) {
+            let _guard = p.expected_syntax_name("function");
+            p.error_with_no_skip();
+            continue;
+        }
+
+        let m = match docs_cm {
+            Some(cm) => cm.precede(p),
+            None => p.start(),
+        };
+        parse_function(p, m, in_interface);
+    }
+
+    p.expect(TokenKind::RBrace);
+    p.expect(TokenKind::Semicolon);
+}
+
 fn parse_docs(p: &mut Parser<'_>) -> CompletedMarker {
     assert!(p.at(TokenKind::DocCommentLeader));
     let m = p.start();
@@ -1,16 +1,30 @@
-use crate::grammar::expr::{parse_expr, EXPR_FIRST};
+use crate::grammar::expr::{parse_expr, parse_expr_with_recovery_set, EXPR_FIRST};
+use crate::grammar::path::parse_path;
 use crate::grammar::ty::parse_ty;
 use crate::parser::{CompletedMarker, Marker, Parser};
 use crate::token_set::TokenSet;
 use syntax::{NodeKind, TokenKind};
 
-pub(super) fn parse_function(p: &mut Parser<'_>, m: Marker) -> CompletedMarker {
-    assert!(p.at(TokenKind::FncKw));
-    p.bump();
+pub(super) fn parse_function(p: &mut Parser<'_>, m: Marker, in_interface: bool) -> CompletedMarker {
+    let is_extern = p.at(TokenKind::ExtKw);
+    if is_extern {
+        p.bump();
+        p.expect_with_recovery_set(TokenKind::FncKw, TokenSet::new([TokenKind::Ident]));
+    } else {
+        assert!(p.at(TokenKind::FncKw));
+        p.bump();
+    }
 
     {
         let _guard = p.expected_syntax_name("function name");
-        p.expect_with_recovery_set(TokenKind::Ident, TokenSet::new([TokenKind::LParen]));
+        p.expect_with_recovery_set(
+            TokenKind::Ident,
+            TokenSet::new([TokenKind::Less, TokenKind::LParen]),
+        );
+    }
+
+    if p.at(TokenKind::Less) {
+        parse_generic_param_list(p);
     }
 
     if p.at(TokenKind::LParen) {
@@ -21,13 +35,79 @@ pub(super) fn parse_function(p: &mut Parser<'_>, m: Marker) -> CompletedMarker {
         parse_return_ty(p);
     }
 
-    p.expect_with_recovery_set(TokenKind::Arrow, EXPR_FIRST);
-    parse_expr(p, "function body");
+    // extern functions are provided by the host and have no body,
+    // nor any contract to check around it
+    if !is_extern && !in_interface {
+        while p.at(TokenKind::RequiresKw) || p.at(TokenKind::EnsuresKw) {
+            if p.at(TokenKind::RequiresKw) {
+                parse_requires(p);
+            } else {
+                parse_ensures(p);
+            }
+        }
+
+        p.expect_with_recovery_set(TokenKind::Arrow, EXPR_FIRST);
+        parse_expr(p, "function body");
+    }
+
     p.expect(TokenKind::Semicolon);
 
     m.complete(p, NodeKind::Function)
 }
 
+fn parse_generic_param_list(p: &mut Parser<'_>) -> CompletedMarker {
+    assert!(p.at(TokenKind::Less));
+    let m = p.start();
+    p.bump();
+
+    while !p.at(TokenKind::Greater)
+        && !p.at(TokenKind::LParen)
+        && !p.at_eof()
+        && !p.at_default_recovery_set()
+    {
+        let m = p.start();
+
+        {
+            let _guard = p.expected_syntax_name("generic parameter name");
+            p.expect_with_recovery_set(
+                TokenKind::Ident,
+                TokenSet::new([TokenKind::Colon, TokenKind::Comma, TokenKind::Greater]),
+            );
+        }
+
+        // bounds look like `T: Describe + Compare`
+        if p.at(TokenKind::Colon) {
+            p.bump();
+
+            loop {
+                let _guard = p.expected_syntax_name("interface name");
+                parse_path(
+                    p,
+                    TokenSet::new([TokenKind::Plus, TokenKind::Comma, TokenKind::Greater]),
+                );
+
+                if !p.at(TokenKind::Plus) {
+                    break;
+                }
+                p.bump();
+            }
+        }
+
+        m.complete(p, NodeKind::GenericParam);
+
+        if !p.at(TokenKind::Greater) && !p.at(TokenKind::LParen) {
+            p.expect_with_recovery_set(
+                TokenKind::Comma,
+                TokenSet::new([TokenKind::Ident, TokenKind::Greater]),
+            );
+        }
+    }
+
+    p.expect_with_recovery_set(TokenKind::Greater, TokenSet::new([TokenKind::LParen]));
+
+    m.complete(p, NodeKind::GenericParamList)
+}
+
 fn parse_param_list(p: &mut Parser<'_>) -> CompletedMarker {
     assert!(p.at(TokenKind::LParen));
     let m = p.start();
@@ -70,12 +150,42 @@ fn parse_return_ty(p: &mut Parser<'_>) -> CompletedMarker {
 
     {
         let _guard = p.expected_syntax_name("return type");
-        parse_ty(p, TokenSet::new([TokenKind::Arrow]).union(EXPR_FIRST));
+        parse_ty(p, CONTRACT_RECOVERY_SET.union(EXPR_FIRST));
     }
 
     m.complete(p, NodeKind::ReturnTy)
 }
 
+const CONTRACT_FIRST: TokenSet = TokenSet::new([TokenKind::RequiresKw, TokenKind::EnsuresKw]);
+const CONTRACT_RECOVERY_SET: TokenSet = CONTRACT_FIRST.union(TokenSet::new([TokenKind::Arrow]));
+
+fn parse_requires(p: &mut Parser<'_>) -> CompletedMarker {
+    assert!(p.at(TokenKind::RequiresKw));
+    let m = p.start();
+    p.bump();
+
+    parse_expr_with_recovery_set(p, CONTRACT_RECOVERY_SET, "precondition");
+
+    m.complete(p, NodeKind::Requires)
+}
+
+fn parse_ensures(p: &mut Parser<'_>) -> CompletedMarker {
+    assert!(p.at(TokenKind::EnsuresKw));
+    let m = p.start();
+    p.bump();
+
+    // the return value can be given a name to refer to it by,
+    // as in `ensures quotient: quotient * b == a`
+    if p.at(TokenKind::Ident) && p.nth_at(1, TokenKind::Colon) {
+        p.bump();
+        p.expect(TokenKind::Colon);
+    }
+
+    parse_expr_with_recovery_set(p, CONTRACT_RECOVERY_SET, "postcondition");
+
+    m.complete(p, NodeKind::Ensures)
+}
+
 fn parse_param(p: &mut Parser<'_>) -> CompletedMarker {
     let m = p.start();
 
@@ -91,7 +201,16 @@ fn parse_param(p: &mut Parser<'_>) -> CompletedMarker {
 
     {
         let _guard = p.expected_syntax_name("parameter type");
-        parse_ty(p, TokenSet::new([TokenKind::Comma, TokenKind::RParen]));
+        parse_ty(p, TokenSet::new([TokenKind::Eq, TokenKind::Comma, TokenKind::RParen]));
+    }
+
+    if p.at(TokenKind::Eq) {
+        p.bump();
+        parse_expr_with_recovery_set(
+            p,
+            TokenSet::new([TokenKind::Comma, TokenKind::RParen]),
+            "default value",
+        );
     }
 
     m.complete(p, NodeKind::Param)
@@ -0,0 +1,27 @@
+use super::parse_function_block;
+use crate::grammar::path::parse_path;
+use crate::grammar::ty::parse_ty;
+use crate::parser::{CompletedMarker, Marker, Parser};
+use crate::token_set::TokenSet;
+use syntax::{NodeKind, TokenKind};
+
+pub(super) fn parse_impl(p: &mut Parser<'_>, m: Marker) -> CompletedMarker {
+    assert!(p.at(TokenKind::ImpKw));
+    p.bump();
+
+    {
+        let _guard = p.expected_syntax_name("interface name");
+        parse_path(p, TokenSet::new([TokenKind::ForKw, TokenKind::LBrace]));
+    }
+
+    p.expect_with_recovery_set(TokenKind::ForKw, TokenSet::new([TokenKind::Ident]));
+
+    {
+        let _guard = p.expected_syntax_name("type");
+        parse_ty(p, TokenSet::new([TokenKind::LBrace]));
+    }
+
+    parse_function_block(p, false);
+
+    m.complete(p, NodeKind::Impl)
+}
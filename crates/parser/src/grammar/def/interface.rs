@@ -0,0 +1,17 @@
+use super::parse_function_block;
+use crate::parser::{CompletedMarker, Marker, Parser};
+use syntax::{NodeKind, TokenKind};
+
+pub(super) fn parse_interface(p: &mut Parser<'_>, m: Marker) -> CompletedMarker {
+    assert!(p.at(TokenKind::IfcKw));
+    p.bump();
+
+    {
+        let _guard = p.expected_syntax_name("interface name");
+        p.expect(TokenKind::Ident);
+    }
+
+    parse_function_block(p, true);
+
+    m.complete(p, NodeKind::Interface)
+}
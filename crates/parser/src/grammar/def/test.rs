@@ -0,0 +1,23 @@
+use crate::grammar::expr::{parse_expr, parse_string_literal, EXPR_FIRST};
+use crate::parser::{CompletedMarker, Marker, Parser};
+use crate::token_set::TokenSet;
+use syntax::{NodeKind, TokenKind};
+
+pub(super) fn parse_test(p: &mut Parser<'_>, m: Marker) -> CompletedMarker {
+    assert!(p.at(TokenKind::TestKw));
+    p.bump();
+
+    if p.at(TokenKind::Quote) {
+        parse_string_literal(p);
+    } else {
+        let _guard = p.expected_syntax_name("test name");
+        p.error_with_recovery_set(TokenSet::new([TokenKind::Arrow]));
+    }
+
+    p.expect_with_recovery_set(TokenKind::Arrow, EXPR_FIRST);
+    parse_expr(p, "test body");
+
+    p.expect(TokenKind::Semicolon);
+
+    m.complete(p, NodeKind::Test)
+}
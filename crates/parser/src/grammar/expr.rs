@@ -1,12 +1,26 @@
-use crate::grammar::def::DEF_FIRST;
+use crate::grammar::def::{parse_def, DEF_FIRST, LOCAL_DEF_FIRST};
 use crate::grammar::path::parse_path;
 use crate::grammar::statement::parse_statement;
 use crate::parser::{CompletedMarker, Parser};
 use crate::token_set::TokenSet;
 use syntax::{NodeKind, TokenKind};
 
-pub(super) const EXPR_FIRST: TokenSet =
-    TokenSet::new([TokenKind::Ident, TokenKind::LBrace, TokenKind::Int, TokenKind::Quote]);
+pub(super) const EXPR_FIRST: TokenSet = TokenSet::new([
+    TokenKind::Ident,
+    TokenKind::LBrace,
+    TokenKind::Int,
+    TokenKind::Quote,
+    TokenKind::LParen,
+]);
+
+const COMPARISON_OPERATORS: TokenSet = TokenSet::new([
+    TokenKind::DoubleEq,
+    TokenKind::BangEq,
+    TokenKind::Less,
+    TokenKind::LessEq,
+    TokenKind::Greater,
+    TokenKind::GreaterEq,
+]);
 
 pub(super) fn parse_expr(
     p: &mut Parser<'_>,
@@ -15,7 +29,7 @@ pub(super) fn parse_expr(
     parse_expr_with_recovery_set(p, TokenSet::default(), expected_syntax_name)
 }
 
-fn parse_expr_with_recovery_set(
+pub(super) fn parse_expr_with_recovery_set(
     p: &mut Parser<'_>,
     recovery_set: TokenSet,
     expected_syntax_name: &'static str,
@@ -32,10 +46,12 @@ fn parse_expr_bp(
     let mut lhs = parse_lhs(p, recovery_set, expected_syntax_name)?;
 
     loop {
-        let (left_bp, right_bp) = if p.at(TokenKind::Plus) || p.at(TokenKind::Hyphen) {
+        let (left_bp, right_bp) = if p.at_set(COMPARISON_OPERATORS) {
             (1, 2)
-        } else if p.at(TokenKind::Asterisk) || p.at(TokenKind::Slash) {
+        } else if p.at(TokenKind::Plus) || p.at(TokenKind::Hyphen) {
             (3, 4)
+        } else if p.at(TokenKind::Asterisk) || p.at(TokenKind::Slash) {
+            (5, 6)
         } else {
             break;
         };
@@ -61,7 +77,7 @@ fn parse_lhs(
 ) -> Option<CompletedMarker> {
     let _guard = p.expected_syntax_name(expected_syntax_name);
 
-    let completed_marker = if p.at(TokenKind::Ident) {
+    let mut completed_marker = if p.at(TokenKind::Ident) {
         parse_call(p)
     } else if p.at(TokenKind::LBrace) {
         parse_block(p)
@@ -69,10 +85,35 @@ fn parse_lhs(
         parse_int_literal(p)
     } else if p.at(TokenKind::Quote) {
         parse_string_literal(p)
+    } else if p.at(TokenKind::LParen) {
+        parse_paren_expr(p)
     } else {
         return p.error_with_recovery_set(recovery_set);
     };
 
+    loop {
+        let m = if p.at(TokenKind::Question) || p.at(TokenKind::Dot) {
+            completed_marker.precede(p)
+        } else {
+            break;
+        };
+
+        // `value?` returns early if `value` is absent
+        if p.at(TokenKind::Question) {
+            p.bump();
+            completed_marker = m.complete(p, NodeKind::TryExpr);
+            continue;
+        }
+
+        // `pair.0` reads the first element of a tuple
+        p.bump();
+        {
+            let _guard = p.expected_syntax_name("tuple field");
+            p.expect_with_no_skip(TokenKind::Int);
+        }
+        completed_marker = m.complete(p, NodeKind::TupleFieldExpr);
+    }
+
     Some(completed_marker)
 }
 
@@ -109,6 +150,12 @@ fn parse_arg(p: &mut Parser<'_>) -> CompletedMarker {
     assert!(p.at_set(EXPR_FIRST));
     let m = p.start();
 
+    // named arguments look like `name: value`
+    if p.at(TokenKind::Ident) && p.nth_at(1, TokenKind::Colon) {
+        p.bump();
+        p.expect(TokenKind::Colon);
+    }
+
     parse_expr(p, "expression");
 
     m.complete(p, NodeKind::Arg)
@@ -119,8 +166,14 @@ fn parse_block(p: &mut Parser<'_>) -> CompletedMarker {
     let m = p.start();
     p.bump();
 
-    while !p.at(TokenKind::RBrace) && !p.at_set(DEF_FIRST) && !p.at_eof() {
-        parse_statement(p);
+    loop {
+        if p.at_set(LOCAL_DEF_FIRST) {
+            parse_def(p);
+        } else if !p.at(TokenKind::RBrace) && !p.at_set(DEF_FIRST) && !p.at_eof() {
+            parse_statement(p);
+        } else {
+            break;
+        }
     }
 
     p.expect(TokenKind::RBrace);
@@ -128,6 +181,32 @@ fn parse_block(p: &mut Parser<'_>) -> CompletedMarker {
     m.complete(p, NodeKind::Block)
 }
 
+fn parse_paren_expr(p: &mut Parser<'_>) -> CompletedMarker {
+    assert!(p.at(TokenKind::LParen));
+    let m = p.start();
+    p.bump();
+
+    let recovery_set = TokenSet::new([TokenKind::Comma, TokenKind::RParen]);
+    parse_expr_with_recovery_set(p, recovery_set, "expression");
+
+    // a comma turns the parentheses into a tuple, so `(a,)` has one element
+    let is_tuple = p.at(TokenKind::Comma);
+
+    while p.at(TokenKind::Comma) {
+        p.bump();
+
+        if p.at(TokenKind::RParen) {
+            break;
+        }
+
+        parse_expr_with_recovery_set(p, recovery_set, "expression");
+    }
+
+    p.expect(TokenKind::RParen);
+
+    m.complete(p, if is_tuple { NodeKind::TupleExpr } else { NodeKind::ParenExpr })
+}
+
 fn parse_int_literal(p: &mut Parser<'_>) -> CompletedMarker {
     assert!(p.at(TokenKind::Int));
     let m = p.start();
@@ -135,7 +214,7 @@ fn parse_int_literal(p: &mut Parser<'_>) -> CompletedMarker {
     m.complete(p, NodeKind::IntLiteral)
 }
 
-fn parse_string_literal(p: &mut Parser<'_>) -> CompletedMarker {
+pub(super) fn parse_string_literal(p: &mut Parser<'_>) -> CompletedMarker {
     assert!(p.at(TokenKind::Quote));
     let m = p.start();
     p.bump();
@@ -6,7 +6,13 @@ pub(super) fn parse_path(p: &mut Parser<'_>, recovery_set: TokenSet) -> Complete
     let m = p.start();
     p.expect_with_recovery_set(TokenKind::Ident, recovery_set);
 
-    if p.at(TokenKind::Dot) {
+    // paths have at most three segments: `package.module.name`,
+    // while a dot followed by a number accesses a tuple field
+    for _ in 0..2 {
+        if !p.at(TokenKind::Dot) || p.nth_at(1, TokenKind::Int) {
+            break;
+        }
+
         p.bump();
         p.expect_with_no_skip(TokenKind::Ident);
     }
@@ -0,0 +1,101 @@
+use crate::grammar::path::parse_path;
+use crate::parser::{CompletedMarker, Parser};
+use crate::token_set::TokenSet;
+use syntax::{NodeKind, TokenKind};
+
+pub(super) const PATTERN_FIRST: TokenSet =
+    TokenSet::new([TokenKind::Ident, TokenKind::Underscore, TokenKind::LParen]);
+
+pub(super) fn parse_pattern(p: &mut Parser<'_>, recovery_set: TokenSet) -> Option<CompletedMarker> {
+    let _guard = p.expected_syntax_name("pattern");
+
+    if p.at(TokenKind::Underscore) {
+        let m = p.start();
+        p.bump();
+        return Some(m.complete(p, NodeKind::WildcardPat));
+    }
+
+    if p.at(TokenKind::LParen) {
+        return Some(parse_tuple_pattern(p));
+    }
+
+    if !p.at(TokenKind::Ident) {
+        return p.error_with_recovery_set(recovery_set);
+    }
+
+    // anything other than a lone identifier names the record being destructured
+    if !p.nth_at(1, TokenKind::LBrace) && !p.nth_at(1, TokenKind::Dot) {
+        let m = p.start();
+        p.bump();
+        return Some(m.complete(p, NodeKind::IdentPat));
+    }
+
+    Some(parse_record_pattern(p))
+}
+
+fn parse_tuple_pattern(p: &mut Parser<'_>) -> CompletedMarker {
+    assert!(p.at(TokenKind::LParen));
+    let m = p.start();
+    p.bump();
+
+    while !p.at(TokenKind::RParen)
+        && !p.at(TokenKind::Eq)
+        && !p.at_eof()
+        && !p.at_default_recovery_set()
+    {
+        parse_pattern(p, TokenSet::new([TokenKind::Comma, TokenKind::RParen, TokenKind::Eq]));
+
+        if p.at(TokenKind::Comma) {
+            p.bump();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_with_recovery_set(TokenKind::RParen, TokenSet::new([TokenKind::Eq]));
+
+    m.complete(p, NodeKind::TuplePat)
+}
+
+fn parse_record_pattern(p: &mut Parser<'_>) -> CompletedMarker {
+    let m = p.start();
+    parse_path(p, TokenSet::new([TokenKind::LBrace]));
+
+    p.expect(TokenKind::LBrace);
+
+    // stopping at `=` lets us recover from an unclosed pattern in a local definition
+    while !p.at(TokenKind::RBrace)
+        && !p.at(TokenKind::Eq)
+        && !p.at_eof()
+        && !p.at_default_recovery_set()
+    {
+        let m = p.start();
+
+        {
+            let _guard = p.expected_syntax_name("field name");
+            p.expect_with_recovery_set(
+                TokenKind::Ident,
+                TokenSet::new([TokenKind::Colon, TokenKind::Comma]),
+            );
+        }
+
+        // `x` is short for `x: x`
+        if p.at(TokenKind::Colon) {
+            p.bump();
+            parse_pattern(p, TokenSet::new([TokenKind::Comma, TokenKind::RBrace]));
+        }
+
+        m.complete(p, NodeKind::RecordPatField);
+
+        if !p.at(TokenKind::RBrace) && !p.at(TokenKind::Eq) {
+            p.expect_with_recovery_set(
+                TokenKind::Comma,
+                TokenSet::new([TokenKind::Ident, TokenKind::Eq]),
+            );
+        }
+    }
+
+    p.expect_with_recovery_set(TokenKind::RBrace, TokenSet::new([TokenKind::Eq]));
+
+    m.complete(p, NodeKind::RecordPat)
+}
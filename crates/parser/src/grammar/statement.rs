@@ -1,4 +1,6 @@
 use crate::grammar::expr::parse_expr;
+use crate::grammar::pattern::{parse_pattern, PATTERN_FIRST};
+use crate::grammar::ty::parse_ty;
 use crate::parser::{CompletedMarker, Parser};
 use crate::token_set::TokenSet;
 use syntax::{NodeKind, TokenKind};
@@ -31,9 +33,24 @@ fn parse_local_def(p: &mut Parser<'_>) -> CompletedMarker {
     let m = p.start();
     p.bump();
 
-    {
+    let is_simple =
+        p.at(TokenKind::Ident) && !p.nth_at(1, TokenKind::LBrace) && !p.nth_at(1, TokenKind::Dot);
+
+    // plain variables are bound by the name on its own, without a pattern node
+    if !is_simple && p.at_set(PATTERN_FIRST) {
+        parse_pattern(p, TokenSet::new([TokenKind::Colon, TokenKind::Eq]));
+    } else {
         let _guard = p.expected_syntax_name("variable name");
-        p.expect_with_recovery_set(TokenKind::Ident, TokenSet::new([TokenKind::Eq]));
+        p.expect_with_recovery_set(
+            TokenKind::Ident,
+            TokenSet::new([TokenKind::Colon, TokenKind::Eq]),
+        );
+    }
+
+    if p.at(TokenKind::Colon) {
+        p.bump();
+        let _guard = p.expected_syntax_name("variable type");
+        parse_ty(p, TokenSet::new([TokenKind::Eq]));
     }
 
     p.expect(TokenKind::Eq);
@@ -1,10 +1,44 @@
 use super::path::parse_path;
 use crate::parser::{CompletedMarker, Parser};
 use crate::token_set::TokenSet;
-use syntax::NodeKind;
+use syntax::{NodeKind, TokenKind};
 
 pub(super) fn parse_ty(p: &mut Parser<'_>, recovery_set: TokenSet) -> CompletedMarker {
     let m = p.start();
-    parse_path(p, recovery_set);
+
+    if p.at(TokenKind::LParen) {
+        parse_tuple_ty(p, recovery_set);
+    } else {
+        parse_path(p, recovery_set);
+    }
+
+    // `s32?` may be absent
+    while p.at(TokenKind::Question) {
+        p.bump();
+    }
+
     m.complete(p, NodeKind::Ty)
 }
+
+fn parse_tuple_ty(p: &mut Parser<'_>, recovery_set: TokenSet) -> CompletedMarker {
+    assert!(p.at(TokenKind::LParen));
+    let m = p.start();
+    p.bump();
+
+    let element_recovery_set =
+        recovery_set.union(TokenSet::new([TokenKind::Comma, TokenKind::RParen]));
+
+    while !p.at(TokenKind::RParen) && !p.at_eof() && !p.at_default_recovery_set() {
+        parse_ty(p, element_recovery_set);
+
+        if p.at(TokenKind::Comma) {
+            p.bump();
+        } else {
+            break;
+        }
+    }
+
+    p.expect_with_recovery_set(TokenKind::RParen, recovery_set);
+
+    m.complete(p, NodeKind::TupleTy)
+}
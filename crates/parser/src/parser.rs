@@ -15,6 +15,10 @@ const DEFAULT_RECOVERY_SET: TokenSet = TokenSet::new([
     TokenKind::LetKw,
     TokenKind::FncKw,
     TokenKind::RecKw,
+    TokenKind::ExtKw,
+    TokenKind::TestKw,
+    TokenKind::IfcKw,
+    TokenKind::ImpKw,
     TokenKind::LBrace,
     TokenKind::RBrace,
     TokenKind::Semicolon,
@@ -152,6 +156,23 @@ impl<'tokens> Parser<'tokens> {
         self.at_raw(kind)
     }
 
+    pub(crate) fn nth_at(&mut self, n: usize, kind: TokenKind) -> bool {
+        self.skip_trivia();
+
+        let mut idx = self.token_idx;
+        for _ in 0..n {
+            idx += 1;
+            while let Some(
+                TokenKind::Whitespace | TokenKind::CommentLeader | TokenKind::CommentContents,
+            ) = self.tokens.get_kind(idx)
+            {
+                idx += 1;
+            }
+        }
+
+        self.tokens.get_kind(idx) == Some(kind)
+    }
+
     pub(crate) fn at_eof(&mut self) -> bool {
         self.skip_trivia();
         self.token_idx >= self.tokens.len()
@@ -163,7 +184,7 @@ impl<'tokens> Parser<'tokens> {
 
     pub(crate) fn at_set(&mut self, set: TokenSet) -> bool {
         self.skip_trivia();
-        self.peek().map_or(false, |kind| set.contains(kind))
+        self.peek().is_some_and(|kind| set.contains(kind))
     }
 
     pub(crate) fn bump(&mut self) {
@@ -198,7 +219,7 @@ impl<'tokens> Parser<'tokens> {
     }
 
     fn at_raw(&self, kind: TokenKind) -> bool {
-        self.peek().map_or(false, |k| k == kind)
+        self.peek() == Some(kind)
     }
 
     fn peek(&self) -> Option<TokenKind> {
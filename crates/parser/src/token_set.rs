@@ -5,7 +5,7 @@ use syntax::TokenKind;
 // This is a TokenSet containing the first and third variants of TokenKind
 // (regardless of what they may be):
 //
-//     0000000000000101
+//     0000000000000000000000000000000000000000000000000000000000000101
 //
 // Thus, the number of TokenKind variants must not exceed
 // the number of bits in TokenSet.
@@ -13,10 +13,10 @@ use syntax::TokenKind;
 // This implementation is mostly stolen from rust-analyzer:
 // https://github.com/rust-analyzer/rust-analyzer/blob/b73b321478d3b2a98d380eb79de717e01620c4e9/crates/parser/src/token_set.rs
 #[derive(Debug, Clone, Copy, Default)]
-pub(crate) struct TokenSet(u32);
+pub(crate) struct TokenSet(u64);
 
 impl TokenSet {
-    pub(crate) const ALL: Self = Self(u32::MAX);
+    pub(crate) const ALL: Self = Self(u64::MAX);
 
     pub(crate) const fn new<const LEN: usize>(kinds: [TokenKind; LEN]) -> Self {
         let mut value = 0;
@@ -39,8 +39,8 @@ impl TokenSet {
     }
 }
 
-const fn mask(kind: TokenKind) -> u32 {
-    1 << kind as u32
+const fn mask(kind: TokenKind) -> u64 {
+    1 << kind as u64
 }
 
 #[cfg(test)]
@@ -0,0 +1,769 @@
+use arena::Id;
+use ast::{AstNode, AstToken};
+use diagnostics::{Diagnostic, Severity};
+use hir_ty::ResolvedTy;
+use interner::Interner;
+use line_index::LineIndex;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::fs;
+use syntax::SyntaxTree;
+
+const MODULE_NAME: &str = "repl";
+
+// earlier lines are kept as the `repl` module, with `let` bindings turned into functions
+// returning the bound value, and each new line is evaluated by wrapping it in this function
+const LINE_FUNCTION: &str = "__repl_line";
+
+const HELP: &str = "\
+:type <expr>   show the type of an expression without evaluating it
+:load <file>   add the definitions in a file to the session
+:help          show this message
+:quit          leave the session";
+
+pub struct Session {
+    interner: Interner,
+    world_index: hir::WorldIndex,
+    std_name: hir::Name,
+    std_bodies: hir::Bodies,
+    std_tys: hir_ty::InferenceResult,
+    module_name: hir::Name,
+    items: Vec<Item>,
+}
+
+#[derive(Clone)]
+struct Item {
+    name: Option<String>,
+    source: String,
+}
+
+struct Module {
+    tree: SyntaxTree,
+    bodies: hir::Bodies,
+    tys: hir_ty::InferenceResult,
+}
+
+struct CheckedLine {
+    items: Vec<Item>,
+    statements: Vec<String>,
+    bindings: Vec<Binding>,
+    tail_expr: Option<(String, ResolvedTy)>,
+}
+
+struct Binding {
+    name: String,
+    ty: ResolvedTy,
+    local_def: String,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        // the module starts out empty, and is replaced every time a line is analyzed
+        let ide::Program { mut interner, world_index, mut bodies_map, mut tys_map, .. } =
+            ide::analyze_sources(&[(MODULE_NAME, "")]);
+
+        let std_name = hir::Name(interner.intern(hir::STD_MODULE_NAME));
+        let std_bodies = bodies_map.remove(&std_name).unwrap();
+        let std_tys = tys_map.remove(&std_name).unwrap();
+        let module_name = hir::Name(interner.intern(MODULE_NAME));
+
+        Self {
+            interner,
+            world_index,
+            std_name,
+            std_bodies,
+            std_tys,
+            module_name,
+            items: Vec::new(),
+        }
+    }
+}
+
+impl Session {
+    /// Runs a line of input or a command,
+    /// returning what should be shown in response (which may be nothing).
+    pub fn eval_line(&mut self, input: &str) -> String {
+        let input = input.trim();
+
+        match input.strip_prefix(':') {
+            Some(command) => self.command(command),
+            None => self.line(input),
+        }
+    }
+
+    fn command(&mut self, command: &str) -> String {
+        let (name, arg) = command.split_once(' ').unwrap_or((command, ""));
+        let arg = arg.trim();
+
+        match name {
+            "type" => self.ty(arg),
+            "load" => self.load(arg),
+            "help" => HELP.to_string(),
+            _ => format!("error: unknown command `:{name}` (see `:help`)"),
+        }
+    }
+
+    fn ty(&mut self, input: &str) -> String {
+        match self.check_line(input) {
+            Ok(CheckedLine { tail_expr: Some((_, ty)), .. }) => ty.display(&self.interner),
+            Ok(_) => "error: `:type` takes an expression".to_string(),
+            Err(errors) => errors,
+        }
+    }
+
+    fn load(&mut self, path: &str) -> String {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) => return format!("error: could not read `{path}`: {error}"),
+        };
+
+        let parse = parse(&content);
+        if !parse.errors().is_empty() {
+            let diagnostics: Vec<_> =
+                parse.errors().iter().copied().map(Diagnostic::from_syntax).collect();
+            return self.render(&diagnostics, &content);
+        }
+
+        let tree = parse.syntax_tree();
+        let root = ast::Root::cast(tree.root(), tree).unwrap();
+
+        let mut items = self.items.clone();
+        let mut count = 0;
+        for def in root.defs(tree) {
+            add_item(&mut items, Item::new(def, tree));
+            count += 1;
+        }
+
+        if let Err(errors) = self.analyze(module_source(&items, "")) {
+            return errors;
+        }
+
+        self.items = items;
+        let s = if count == 1 { "" } else { "s" };
+        format!("loaded {count} definition{s} from {path}")
+    }
+
+    fn line(&mut self, input: &str) -> String {
+        let CheckedLine { items, statements, mut bindings, tail_expr } =
+            match self.check_line(input) {
+                Ok(line) => line,
+                Err(errors) => return errors,
+            };
+
+        if statements.is_empty() && tail_expr.is_none() {
+            self.items = items;
+            return String::new();
+        }
+
+        // a name bound twice on the same line refers to the last binding afterwards
+        let mut seen = FxHashSet::default();
+        bindings.reverse();
+        bindings.retain(|binding| seen.insert(binding.name.clone()));
+        bindings.reverse();
+
+        // the function returns the values of every binding and of the expression
+        // which can be read back out of the module once it has run
+        let mut body = String::new();
+        for statement in &statements {
+            body.push_str(statement);
+            body.push('\n');
+        }
+
+        let mut results = Vec::new();
+        for binding in &bindings {
+            if is_readable(&binding.ty) {
+                results.push((binding.name.clone(), &binding.ty));
+            }
+        }
+
+        let shows_tail_expr = matches!(&tail_expr, Some((_, ty)) if is_readable(ty));
+        if let Some((text, ty)) = &tail_expr {
+            if shows_tail_expr {
+                results.push((text.clone(), ty));
+            } else {
+                body.push_str(text);
+                body.push_str(";\n");
+            }
+        }
+
+        let return_ty = match results.as_slice() {
+            [] => None,
+            [(expr, ty)] => {
+                body.push_str(expr);
+                Some(ty.display(&self.interner))
+            }
+            _ => {
+                let exprs: Vec<_> = results.iter().map(|(expr, _)| expr.as_str()).collect();
+                let tys: Vec<_> =
+                    results.iter().map(|(_, ty)| ty.display(&self.interner)).collect();
+                body.push_str(&format!("({})", exprs.join(", ")));
+                Some(format!("({})", tys.join(", ")))
+            }
+        };
+        body.push('\n');
+
+        let module = match self.analyze(module_source(&items, &line_function(return_ty, &body))) {
+            Ok(module) => module,
+            Err(errors) => return errors,
+        };
+
+        let val = match self.eval(module) {
+            Ok(val) => val,
+            Err(error) => return format!("error: {error}"),
+        };
+
+        let mut vals = match (results.len(), val) {
+            (0, _) => Vec::new(),
+            (1, val) => vec![val],
+            (_, eval::Val::Tuple(vals)) => vals,
+            (_, val) => unreachable!("line returned {val:?} instead of a tuple"),
+        }
+        .into_iter();
+
+        let mut output = Vec::new();
+
+        self.items = items;
+        for binding in bindings {
+            let val = if is_readable(&binding.ty) { vals.next() } else { None };
+            let ty = binding.ty.display(&self.interner);
+
+            // values which can’t be written out are computed again each time they’re used
+            let source = match val.as_ref().and_then(val_source) {
+                Some(value) => format!("fnc {}: {ty} -> {value};", binding.name),
+                None if binding.ty == ResolvedTy::Unit => {
+                    format!("fnc {0} -> {{\n    {1}\n    {0}\n}};", binding.name, binding.local_def)
+                }
+                None => format!(
+                    "fnc {0}: {ty} -> {{\n    {1}\n    {0}\n}};",
+                    binding.name, binding.local_def
+                ),
+            };
+
+            let mut items = self.items.clone();
+            add_item(&mut items, Item { name: Some(binding.name.clone()), source });
+            match self.analyze(module_source(&items, "")) {
+                Ok(_) => self.items = items,
+                Err(_) => output.push(format!("note: `{}` could not be kept", binding.name)),
+            }
+        }
+
+        if let Some((_, ty)) = tail_expr {
+            if shows_tail_expr {
+                let val = vals.next().unwrap();
+                output.insert(0, format!("{val}: {}", ty.display(&self.interner)));
+            } else if ty != ResolvedTy::Unit {
+                output.insert(0, format!("<{}>", ty.display(&self.interner)));
+            }
+        }
+
+        output.join("\n")
+    }
+
+    fn check_line(&mut self, input: &str) -> Result<CheckedLine, String> {
+        let tokens = lexer::lex(input);
+        let parse = parser::parse_repl_line(&tokens, input);
+        if !parse.errors().is_empty() {
+            let diagnostics: Vec<_> =
+                parse.errors().iter().copied().map(Diagnostic::from_syntax).collect();
+            return Err(self.render(&diagnostics, input));
+        }
+
+        let tree = parse.syntax_tree();
+        let root = ast::Root::cast(tree.root(), tree).unwrap();
+
+        let mut items = self.items.clone();
+        for def in root.defs(tree) {
+            add_item(&mut items, Item::new(def, tree));
+        }
+
+        let statements: Vec<_> = root.statements(tree).collect();
+        let tail_expr = root.tail_expr(tree);
+
+        if statements.is_empty() && tail_expr.is_none() {
+            self.analyze(module_source(&items, ""))?;
+            return Ok(CheckedLine {
+                items,
+                statements: Vec::new(),
+                bindings: Vec::new(),
+                tail_expr: None,
+            });
+        }
+
+        let mut body = String::new();
+        for statement in &statements {
+            body.push_str(statement.text(tree));
+            body.push('\n');
+        }
+        if let Some(tail_expr) = tail_expr {
+            body.push_str(tail_expr.text(tree));
+            body.push_str(";\n");
+        }
+
+        let module = self.analyze(module_source(&items, &line_function(None, &body)))?;
+
+        let line_function = hir::Name(self.interner.intern(LINE_FUNCTION));
+        let hir_statements = match &module.bodies[module.bodies.function_body(line_function)] {
+            hir::Expr::Block { statements, .. } => statements,
+            _ => unreachable!(),
+        };
+
+        let mut bindings = Vec::new();
+        for (statement, hir_statement) in statements.iter().zip(hir_statements) {
+            let (local_def, hir_local_def) = match (statement, &module.bodies[*hir_statement]) {
+                (ast::Statement::LocalDef(local_def), hir::Statement::LocalDef(hir_local_def)) => {
+                    (local_def, *hir_local_def)
+                }
+                _ => continue,
+            };
+
+            let mut local_defs = Vec::new();
+            collect_bindings(&module.bodies, hir_local_def, &mut local_defs);
+
+            for hir_local_def in local_defs {
+                let name = match module.bodies[hir_local_def].name {
+                    Some(name) => name.text(&module.tree).to_string(),
+                    None => continue,
+                };
+
+                bindings.push(Binding {
+                    name,
+                    ty: module.tys[hir_local_def].clone(),
+                    local_def: local_def.text(tree).to_string(),
+                });
+            }
+        }
+
+        let tail_expr = tail_expr.map(|tail_expr| {
+            let ty = match module.bodies[*hir_statements.last().unwrap()] {
+                hir::Statement::Expr(expr) => module.tys[expr].clone(),
+                hir::Statement::LocalDef(_) => unreachable!(),
+            };
+            (tail_expr.text(tree).to_string(), ty)
+        });
+
+        Ok(CheckedLine {
+            items,
+            statements: statements.iter().map(|s| s.text(tree).to_string()).collect(),
+            bindings,
+            tail_expr,
+        })
+    }
+
+    fn analyze(&mut self, source: String) -> Result<Module, String> {
+        let parse = parse(&source);
+        let mut diagnostics: Vec<_> =
+            parse.errors().iter().copied().map(Diagnostic::from_syntax).collect();
+
+        let tree = parse.into_syntax_tree();
+        let root = ast::Root::cast(tree.root(), &tree).unwrap();
+
+        let (index, d) = hir::index(root, &tree, &mut self.interner);
+        diagnostics.extend(d.into_iter().map(Diagnostic::from_indexing));
+        self.world_index.update_module(self.module_name, index.clone());
+
+        let (bodies, d) =
+            hir::lower(root, &tree, None, &index, &self.world_index, &mut self.interner);
+        diagnostics.extend(d.into_iter().map(Diagnostic::from_lowering));
+
+        let (tys, d) = hir_ty::infer_all(&bodies, &index, &self.world_index);
+        diagnostics.extend(d.into_iter().map(Diagnostic::from_ty));
+
+        if diagnostics.iter().any(|d| d.severity() == Severity::Error) {
+            return Err(self.render(&diagnostics, &source));
+        }
+
+        Ok(Module { tree, bodies, tys })
+    }
+
+    fn eval(&mut self, module: Module) -> Result<eval::Val, String> {
+        let mut bodies_map = FxHashMap::default();
+        bodies_map.insert(self.std_name, self.std_bodies.clone());
+        bodies_map.insert(self.module_name, module.bodies);
+
+        let mut tys_map = FxHashMap::default();
+        tys_map.insert(self.std_name, self.std_tys.clone());
+        tys_map.insert(self.module_name, module.tys);
+
+        let fqn = hir::Fqn {
+            module: self.module_name,
+            name: hir::Name(self.interner.intern(LINE_FUNCTION)),
+        };
+
+        eval::eval(
+            fqn,
+            bodies_map,
+            tys_map,
+            &self.world_index,
+            &self.interner,
+            &eval::HostFunctions::default(),
+            eval::CompileOptions::default(),
+        )
+        .map_err(|error| match error {
+            eval::EvalError::Panic { message, .. } => format!("panicked: {message}"),
+            eval::EvalError::ContractViolation { function, clause, .. } => {
+                format!("broke the contract of `{function}`: {clause} does not hold")
+            }
+            _ => error.to_string(),
+        })
+    }
+
+    fn render(&self, diagnostics: &[Diagnostic], source: &str) -> String {
+        let line_index = LineIndex::new(source);
+        let mut lines = Vec::new();
+
+        for diagnostic in diagnostics.iter().filter(|d| d.severity() == Severity::Error) {
+            lines.push(format!("error: {}", diagnostic.message(&self.interner)));
+
+            // the position is left out since it’s in the `repl` module rather than the input
+            let display = diagnostic.display(source, &self.interner, &line_index);
+            lines.extend(display.into_iter().skip(1));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl Item {
+    fn new(def: ast::Def, tree: &SyntaxTree) -> Self {
+        let name = match def {
+            ast::Def::Function(function) => function.name(tree),
+            ast::Def::Record(record) => record.name(tree),
+            ast::Def::Interface(interface) => interface.name(tree),
+            ast::Def::Test(_) | ast::Def::Impl(_) => None,
+        };
+
+        Self {
+            name: name.map(|name| name.text(tree).to_string()),
+            source: def.text(tree).to_string(),
+        }
+    }
+}
+
+fn add_item(items: &mut Vec<Item>, item: Item) {
+    if item.name.is_some() {
+        items.retain(|i| i.name != item.name);
+    }
+    items.push(item);
+}
+
+fn parse(source: &str) -> parser::Parse {
+    let tokens = lexer::lex(source);
+    parser::parse_source_file(&tokens, source)
+}
+
+fn module_source(items: &[Item], line_function: &str) -> String {
+    let mut source = String::new();
+    for item in items {
+        source.push_str(&item.source);
+        source.push('\n');
+    }
+    source.push_str(line_function);
+
+    source
+}
+
+fn line_function(return_ty: Option<String>, body: &str) -> String {
+    match return_ty {
+        Some(return_ty) => format!("fnc {LINE_FUNCTION}: {return_ty} -> {{\n{body}}};\n"),
+        None => format!("fnc {LINE_FUNCTION} -> {{\n{body}}};\n"),
+    }
+}
+
+fn collect_bindings(
+    bodies: &hir::Bodies,
+    local_def: Id<hir::LocalDef>,
+    local_defs: &mut Vec<Id<hir::LocalDef>>,
+) {
+    fn collect(
+        bodies: &hir::Bodies,
+        pattern: Id<hir::Pattern>,
+        local_defs: &mut Vec<Id<hir::LocalDef>>,
+    ) {
+        match &bodies[pattern] {
+            hir::Pattern::Missing | hir::Pattern::Wildcard => {}
+            hir::Pattern::Binding(local_def) => local_defs.push(*local_def),
+            hir::Pattern::Record { fields, .. } => {
+                for field in fields {
+                    collect(bodies, field.pattern, local_defs);
+                }
+            }
+            hir::Pattern::Tuple { elements } => {
+                for element in elements {
+                    collect(bodies, *element, local_defs);
+                }
+            }
+        }
+    }
+
+    match bodies[local_def].pattern {
+        Some(pattern) => collect(bodies, pattern, local_defs),
+        None => local_defs.push(local_def),
+    }
+}
+
+fn is_readable(ty: &ResolvedTy) -> bool {
+    match ty {
+        ResolvedTy::S32 | ResolvedTy::String | ResolvedTy::Bool => true,
+        ResolvedTy::Optional(ty) => is_readable(ty),
+        ResolvedTy::Tuple(tys) => tys.iter().all(is_readable),
+        ResolvedTy::Unknown | ResolvedTy::Named(_) | ResolvedTy::Unit | ResolvedTy::Generic(_) => {
+            false
+        }
+    }
+}
+
+fn val_source(val: &eval::Val) -> Option<String> {
+    match val {
+        eval::Val::S32(i32::MIN) => Some(format!("{{0 - {} - 1}}", i32::MAX)),
+        eval::Val::S32(n) if *n < 0 => Some(format!("{{0 - {}}}", -n)),
+        eval::Val::S32(n) => Some(n.to_string()),
+        eval::Val::String(s) => {
+            let mut source = String::from('"');
+            for c in s.chars() {
+                match c {
+                    '"' => source.push_str("\\\""),
+                    '\\' => source.push_str("\\\\"),
+                    '\n' => source.push_str("\\n"),
+                    '\r' => source.push_str("\\r"),
+                    '\t' => source.push_str("\\t"),
+                    _ => source.push(c),
+                }
+            }
+            source.push('"');
+            Some(source)
+        }
+        // there are no boolean literals
+        eval::Val::Bool(true) => Some("{0 == 0}".to_string()),
+        eval::Val::Bool(false) => Some("{0 == 1}".to_string()),
+        eval::Val::Some(val) => val_source(val),
+        eval::Val::Tuple(vals) => {
+            let vals: Option<Vec<_>> = vals.iter().map(val_source).collect();
+            let vals = vals?;
+            if vals.len() == 1 {
+                Some(format!("({},)", vals[0]))
+            } else {
+                Some(format!("({})", vals.join(", ")))
+            }
+        }
+        eval::Val::Nil | eval::Val::None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    fn check(lines: &[&str], expect: Expect) {
+        let mut session = Session::default();
+        let mut transcript = String::new();
+
+        for line in lines {
+            transcript.push_str(&format!("> {line}\n"));
+            let output = session.eval_line(line);
+            if !output.is_empty() {
+                transcript.push_str(&output);
+                transcript.push('\n');
+            }
+        }
+
+        expect.assert_eq(&transcript);
+    }
+
+    #[test]
+    fn expression() {
+        check(
+            &["1 + 2 * 3", r#""foo""#, "(1, \"a\")", "1 < 2"],
+            expect![[r#"
+                > 1 + 2 * 3
+                7: s32
+                > "foo"
+                "foo": string
+                > (1, "a")
+                (1, "a"): (s32, string)
+                > 1 < 2
+                true: bool
+            "#]],
+        );
+    }
+
+    #[test]
+    fn definitions_accumulate() {
+        check(
+            &[
+                "fnc double(n: s32): s32 -> n * 2;",
+                "fnc quadruple(n: s32): s32 -> double double n;",
+                "quadruple 3",
+            ],
+            expect![[r#"
+                > fnc double(n: s32): s32 -> n * 2;
+                > fnc quadruple(n: s32): s32 -> double double n;
+                > quadruple 3
+                12: s32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn redefine_function() {
+        check(
+            &["fnc x: s32 -> 1;", "x", "fnc x: s32 -> 2;", "x"],
+            expect![[r#"
+                > fnc x: s32 -> 1;
+                > x
+                1: s32
+                > fnc x: s32 -> 2;
+                > x
+                2: s32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn bindings_persist() {
+        check(
+            &["let x = 5;", "let (a, b) = (x * 2, \"b\");", "(x, a, b)", "let x = 0 - 1;", "x"],
+            expect![[r#"
+                > let x = 5;
+                > let (a, b) = (x * 2, "b");
+                > (x, a, b)
+                (5, 10, "b"): (s32, s32, string)
+                > let x = 0 - 1;
+                > x
+                -1: s32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn kept_values() {
+        check(
+            &[
+                "fnc f(s: string): string? -> s;",
+                r#"let (flag, s, o) = (1 > 2, "a\"b\n", f "c");"#,
+                "let min = 0 - 2147483647 - 1;",
+                "(flag, s, o, min)",
+            ],
+            expect![[r#"
+                > fnc f(s: string): string? -> s;
+                > let (flag, s, o) = (1 > 2, "a\"b\n", f "c");
+                > let min = 0 - 2147483647 - 1;
+                > (flag, s, o, min)
+                (false, "a\"b\n", "c", -2147483648): (bool, string, string?, s32)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn bindings_and_expression_on_one_line() {
+        check(
+            &["let a = 1; let b = a + 1; a + b", "b"],
+            expect![[r#"
+                > let a = 1; let b = a + 1; a + b
+                3: s32
+                > b
+                2: s32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn statements_without_output() {
+        check(
+            &["let u = {};", "u", "fnc id(n: s32): s32 -> n;", "id 1;"],
+            expect![[r#"
+                > let u = {};
+                > u
+                > fnc id(n: s32): s32 -> n;
+                > id 1;
+            "#]],
+        );
+    }
+
+    #[test]
+    fn type_command() {
+        check(
+            &[
+                ":type 1 + 1",
+                "fnc f(s: string): string? -> s;",
+                ":type ({f \"a\"}, 1 == 1)",
+                ":type let a = 1;",
+            ],
+            expect![[r#"
+                > :type 1 + 1
+                s32
+                > fnc f(s: string): string? -> s;
+                > :type ({f "a"}, 1 == 1)
+                (string?, bool)
+                > :type let a = 1;
+                error: `:type` takes an expression
+            "#]],
+        );
+    }
+
+    #[test]
+    fn errors_leave_session_unchanged() {
+        check(
+            &["let a = 1;", "let a = b;", "fnc f: s32 -> \"s\";", "f", "a +", "a"],
+            expect![[r#"
+                > let a = 1;
+                > let a = b;
+                error: undefined variable `b`
+                  let a = b;
+                          ^
+                > fnc f: s32 -> "s";
+                error: expected `s32` but found `string`
+                  fnc f: s32 -> "s";
+                                ^^^
+                > f
+                error: undefined variable `f`
+                  f;
+                  ^
+                > a +
+                error: missing operand
+                  a +
+                     ^
+                > a
+                1: s32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn runtime_errors() {
+        check(
+            &["fnc half(n: s32): s32 requires n > 0 -> n / 2;", "half 0", "1 / 0"],
+            expect![[r#"
+                > fnc half(n: s32): s32 requires n > 0 -> n / 2;
+                > half 0
+                error: broke the contract of `half`: precondition does not hold
+                > 1 / 0
+                error: program aborted: wasm trap: integer divide by zero
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unknown_command() {
+        check(
+            &[":frobnicate"],
+            expect![[r#"
+                > :frobnicate
+                error: unknown command `:frobnicate` (see `:help`)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn load() {
+        let path = std::env::temp_dir().join(format!("repl_load_{}.gb", std::process::id()));
+        fs::write(&path, "fnc one: s32 -> 1;\n\nfnc two: s32 -> one + one;\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut session = Session::default();
+        let loaded = session.eval_line(&format!(":load {path}"));
+        assert_eq!(loaded, format!("loaded 2 definitions from {path}"));
+        assert_eq!(session.eval_line("two"), "2: s32");
+
+        fs::remove_file(path).unwrap();
+    }
+}
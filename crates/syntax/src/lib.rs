@@ -20,7 +20,15 @@ pub enum TokenKind {
     LetKw,
     FncKw,
     RecKw,
+    ExtKw,
+    TestKw,
+    IfcKw,
+    ImpKw,
+    ForKw,
+    RequiresKw,
+    EnsuresKw,
     Ident,
+    Underscore,
     Int,
     Quote,
     Escape,
@@ -29,9 +37,16 @@ pub enum TokenKind {
     Hyphen,
     Asterisk,
     Slash,
+    DoubleEq,
+    BangEq,
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
     Eq,
     Dot,
     Colon,
+    Question,
     Comma,
     Semicolon,
     Arrow,
@@ -57,15 +72,32 @@ pub enum NodeKind {
     IntLiteral,
     StringLiteral,
     BinaryExpr,
+    ParenExpr,
+    TupleExpr,
+    TryExpr,
+    TupleFieldExpr,
     LocalDef,
     ExprStatement,
     Function,
+    GenericParamList,
+    GenericParam,
     ParamList,
     Param,
     ReturnTy,
+    Requires,
+    Ensures,
     Ty,
+    TupleTy,
     Record,
     Field,
+    Test,
+    Interface,
+    Impl,
+    IdentPat,
+    WildcardPat,
+    RecordPat,
+    RecordPatField,
+    TuplePat,
     Path,
     Comment,
     DocComment,
@@ -1,4 +1,6 @@
 use rustc_hash::FxHashMap;
+use std::fs;
+use std::path::PathBuf;
 
 pub fn split_multi_module_test_data(input: &str) -> FxHashMap<&str, &str> {
     const MARKER_COMMENT_START: &str = "#- ";
@@ -36,3 +38,17 @@ pub fn split_multi_module_test_data(input: &str) -> FxHashMap<&str, &str> {
 
     module_idxs.into_iter().map(|(module_name, range)| (module_name, &input[range])).collect()
 }
+
+// writes the files to a directory of their own, replacing whatever an earlier run left there
+pub fn create_fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("gb-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+
+    for (path, contents) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    root
+}